@@ -0,0 +1,403 @@
+use crate::models::runner::Runner;
+use crate::query::Query;
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How long resolved alerts stay listed
+const RESOLVED_RETENTION_SECS: i64 = 3600;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    #[default]
+    Warning,
+    Critical,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+/// One `[[alerts]]` entry of `config.toml`. `selector` and `condition` are
+/// filter expressions, as accepted by `--where`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlertRule {
+    pub name: String,
+    /// Runners the rule applies to; every runner when omitted
+    #[serde(default)]
+    pub selector: Option<String>,
+    /// The alert is active for a selected runner while this holds
+    pub condition: String,
+    #[serde(default)]
+    pub severity: Severity,
+    /// How long the condition must hold before the alert fires
+    #[serde(default)]
+    pub for_secs: u64,
+}
+
+/// A rule with its expressions parsed
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    name: String,
+    severity: Severity,
+    selector: Option<Query>,
+    condition: Query,
+    hold: Duration,
+}
+
+impl CompiledRule {
+    fn new(rule: &AlertRule) -> Result<Self> {
+        if rule.name.trim().is_empty() {
+            bail!("a rule needs a name");
+        }
+        let selector = rule
+            .selector
+            .as_deref()
+            .map(Query::parse)
+            .transpose()
+            .map_err(|e| anyhow!("selector: {}", e))?;
+        let condition = Query::parse(&rule.condition).map_err(|e| anyhow!("condition: {}", e))?;
+        Ok(Self {
+            name: rule.name.clone(),
+            severity: rule.severity,
+            selector,
+            condition,
+            hold: Duration::seconds(rule.for_secs as i64),
+        })
+    }
+
+    fn is_active(&self, runner: &Runner, now: DateTime<Utc>) -> bool {
+        self.selector
+            .as_ref()
+            .is_none_or(|s| s.matches_runner_at(runner, now))
+            && self.condition.matches_runner_at(runner, now)
+    }
+}
+
+/// Declared in display order: firing alerts are listed first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AlertState {
+    Firing,
+    /// Condition holds, but not yet for the rule's `for_secs`
+    Pending,
+    Resolved,
+}
+
+impl fmt::Display for AlertState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlertState::Firing => write!(f, "firing"),
+            AlertState::Pending => write!(f, "pending"),
+            AlertState::Resolved => write!(f, "resolved"),
+        }
+    }
+}
+
+/// One rule's alert for one runner
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    pub rule: String,
+    pub severity: Severity,
+    pub runner_id: u64,
+    pub description: Option<String>,
+    pub state: AlertState,
+    /// When the condition started holding
+    pub since: DateTime<Utc>,
+    /// When the alert fired or resolved; `since` while pending
+    pub changed_at: DateTime<Utc>,
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[{}] {}: Runner {}",
+            self.severity, self.rule, self.runner_id
+        )?;
+        if let Some(description) = &self.description {
+            write!(f, " ({})", description)?;
+        }
+        write!(f, " {}", self.state)
+    }
+}
+
+/// Evaluates the configured rules against every poll and tracks each
+/// alert through pending, firing and resolved
+#[derive(Debug, Default)]
+pub struct AlertEngine {
+    rules: Vec<CompiledRule>,
+    alerts: Vec<Alert>,
+}
+
+impl AlertEngine {
+    pub fn new(rules: &[AlertRule]) -> Result<Self> {
+        let mut compiled: Vec<CompiledRule> = Vec::new();
+        for (i, rule) in rules.iter().enumerate() {
+            let rule = CompiledRule::new(rule).map_err(|e| anyhow!("alerts[{}]: {}", i, e))?;
+            if compiled.iter().any(|r| r.name == rule.name) {
+                bail!("alerts[{}]: duplicate rule name '{}'", i, rule.name);
+            }
+            compiled.push(rule);
+        }
+        Ok(Self {
+            rules: compiled,
+            alerts: Vec::new(),
+        })
+    }
+
+    /// Switch to new rules. Alerts of rules that are still defined carry on
+    /// (with the rule's new severity); the others are dropped.
+    pub fn reload(&mut self, rules: &[AlertRule]) -> Result<()> {
+        let next = AlertEngine::new(rules)?;
+        self.alerts.retain_mut(|alert| {
+            let Some(rule) = next.rules.iter().find(|r| r.name == alert.rule) else {
+                return false;
+            };
+            alert.severity = rule.severity;
+            true
+        });
+        self.rules = next.rules;
+        Ok(())
+    }
+
+    pub fn has_rules(&self) -> bool {
+        !self.rules.is_empty()
+    }
+
+    /// Evaluate a poll and return the alerts that fired or resolved in it.
+    /// Alerts for runners missing from the poll (filtered out, say) are
+    /// left as they were.
+    pub fn evaluate(&mut self, runners: &[Runner], now: DateTime<Utc>) -> Vec<Alert> {
+        let mut changed = Vec::new();
+        let mut next = Vec::new();
+
+        for rule in &self.rules {
+            let active: Vec<&Runner> = runners.iter().filter(|r| rule.is_active(r, now)).collect();
+            for runner in &active {
+                let previous = self
+                    .alerts
+                    .iter()
+                    .find(|a| a.rule == rule.name && a.runner_id == runner.id)
+                    .filter(|a| a.state != AlertState::Resolved);
+                let mut alert = match previous {
+                    Some(alert) => alert.clone(),
+                    None => Alert {
+                        rule: rule.name.clone(),
+                        severity: rule.severity,
+                        runner_id: runner.id,
+                        description: None,
+                        state: AlertState::Pending,
+                        since: now,
+                        changed_at: now,
+                    },
+                };
+                alert.description = runner.description.clone();
+                if alert.state == AlertState::Pending && now - alert.since >= rule.hold {
+                    alert.state = AlertState::Firing;
+                    alert.changed_at = now;
+                    changed.push(alert.clone());
+                }
+                next.push(alert);
+            }
+
+            let retained_since = now - Duration::seconds(RESOLVED_RETENTION_SECS);
+            for alert in self.alerts.iter().filter(|a| a.rule == rule.name) {
+                if active.iter().any(|r| r.id == alert.runner_id) {
+                    continue;
+                }
+                let seen = runners.iter().any(|r| r.id == alert.runner_id);
+                match alert.state {
+                    AlertState::Firing if seen => {
+                        let mut resolved = alert.clone();
+                        resolved.state = AlertState::Resolved;
+                        resolved.changed_at = now;
+                        changed.push(resolved.clone());
+                        next.push(resolved);
+                    }
+                    // A pending alert that clears never fired
+                    AlertState::Pending if seen => {}
+                    AlertState::Resolved if alert.changed_at < retained_since => {}
+                    _ => next.push(alert.clone()),
+                }
+            }
+        }
+
+        next.sort_by(|a, b| {
+            (a.state, b.severity, b.changed_at).cmp(&(b.state, a.severity, a.changed_at))
+        });
+        self.alerts = next;
+        changed
+    }
+
+    /// Firing alerts first (most severe first), then pending, then resolved
+    pub fn alerts(&self) -> &[Alert] {
+        &self.alerts
+    }
+
+    pub fn firing(&self) -> usize {
+        self.alerts
+            .iter()
+            .filter(|a| a.state == AlertState::Firing)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::manager::RunnerManager;
+    use crate::models::test_support;
+
+    fn runner(id: u64, online: bool, tags: &[&str]) -> Runner {
+        Runner {
+            status: test_support::status(online),
+            tag_list: test_support::tags(tags),
+            managers: vec![RunnerManager {
+                status: test_support::status(online),
+                ..test_support::manager(id, &format!("m{}", id))
+            }],
+            ..test_support::runner(id)
+        }
+    }
+
+    fn rule(name: &str, selector: Option<&str>, condition: &str, for_secs: u64) -> AlertRule {
+        AlertRule {
+            name: name.to_string(),
+            selector: selector.map(String::from),
+            condition: condition.to_string(),
+            severity: Severity::Critical,
+            for_secs,
+        }
+    }
+
+    fn at(minute: i64) -> DateTime<Utc> {
+        "2024-06-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap() + Duration::minutes(minute)
+    }
+
+    fn states(engine: &AlertEngine) -> Vec<(u64, AlertState)> {
+        engine
+            .alerts()
+            .iter()
+            .map(|a| (a.runner_id, a.state))
+            .collect()
+    }
+
+    #[test]
+    fn test_alert_fires_after_hold_and_resolves() {
+        let rules = [rule(
+            "prod-down",
+            Some("tags contains prod"),
+            "status = offline",
+            300,
+        )];
+        let mut engine = AlertEngine::new(&rules).unwrap();
+        let fleet = |down: bool| vec![runner(1, !down, &["prod"]), runner(2, false, &["dev"])];
+
+        assert!(engine.evaluate(&fleet(true), at(0)).is_empty());
+        assert_eq!(states(&engine), vec![(1, AlertState::Pending)]);
+        assert!(engine.evaluate(&fleet(true), at(4)).is_empty());
+
+        let fired = engine.evaluate(&fleet(true), at(5));
+        assert_eq!(fired.len(), 1);
+        assert_eq!(
+            fired[0].to_string(),
+            "[critical] prod-down: Runner 1 (runner-1) firing"
+        );
+        assert_eq!(engine.firing(), 1);
+
+        let resolved = engine.evaluate(&fleet(false), at(6));
+        assert_eq!(resolved[0].state, AlertState::Resolved);
+        assert_eq!(states(&engine), vec![(1, AlertState::Resolved)]);
+
+        // Resolved alerts age out
+        engine.evaluate(&fleet(false), at(70));
+        assert!(engine.alerts().is_empty());
+    }
+
+    #[test]
+    fn test_pending_alert_that_clears_never_fires() {
+        let rules = [rule("down", None, "status = offline", 600)];
+        let mut engine = AlertEngine::new(&rules).unwrap();
+        engine.evaluate(&[runner(1, false, &[])], at(0));
+        assert!(engine.evaluate(&[runner(1, true, &[])], at(1)).is_empty());
+        assert!(engine.alerts().is_empty());
+
+        // Without a hold the alert fires on the first poll
+        let rules = [rule("down", None, "status = offline", 0)];
+        let mut engine = AlertEngine::new(&rules).unwrap();
+        assert_eq!(engine.evaluate(&[runner(1, false, &[])], at(0)).len(), 1);
+    }
+
+    #[test]
+    fn test_runners_missing_from_a_poll_keep_their_alerts() {
+        let rules = [rule("down", None, "status = offline", 0)];
+        let mut engine = AlertEngine::new(&rules).unwrap();
+        engine.evaluate(&[runner(1, false, &[]), runner(2, true, &[])], at(0));
+        assert_eq!(engine.firing(), 1);
+
+        // A narrower filter hides runner 1: nothing resolves
+        assert!(engine.evaluate(&[runner(2, true, &[])], at(1)).is_empty());
+        assert_eq!(states(&engine), vec![(1, AlertState::Firing)]);
+
+        let resolved = engine.evaluate(&[runner(1, true, &[])], at(2));
+        assert_eq!(resolved[0].state, AlertState::Resolved);
+    }
+
+    #[test]
+    fn test_uncontacted_is_measured_from_the_poll_time() {
+        let rules = [rule("silent", None, "uncontacted > 600", 0)];
+        let mut engine = AlertEngine::new(&rules).unwrap();
+        let mut silent = runner(1, true, &[]);
+        silent.managers[0].contacted_at = Some(at(0));
+
+        assert!(engine.evaluate(&[silent.clone()], at(5)).is_empty());
+        assert_eq!(engine.evaluate(&[silent], at(11)).len(), 1);
+    }
+
+    #[test]
+    fn test_reload_keeps_alerts_of_remaining_rules() {
+        let rules = [
+            rule("down", None, "status = offline", 0),
+            rule("dev", None, "tags contains dev", 0),
+        ];
+        let mut engine = AlertEngine::new(&rules).unwrap();
+        engine.evaluate(&[runner(1, false, &["dev"])], at(0));
+        assert_eq!(engine.firing(), 2);
+
+        let mut down = rule("down", None, "status = offline", 0);
+        down.severity = Severity::Info;
+        engine.reload(&[down]).unwrap();
+        assert_eq!(engine.alerts().len(), 1);
+        assert_eq!(engine.alerts()[0].severity, Severity::Info);
+        // Still firing: no new transition
+        assert!(engine.evaluate(&[runner(1, false, &[])], at(1)).is_empty());
+
+        assert!(engine.reload(&[rule("bad", None, "nope", 0)]).is_err());
+        assert_eq!(engine.alerts().len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_rules_are_rejected() {
+        let err = AlertEngine::new(&[rule("bad", None, "colour = red", 0)]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "alerts[0]: condition: unknown field 'colour' at position 1"
+        );
+        let err = AlertEngine::new(&[rule("a", Some("tags ~ '['"), "paused", 0)]).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("alerts[0]: selector: invalid regex"));
+        let duplicate = [rule("a", None, "paused", 0), rule("a", None, "paused", 0)];
+        assert!(AlertEngine::new(&duplicate).is_err());
+    }
+}
@@ -0,0 +1,360 @@
+use crate::models::coverage::{normalize_tag_set, TagSetCoverage};
+use crate::models::runner::Runner;
+use anyhow::{bail, Context, Result};
+use serde_yaml::{Mapping, Value};
+use std::path::Path;
+
+/// Top-level keys of `.gitlab-ci.yml` that configure the pipeline rather
+/// than define a job
+const RESERVED_KEYS: &[&str] = &[
+    "default",
+    "include",
+    "stages",
+    "variables",
+    "workflow",
+    "image",
+    "services",
+    "cache",
+    "before_script",
+    "after_script",
+    "spec",
+];
+
+/// Deepest `extends` chain GitLab accepts
+const MAX_EXTENDS_DEPTH: usize = 11;
+
+/// A job from `.gitlab-ci.yml` and the runner tags it requires
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CiJob {
+    pub name: String,
+    pub tags: Vec<String>,
+}
+
+/// String entries of a `tags:` list; anything else (e.g. `!reference`) is skipped
+fn tag_list(value: &Value) -> Vec<String> {
+    let tags: Vec<String> = value
+        .as_sequence()
+        .into_iter()
+        .flatten()
+        .filter_map(|t| t.as_str().map(String::from))
+        .collect();
+    normalize_tag_set(&tags)
+}
+
+/// Merge `over` into `base` the way `extends` does: mappings merge key by
+/// key, anything else (tag lists included) replaces the inherited value
+fn deep_merge(base: &mut Mapping, over: &Mapping) {
+    for (key, value) in over {
+        match (base.get_mut(key), value) {
+            (Some(Value::Mapping(base)), Value::Mapping(over)) => deep_merge(base, over),
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// A job's definition with its `extends` chain merged in, parents first
+fn resolve_extends(root: &Mapping, chain: &mut Vec<String>) -> Result<Mapping> {
+    let name = chain.last().cloned().unwrap_or_default();
+    let Some(Value::Mapping(job)) = root.get(name.as_str()) else {
+        bail!("'{}' is not defined", name);
+    };
+
+    let parents: Vec<&str> = match job.get("extends") {
+        Some(Value::String(parent)) => vec![parent.as_str()],
+        Some(Value::Sequence(parents)) => parents.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+
+    let mut resolved = Mapping::new();
+    for parent in parents {
+        if chain.iter().any(|c| c == parent) {
+            bail!("extends loop: {} → {}", chain.join(" → "), parent);
+        }
+        if chain.len() > MAX_EXTENDS_DEPTH {
+            bail!("extends nests deeper than {} levels", MAX_EXTENDS_DEPTH);
+        }
+        chain.push(parent.to_string());
+        let inherited = resolve_extends(root, chain)
+            .with_context(|| format!("'{}' extends '{}'", name, parent))?;
+        chain.pop();
+        deep_merge(&mut resolved, &inherited);
+    }
+    deep_merge(&mut resolved, job);
+    resolved.remove("extends");
+    Ok(resolved)
+}
+
+/// Whether a job takes `default:tags`; `inherit:default` can opt out
+/// entirely or list the keys to keep
+fn inherits_default_tags(job: &Mapping) -> bool {
+    match job.get("inherit").and_then(|i| i.get("default")) {
+        Some(Value::Bool(inherit)) => *inherit,
+        Some(Value::Sequence(keys)) => keys.iter().any(|k| k.as_str() == Some("tags")),
+        _ => true,
+    }
+}
+
+/// Jobs defined in a `.gitlab-ci.yml`, in file order, with `extends`
+/// resolved. Hidden jobs (`.name`) are templates and are skipped, as are
+/// trigger jobs, which GitLab runs itself; jobs without `tags` inherit
+/// `default:tags`.
+pub fn parse_ci_jobs(yaml: &str) -> Result<Vec<CiJob>> {
+    let doc: Value = serde_yaml::from_str(yaml).context("Failed to parse CI YAML")?;
+    let root = match doc {
+        Value::Mapping(root) => root,
+        Value::Null => return Ok(Vec::new()),
+        _ => bail!("expected a mapping of jobs at the top level"),
+    };
+
+    let default_tags = root
+        .get("default")
+        .and_then(|d| d.get("tags"))
+        .map(tag_list)
+        .unwrap_or_default();
+
+    let mut jobs = Vec::new();
+    for (key, value) in &root {
+        let Some(name) = key.as_str() else {
+            continue;
+        };
+        if name.starts_with('.') || RESERVED_KEYS.contains(&name) || !value.is_mapping() {
+            continue;
+        }
+
+        let job = resolve_extends(&root, &mut vec![name.to_string()])
+            .with_context(|| format!("job '{}'", name))?;
+        if job.contains_key("trigger") {
+            continue;
+        }
+        let tags = match job.get("tags") {
+            Some(tags) => tag_list(tags),
+            None if inherits_default_tags(&job) => default_tags.clone(),
+            None => Vec::new(),
+        };
+        jobs.push(CiJob {
+            name: name.to_string(),
+            tags,
+        });
+    }
+    Ok(jobs)
+}
+
+pub fn load_ci_jobs(path: &Path) -> Result<Vec<CiJob>> {
+    let yaml = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    parse_ci_jobs(&yaml).with_context(|| format!("Invalid CI file {}", path.display()))
+}
+
+/// Distinct tag sets to check: the given sets first, then those required by
+/// the jobs in `ci_file`. Untagged jobs add no set; finding no sets at all
+/// is an error.
+pub fn required_tag_sets(sets: &[Vec<String>], ci_file: Option<&Path>) -> Result<Vec<Vec<String>>> {
+    let mut required: Vec<Vec<String>> = Vec::new();
+    let mut add = |tags: Vec<String>| {
+        if !tags.is_empty() && !required.contains(&tags) {
+            required.push(tags);
+        }
+    };
+
+    for set in sets {
+        add(normalize_tag_set(set));
+    }
+    if let Some(path) = ci_file {
+        for job in load_ci_jobs(path)? {
+            add(job.tags);
+        }
+    }
+    if required.is_empty() {
+        bail!(
+            "no tag sets to check: pass --set or --ci-file, or set required_tag_sets \
+             or ci_file in config.toml"
+        );
+    }
+    Ok(required)
+}
+
+/// Jobs in the pipeline's CI file; the command needs one
+pub fn pipeline_jobs(ci_file: Option<&Path>) -> Result<Vec<CiJob>> {
+    match ci_file {
+        Some(path) => load_ci_jobs(path),
+        None => bail!("no CI file to check: pass --ci-file or set ci_file in config.toml"),
+    }
+}
+
+/// A CI job and the runners that could pick it up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobCoverage {
+    pub name: String,
+    pub coverage: TagSetCoverage,
+}
+
+impl JobCoverage {
+    /// No online runner matches, so the job would sit pending
+    pub fn is_stuck(&self) -> bool {
+        !self.coverage.is_covered()
+    }
+}
+
+/// Match every job against the fleet, in pipeline order
+pub fn job_coverage(runners: &[Runner], jobs: &[CiJob]) -> Vec<JobCoverage> {
+    jobs.iter()
+        .map(|job| JobCoverage {
+            name: job.name.clone(),
+            coverage: TagSetCoverage::from_runners(runners, &job.tags),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CI: &str = r#"
+stages: [build, test]
+
+default:
+  tags: [docker, linux]
+
+variables:
+  FOO: bar
+
+.template:
+  tags: [gpu]
+
+build:
+  stage: build
+  script: make
+
+test-arm:
+  stage: test
+  tags: [docker, arm64]
+  script: make test
+"#;
+
+    #[test]
+    fn test_parse_jobs_with_default_tags() {
+        let jobs = parse_ci_jobs(CI).unwrap();
+        assert_eq!(
+            jobs,
+            vec![
+                CiJob {
+                    name: "build".to_string(),
+                    tags: vec!["docker".to_string(), "linux".to_string()],
+                },
+                CiJob {
+                    name: "test-arm".to_string(),
+                    tags: vec!["arm64".to_string(), "docker".to_string()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extends_merges_templates_in_order() {
+        let ci = r#"
+default:
+  tags: [docker]
+
+.arm:
+  tags: [arm64]
+  variables: {ARCH: arm64}
+
+.gpu:
+  extends: .arm
+  tags: [arm64, gpu]
+
+.no-default:
+  inherit:
+    default: false
+
+train:
+  extends: [.arm, .gpu]
+  script: train
+
+lint:
+  extends: .arm
+  tags: [linux]
+  script: lint
+
+untagged:
+  extends: .no-default
+  script: echo
+
+deploy:
+  trigger: other/project
+"#;
+        let jobs = parse_ci_jobs(ci).unwrap();
+        let tags: Vec<(&str, Vec<String>)> = jobs
+            .iter()
+            .map(|j| (j.name.as_str(), j.tags.clone()))
+            .collect();
+        assert_eq!(
+            tags,
+            vec![
+                ("train", vec!["arm64".to_string(), "gpu".to_string()]),
+                ("lint", vec!["linux".to_string()]),
+                ("untagged", vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extends_errors() {
+        let err = parse_ci_jobs("job:\n  extends: .missing\n").unwrap_err();
+        assert!(format!("{:#}", err).contains("'.missing' is not defined"));
+
+        let looped = ".a:\n  extends: .b\n.b:\n  extends: .a\njob:\n  extends: .a\n";
+        let err = parse_ci_jobs(looped).unwrap_err();
+        assert!(format!("{:#}", err).contains("extends loop: job → .a → .b → .a"));
+    }
+
+    #[test]
+    fn test_job_coverage_reports_stuck_jobs() {
+        use crate::models::runner::RunnerType;
+        use crate::models::test_support;
+
+        let runner = Runner {
+            runner_type: RunnerType::InstanceType,
+            is_shared: true,
+            tag_list: test_support::tags(&["docker", "linux"]),
+            run_untagged: Some(false),
+            ..test_support::runner(1)
+        };
+        let jobs = vec![
+            CiJob {
+                name: "build".to_string(),
+                tags: vec!["docker".to_string()],
+            },
+            CiJob {
+                name: "untagged".to_string(),
+                tags: vec![],
+            },
+        ];
+        let report = job_coverage(&[runner], &jobs);
+        assert!(!report[0].is_stuck());
+        assert!(report[1].is_stuck());
+    }
+
+    #[test]
+    fn test_parse_rejects_non_mapping() {
+        assert!(parse_ci_jobs("- just\n- a list\n").is_err());
+        assert!(parse_ci_jobs("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_required_tag_sets_are_distinct() {
+        let sets = vec![
+            vec!["linux".to_string(), "docker".to_string()],
+            vec!["docker".to_string(), "linux".to_string()],
+            vec![],
+        ];
+        let required = required_tag_sets(&sets, None).unwrap();
+        assert_eq!(
+            required,
+            vec![vec!["docker".to_string(), "linux".to_string()]]
+        );
+        assert!(required_tag_sets(&[vec![]], None).is_err());
+    }
+}
@@ -0,0 +1,631 @@
+use crate::conductor::DEFAULT_FLAMES_THRESHOLD_SECS;
+use crate::config::{Overrides, Source};
+use crate::models::runner::{RunnerFilters, RunnerType};
+use crate::models::status::RunnerStatus;
+use crate::models::version::parse_version;
+use crate::presets::{self, Preset};
+use crate::query::Query;
+use crate::source::demo::DEFAULT_DEMO_SEED;
+use crate::tui::app::Command;
+use crate::tui::filter_form::{RUNNER_TYPE_OPTIONS, STATUS_OPTIONS};
+use crate::tui::table::Column;
+use anyhow::{bail, Result};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Args, Parser, Subcommand};
+use clap_complete::Shell;
+use semver::Version;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub struct Cli {
+    /// GitLab instance URL
+    #[arg(long, env("GITLAB_HOST"), global = true)]
+    pub host: Option<String>,
+
+    /// Personal access token with read_api scope
+    #[arg(long, env("GITLAB_TOKEN"), hide_env_values = true, global = true)]
+    pub token: Option<String>,
+
+    /// Config file to use instead of ./config.toml or
+    /// ~/.config/igor/config.toml
+    #[arg(long, value_name = "FILE", global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Serve a generated fleet instead of talking to GitLab
+    #[arg(long, global = true)]
+    pub demo: bool,
+
+    /// Seed for the --demo fleet; the same seed yields the same runners
+    #[arg(long, global = true, requires = "demo", default_value_t = DEFAULT_DEMO_SEED)]
+    pub demo_seed: u64,
+
+    /// Command to run; launches the TUI when omitted
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CliCommand {
+    /// Fetch all runners with optional filters
+    Fetch(QueryArgs),
+    /// Health check - verify all tagged runners are online
+    Lights(QueryArgs),
+    /// List runners with no online managers
+    Switch(QueryArgs),
+    /// Show detailed list of runner managers
+    Workers(QueryArgs),
+    /// Find runners not contacted recently
+    Flames(FlamesArgs),
+    /// List runners with no managers
+    Empty(QueryArgs),
+    /// Detect runners with multiple managers (rotation)
+    Rotate(QueryArgs),
+    /// Compare runner versions against a target version
+    Drift(DriftArgs),
+    /// Count runners able to pick up each required tag set
+    Coverage(CoverageArgs),
+    /// List pipeline jobs that no online runner would pick up
+    Pipeline(PipelineArgs),
+    /// Poll the fleet as a long-running service: notifications and alerts,
+    /// JSON logs on stdout, SIGHUP reloads config.toml
+    Daemon(DaemonArgs),
+    /// Launch the interactive terminal UI
+    Tui,
+    /// Validate config.toml or print the effective settings
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    /// Serve a fake GitLab runners API for integration tests
+    MockServer(MockServerArgs),
+    /// Generate shell completions and print them to stdout
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigCommand {
+    /// Report syntax errors, unknown keys and invalid values with line numbers
+    Check,
+    /// Print the effective settings and where each came from, secrets redacted
+    Show,
+}
+
+/// Options shared by every headless query command
+#[derive(Args, Debug, Clone, Default)]
+pub struct QueryArgs {
+    #[command(flatten)]
+    pub filters: FilterArgs,
+
+    #[command(flatten)]
+    pub run_mode: RunMode,
+}
+
+/// Which runners to fetch, and the client-side expression applied to them
+#[derive(Args, Debug, Clone, Default)]
+pub struct FilterArgs {
+    /// Comma-separated tags to filter runners
+    #[arg(long, value_delimiter = ',')]
+    pub tags: Vec<String>,
+
+    /// Only runners with this status
+    #[arg(long, value_parser = STATUS_OPTIONS.to_vec())]
+    pub status: Option<String>,
+
+    /// Only runners whose version starts with this prefix (e.g. 17.5)
+    #[arg(long)]
+    pub version_prefix: Option<String>,
+
+    /// Only runners of this type
+    #[arg(long = "type", value_parser = RUNNER_TYPE_OPTIONS.to_vec())]
+    pub runner_type: Option<String>,
+
+    /// Only paused (true) or active (false) runners
+    #[arg(long)]
+    pub paused: Option<bool>,
+
+    /// Client-side filter expression, e.g. "status = online and version < 17.0"
+    #[arg(long = "where", value_name = "EXPR")]
+    pub where_clause: Option<Query>,
+
+    /// Start from a `[[presets]]` entry's filters, expression and sort; the
+    /// flags above override it
+    #[arg(long, value_name = "NAME")]
+    pub preset: Option<String>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct FlamesArgs {
+    #[command(flatten)]
+    pub query: QueryArgs,
+
+    /// Seconds since last contact before a runner counts as uncontacted
+    #[arg(long, default_value_t = DEFAULT_FLAMES_THRESHOLD_SECS)]
+    pub threshold: u64,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct DriftArgs {
+    #[command(flatten)]
+    pub query: QueryArgs,
+
+    /// Version to compare against; defaults to target_version from config,
+    /// then the newest version in the fleet
+    #[arg(long, value_parser = parse_target_version)]
+    pub target: Option<Version>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct CoverageArgs {
+    #[command(flatten)]
+    pub query: QueryArgs,
+
+    /// Comma-separated tag set to check; repeat for more sets
+    #[arg(long = "set", value_name = "TAGS")]
+    pub sets: Vec<String>,
+
+    /// Also check the tags required by the jobs in this .gitlab-ci.yml
+    #[arg(long, value_name = "FILE")]
+    pub ci_file: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct PipelineArgs {
+    #[command(flatten)]
+    pub query: QueryArgs,
+
+    /// .gitlab-ci.yml to check; defaults to ci_file from config
+    #[arg(long, value_name = "FILE")]
+    pub ci_file: Option<PathBuf>,
+}
+
+fn parse_target_version(raw: &str) -> Result<Version, String> {
+    parse_version(raw).ok_or_else(|| format!("'{}' is not a runner version", raw))
+}
+
+#[derive(Args, Debug, Clone, Copy, Default)]
+#[group(multiple = false)]
+pub struct RunMode {
+    /// Run the query a single time and exit (default)
+    #[arg(long)]
+    pub once: bool,
+
+    /// Keep polling until the configured timeout is reached
+    #[arg(long)]
+    pub watch: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct DaemonArgs {
+    #[command(flatten)]
+    pub filters: FilterArgs,
+
+    /// Report readiness, reloads and watchdog pings to systemd
+    /// (Type=notify units)
+    #[arg(long)]
+    pub sd_notify: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct MockServerArgs {
+    /// Address to listen on; port 0 picks a free port
+    #[arg(long, default_value = "127.0.0.1:8929")]
+    pub listen: SocketAddr,
+
+    /// Fixture directory: runners/<id>.json and optional runners/<id>/managers.json
+    #[arg(long, value_name = "DIR", conflicts_with = "snapshot")]
+    pub fixtures: Option<PathBuf>,
+
+    /// Snapshot file: a JSON array of runners with managers inline
+    #[arg(long, value_name = "FILE")]
+    pub snapshot: Option<PathBuf>,
+
+    /// Seed for the generated fleet served when no fixtures are given
+    #[arg(long, default_value_t = DEFAULT_DEMO_SEED)]
+    pub seed: u64,
+
+    /// Delay every response by this many milliseconds
+    #[arg(long, value_name = "MS", default_value_t = 0)]
+    pub latency_ms: u64,
+
+    /// Answer every Nth request with 429 Too Many Requests
+    #[arg(long, value_name = "N")]
+    pub rate_limit_every: Option<u64>,
+
+    /// Answer every Nth request with a server error
+    #[arg(long, value_name = "N")]
+    pub error_every: Option<u64>,
+
+    /// Status code returned by --error-every
+    #[arg(long, default_value_t = 500, value_parser = clap::value_parser!(u16).range(500..600))]
+    pub error_status: u16,
+}
+
+/// A headless query resolved from the command line
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeadlessQuery {
+    pub command: Command,
+    pub filters: RunnerFilters,
+    pub query: Option<Query>,
+    /// Preset the filters and query are merged with
+    pub preset: Option<String>,
+    /// Column and direction to sort by, from the preset
+    pub sort: Option<(Column, bool)>,
+    pub threshold_secs: u64,
+    /// Drift target given on the command line
+    pub target_version: Option<Version>,
+    /// Coverage tag sets and coverage/pipeline CI file given on the command line
+    pub tag_sets: Vec<Vec<String>>,
+    pub ci_file: Option<PathBuf>,
+    pub watch: bool,
+}
+
+impl Cli {
+    /// `--host` and `--token`, and whether each came from the flag or the
+    /// environment
+    pub fn overrides(&self, matches: &ArgMatches) -> Overrides {
+        let source = |id: &str| match matches.value_source(id) {
+            Some(ValueSource::EnvVariable) => Source::Env,
+            _ => Source::Flag,
+        };
+        Overrides {
+            host: self.host.clone().map(|host| (host, source("host"))),
+            token: self.token.clone().map(|token| (token, source("token"))),
+        }
+    }
+}
+
+impl FilterArgs {
+    pub fn to_filters(&self) -> RunnerFilters {
+        let tags: Vec<String> = self
+            .tags
+            .iter()
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect();
+
+        RunnerFilters {
+            tag_list: if tags.is_empty() { None } else { Some(tags) },
+            status: self.status.as_deref().map(RunnerStatus::from),
+            version_prefix: self.version_prefix.clone(),
+            runner_type: self.runner_type.as_deref().map(RunnerType::from),
+            paused: self.paused,
+        }
+    }
+
+    /// Filters and expression, merged with `--preset` when given
+    pub fn resolve(&self, presets: &[Preset]) -> Result<(RunnerFilters, Option<Query>)> {
+        let filters = self.to_filters();
+        let query = self.where_clause.clone();
+        match &self.preset {
+            Some(name) => Ok(presets::find(presets, name)?.merge(filters, query)),
+            None => Ok((filters, query)),
+        }
+    }
+}
+
+impl HeadlessQuery {
+    /// Merge in the named preset: flags win over its filters and
+    /// expression, and its sort applies. The preset must be for the
+    /// subcommand, since its sort is a column of that command's view.
+    pub fn with_preset(mut self, presets: &[Preset]) -> Result<Self> {
+        let Some(name) = &self.preset else {
+            return Ok(self);
+        };
+        let preset = presets::find(presets, name)?;
+        if preset.command != self.command {
+            bail!(
+                "preset '{}' is for {}, not {}: run `{} --preset {}`",
+                name,
+                preset.command,
+                self.command,
+                preset.command,
+                name
+            );
+        }
+        (self.filters, self.query) = preset.merge(self.filters, self.query);
+        self.sort = preset.sort_column().map(|c| (c, preset.descending));
+        Ok(self)
+    }
+}
+
+impl CliCommand {
+    /// Returns the headless query for this subcommand, or `None` for
+    /// subcommands that don't run a one-off query (`daemon`, `tui`,
+    /// `config`, `mock-server`, `completions`)
+    pub fn headless_query(&self) -> Option<HeadlessQuery> {
+        let (command, args, threshold_secs) = match self {
+            CliCommand::Fetch(args) => (Command::Fetch, args, DEFAULT_FLAMES_THRESHOLD_SECS),
+            CliCommand::Lights(args) => (Command::Lights, args, DEFAULT_FLAMES_THRESHOLD_SECS),
+            CliCommand::Switch(args) => (Command::Switch, args, DEFAULT_FLAMES_THRESHOLD_SECS),
+            CliCommand::Workers(args) => (Command::Workers, args, DEFAULT_FLAMES_THRESHOLD_SECS),
+            CliCommand::Flames(flames) => (Command::Flames, &flames.query, flames.threshold),
+            CliCommand::Empty(args) => (Command::Empty, args, DEFAULT_FLAMES_THRESHOLD_SECS),
+            CliCommand::Rotate(args) => (Command::Rotate, args, DEFAULT_FLAMES_THRESHOLD_SECS),
+            CliCommand::Drift(drift) => {
+                (Command::Drift, &drift.query, DEFAULT_FLAMES_THRESHOLD_SECS)
+            }
+            CliCommand::Coverage(coverage) => (
+                Command::Coverage,
+                &coverage.query,
+                DEFAULT_FLAMES_THRESHOLD_SECS,
+            ),
+            CliCommand::Pipeline(pipeline) => (
+                Command::Pipeline,
+                &pipeline.query,
+                DEFAULT_FLAMES_THRESHOLD_SECS,
+            ),
+            CliCommand::Daemon(_)
+            | CliCommand::Tui
+            | CliCommand::Config { .. }
+            | CliCommand::MockServer(_)
+            | CliCommand::Completions { .. } => return None,
+        };
+
+        Some(HeadlessQuery {
+            command,
+            filters: args.filters.to_filters(),
+            query: args.filters.where_clause.clone(),
+            preset: args.filters.preset.clone(),
+            sort: None,
+            threshold_secs,
+            target_version: match self {
+                CliCommand::Drift(drift) => drift.target.clone(),
+                _ => None,
+            },
+            tag_sets: match self {
+                CliCommand::Coverage(coverage) => coverage
+                    .sets
+                    .iter()
+                    .map(|set| set.split(',').map(String::from).collect())
+                    .collect(),
+                _ => Vec::new(),
+            },
+            ci_file: match self {
+                CliCommand::Coverage(coverage) => coverage.ci_file.clone(),
+                CliCommand::Pipeline(pipeline) => pipeline.ci_file.clone(),
+                _ => None,
+            },
+            watch: args.run_mode.watch,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    fn parse(args: &[&str]) -> Cli {
+        Cli::try_parse_from(std::iter::once("gitlab-runner-tui").chain(args.iter().copied()))
+            .expect("Failed to parse args")
+    }
+
+    #[test]
+    fn test_cli_definition_is_valid() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_no_subcommand_launches_tui() {
+        let cli = parse(&[]);
+        assert!(cli.command.is_none());
+    }
+
+    #[test]
+    fn test_every_command_has_a_subcommand() {
+        for command in Command::ALL {
+            let cli = parse(&[&command.to_string()]);
+            let query = cli.command.unwrap().headless_query().unwrap();
+            assert_eq!(query.command, *command);
+        }
+    }
+
+    #[test]
+    fn test_drift_target_is_parsed() {
+        let cli = parse(&["drift", "--target", "v17.5"]);
+        let query = cli.command.unwrap().headless_query().unwrap();
+        assert_eq!(query.command, Command::Drift);
+        assert_eq!(query.target_version, Some(Version::new(17, 5, 0)));
+
+        let bad = Cli::try_parse_from(["gitlab-runner-tui", "drift", "--target", "latest"]);
+        assert!(bad.is_err());
+    }
+
+    #[test]
+    fn test_coverage_sets_are_repeatable() {
+        let cli = parse(&["coverage", "--set", "docker,arm64", "--set", "windows"]);
+        let query = cli.command.unwrap().headless_query().unwrap();
+        assert_eq!(query.command, Command::Coverage);
+        assert_eq!(
+            query.tag_sets,
+            vec![
+                vec!["docker".to_string(), "arm64".to_string()],
+                vec!["windows".to_string()]
+            ]
+        );
+        assert!(query.ci_file.is_none());
+    }
+
+    #[test]
+    fn test_lights_and_workers_are_headless_commands() {
+        let lights = parse(&["lights", "--tags", "alm"]);
+        let query = lights.command.unwrap().headless_query().unwrap();
+        assert_eq!(query.command, Command::Lights);
+        assert_eq!(query.filters.tag_list, Some(vec!["alm".to_string()]));
+
+        let workers = parse(&["workers"]);
+        let query = workers.command.unwrap().headless_query().unwrap();
+        assert_eq!(query.command, Command::Workers);
+        assert!(query.filters.tag_list.is_none());
+    }
+
+    #[test]
+    fn test_tags_are_split_and_trimmed() {
+        let cli = parse(&["fetch", "--tags", "alm, production,,linux"]);
+        let query = cli.command.unwrap().headless_query().unwrap();
+        assert_eq!(
+            query.filters.tag_list,
+            Some(vec![
+                "alm".to_string(),
+                "production".to_string(),
+                "linux".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn test_all_runner_filters_have_flags() {
+        let cli = parse(&[
+            "fetch",
+            "--status",
+            "offline",
+            "--version-prefix",
+            "17.5",
+            "--type",
+            "group_type",
+            "--paused",
+            "true",
+        ]);
+        let filters = cli.command.unwrap().headless_query().unwrap().filters;
+        assert_eq!(filters.status, Some(RunnerStatus::Offline));
+        assert_eq!(filters.version_prefix, Some("17.5".to_string()));
+        assert_eq!(filters.runner_type, Some(RunnerType::GroupType));
+        assert_eq!(filters.paused, Some(true));
+    }
+
+    #[test]
+    fn test_where_flag_parses_query() {
+        let cli = parse(&["workers", "--where", "platform = windows"]);
+        let query = cli.command.unwrap().headless_query().unwrap().query;
+        assert_eq!(query.unwrap().source(), "platform = windows");
+    }
+
+    #[test]
+    fn test_invalid_where_is_rejected() {
+        let result = Cli::try_parse_from(["gitlab-runner-tui", "fetch", "--where", "colour = red"]);
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("unknown field 'colour'"), "got: {}", err);
+    }
+
+    #[test]
+    fn test_invalid_status_is_rejected() {
+        let result = Cli::try_parse_from(["gitlab-runner-tui", "fetch", "--status", "sleepy"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_flames_threshold_defaults_and_overrides() {
+        let cli = parse(&["flames"]);
+        let query = cli.command.unwrap().headless_query().unwrap();
+        assert_eq!(query.threshold_secs, DEFAULT_FLAMES_THRESHOLD_SECS);
+
+        let cli = parse(&["flames", "--threshold", "600"]);
+        let query = cli.command.unwrap().headless_query().unwrap();
+        assert_eq!(query.threshold_secs, 600);
+    }
+
+    #[test]
+    fn test_threshold_is_only_accepted_by_flames() {
+        let result = Cli::try_parse_from(["gitlab-runner-tui", "fetch", "--threshold", "600"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_once_is_the_default_run_mode() {
+        let cli = parse(&["rotate"]);
+        assert!(!cli.command.unwrap().headless_query().unwrap().watch);
+
+        let cli = parse(&["rotate", "--once"]);
+        assert!(!cli.command.unwrap().headless_query().unwrap().watch);
+
+        let cli = parse(&["rotate", "--watch"]);
+        assert!(cli.command.unwrap().headless_query().unwrap().watch);
+    }
+
+    #[test]
+    fn test_once_and_watch_conflict() {
+        let result = Cli::try_parse_from(["gitlab-runner-tui", "switch", "--once", "--watch"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_global_flags_accepted_after_subcommand() {
+        let cli = parse(&["fetch", "--host", "https://gitlab.example.com"]);
+        assert_eq!(cli.host, Some("https://gitlab.example.com".to_string()));
+    }
+
+    #[test]
+    fn test_daemon_takes_filters_but_no_run_mode() {
+        let cli = parse(&["daemon", "--tags", "prod", "--sd-notify"]);
+        let Some(CliCommand::Daemon(daemon)) = cli.command else {
+            panic!("expected the daemon command");
+        };
+        assert!(daemon.sd_notify);
+        assert_eq!(
+            daemon.filters.to_filters().tag_list,
+            Some(vec!["prod".to_string()])
+        );
+        assert!(Cli::try_parse_from(["gitlab-runner-tui", "daemon", "--watch"]).is_err());
+        assert!(parse(&["daemon"])
+            .command
+            .unwrap()
+            .headless_query()
+            .is_none());
+    }
+
+    #[test]
+    fn test_preset_merges_under_flags() {
+        let presets: Vec<Preset> = toml::from_str::<toml::Table>(
+            r#"
+            [[presets]]
+            name = "prod"
+            command = "lights"
+            tags = ["production"]
+            status = "online"
+            where = "version < 17.0"
+            sort = "version"
+            descending = true
+            "#,
+        )
+        .unwrap()["presets"]
+            .clone()
+            .try_into()
+            .unwrap();
+
+        let cli = parse(&["lights", "--preset", "prod", "--status", "offline"]);
+        let query = cli.command.unwrap().headless_query().unwrap();
+        let query = query.with_preset(&presets).unwrap();
+        assert_eq!(query.command, Command::Lights);
+        assert_eq!(query.filters.tag_list, Some(vec!["production".to_string()]));
+        assert_eq!(query.filters.status, Some(RunnerStatus::Offline));
+        assert!(query.query.is_some());
+        assert_eq!(query.sort, Some((Column::Version, true)));
+
+        let cli = parse(&["fetch", "--preset", "staging"]);
+        let query = cli.command.unwrap().headless_query().unwrap();
+        assert!(query.with_preset(&presets).is_err());
+
+        // A lights preset's sort column may not exist in another view
+        let cli = parse(&["workers", "--preset", "prod"]);
+        let query = cli.command.unwrap().headless_query().unwrap();
+        assert_eq!(
+            query.with_preset(&presets).unwrap_err().to_string(),
+            "preset 'prod' is for lights, not workers: run `lights --preset prod`"
+        );
+    }
+
+    #[test]
+    fn test_tui_and_completions_are_not_headless() {
+        assert!(parse(&["tui"]).command.unwrap().headless_query().is_none());
+        assert!(parse(&["completions", "bash"])
+            .command
+            .unwrap()
+            .headless_query()
+            .is_none());
+    }
+}
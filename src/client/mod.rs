@@ -1,6 +1,10 @@
+use crate::models::job::Job;
 use crate::models::manager::RunnerManager;
 use crate::models::runner::{Runner, RunnerFilters};
+use crate::models::server::ServerVersion;
+use crate::source::RunnerSource;
 use anyhow::{Context, Result};
+use async_trait::async_trait;
 use reqwest::{Client, Method, RequestBuilder};
 
 #[derive(Clone)]
@@ -34,8 +38,11 @@ impl GitLabClient {
             .request(method, &url)
             .header("PRIVATE-TOKEN", &self.token)
     }
+}
 
-    pub async fn fetch_runners(
+#[async_trait]
+impl RunnerSource for GitLabClient {
+    async fn fetch_runners(
         &self,
         filters: &RunnerFilters,
         page: u32,
@@ -46,10 +53,10 @@ impl GitLabClient {
             .query(&[("per_page", per_page), ("page", page)]);
 
         if let Some(status) = &filters.status {
-            request = request.query(&[("status", status)]);
+            request = request.query(&[("status", status.as_str())]);
         }
         if let Some(runner_type) = &filters.runner_type {
-            request = request.query(&[("type", runner_type)]);
+            request = request.query(&[("type", runner_type.as_str())]);
         }
         if let Some(paused) = filters.paused {
             request = request.query(&[("paused", paused.to_string())]);
@@ -75,7 +82,7 @@ impl GitLabClient {
         Ok(runners)
     }
 
-    pub async fn fetch_runner_detail(&self, runner_id: u64) -> Result<Runner> {
+    async fn fetch_runner_detail(&self, runner_id: u64) -> Result<Runner> {
         let endpoint = format!("runners/{}", runner_id);
         let response = self
             .request(Method::GET, &endpoint)
@@ -92,7 +99,7 @@ impl GitLabClient {
         Ok(runner)
     }
 
-    pub async fn fetch_runner_managers(&self, runner_id: u64) -> Result<Vec<RunnerManager>> {
+    async fn fetch_runner_managers(&self, runner_id: u64) -> Result<Vec<RunnerManager>> {
         let endpoint = format!("runners/{}/managers", runner_id);
         let response = self
             .request(Method::GET, &endpoint)
@@ -115,11 +122,66 @@ impl GitLabClient {
             .context("Failed to deserialize managers")?;
         Ok(managers)
     }
+
+    async fn fetch_runner_jobs(&self, runner_id: u64, per_page: u32) -> Result<Vec<Job>> {
+        let endpoint = format!("runners/{}/jobs", runner_id);
+        let response = self
+            .request(Method::GET, &endpoint)
+            .query(&[("order_by", "id"), ("sort", "desc")])
+            .query(&[("per_page", per_page)])
+            .send()
+            .await
+            .context("Failed to send request")?;
+        let response = response
+            .error_for_status()
+            .context("Failed to fetch runner jobs")?;
+        let jobs = response
+            .json::<Vec<Job>>()
+            .await
+            .context("Failed to deserialize jobs")?;
+        Ok(jobs)
+    }
+
+    async fn fetch_server_version(&self) -> Result<ServerVersion> {
+        let response = self
+            .request(Method::GET, "version")
+            .send()
+            .await
+            .context("Failed to send request")?;
+        let response = response
+            .error_for_status()
+            .context("Failed to fetch GitLab version")?;
+        let version = response
+            .json::<ServerVersion>()
+            .await
+            .context("Failed to deserialize GitLab version")?;
+        Ok(version)
+    }
+
+    async fn set_runner_paused(&self, runner_id: u64, paused: bool) -> Result<Runner> {
+        let endpoint = format!("runners/{}", runner_id);
+        let response = self
+            .request(Method::PUT, &endpoint)
+            .json(&serde_json::json!({ "paused": paused }))
+            .send()
+            .await
+            .context("Failed to send request")?;
+        let response = response
+            .error_for_status()
+            .context("Failed to update runner")?;
+        let runner = response
+            .json::<Runner>()
+            .await
+            .context("Failed to deserialize runner detail")?;
+        Ok(runner)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::runner::RunnerType;
+    use crate::models::status::RunnerStatus;
     use mockito::{Matcher, Server};
 
     #[test]
@@ -178,7 +240,7 @@ mod tests {
         mock.assert_async().await;
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].id, 12345);
-        assert_eq!(runners[0].status, "online");
+        assert_eq!(runners[0].status, RunnerStatus::Online);
         // tag_list defaults to empty since /runners/all doesn't return it
         assert!(runners[0].tag_list.is_empty());
     }
@@ -202,7 +264,7 @@ mod tests {
 
         let client = GitLabClient::new(server.url(), "test-token".to_string()).unwrap();
         let filters = RunnerFilters {
-            status: Some("online".to_string()),
+            status: Some(RunnerStatus::Online),
             ..Default::default()
         };
 
@@ -231,7 +293,7 @@ mod tests {
 
         let client = GitLabClient::new(server.url(), "test-token".to_string()).unwrap();
         let filters = RunnerFilters {
-            runner_type: Some("group_type".to_string()),
+            runner_type: Some(RunnerType::GroupType),
             ..Default::default()
         };
 
@@ -270,7 +332,64 @@ mod tests {
         assert_eq!(managers.len(), 1);
         assert_eq!(managers[0].id, 67890);
         assert_eq!(managers[0].system_id, "runner-host-01");
-        assert_eq!(managers[0].status, "online");
+        assert_eq!(managers[0].status, RunnerStatus::Online);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_runner_jobs_newest_first() {
+        let mut server = Server::new_async().await;
+
+        let mock = server
+            .mock("GET", "/api/v4/runners/12345/jobs")
+            .match_header("PRIVATE-TOKEN", "test-token")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("order_by".into(), "id".into()),
+                Matcher::UrlEncoded("sort".into(), "desc".into()),
+                Matcher::UrlEncoded("per_page".into(), "20".into()),
+            ]))
+            .with_status(200)
+            .with_body(
+                r#"[{
+                    "id": 9001,
+                    "name": "rspec",
+                    "stage": "test",
+                    "status": "running",
+                    "ref": "main",
+                    "created_at": "2024-06-09T11:12:02.507Z"
+                }]"#,
+            )
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(server.url(), "test-token".to_string()).unwrap();
+
+        let jobs = client.fetch_runner_jobs(12345, 20).await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].id, 9001);
+        assert_eq!(jobs[0].status, "running");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_server_version() {
+        let mut server = Server::new_async().await;
+
+        let mock = server
+            .mock("GET", "/api/v4/version")
+            .match_header("PRIVATE-TOKEN", "test-token")
+            .with_status(200)
+            .with_body(r#"{"version": "17.5.1-ee", "revision": "4b1f0c2e"}"#)
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(server.url(), "test-token".to_string()).unwrap();
+
+        let version = client.fetch_server_version().await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(version.version, "17.5.1-ee");
+        assert_eq!(version.revision, "4b1f0c2e");
     }
 
     #[tokio::test]
@@ -451,7 +570,8 @@ mod tests {
                     "status": "online",
                     "version": "17.5.0",
                     "revision": "abc123",
-                    "tag_list": ["alm", "production"]
+                    "tag_list": ["alm", "production"],
+                    "run_untagged": false
                 }"#,
             )
             .create_async()
@@ -465,6 +585,37 @@ mod tests {
         assert_eq!(runner.id, 12345);
         assert_eq!(runner.tag_list, vec!["alm", "production"]);
         assert_eq!(runner.version, Some("17.5.0".to_string()));
+        assert_eq!(runner.run_untagged, Some(false));
+    }
+
+    #[tokio::test]
+    async fn test_set_runner_paused() {
+        let mut server = Server::new_async().await;
+
+        let mock = server
+            .mock("PUT", "/api/v4/runners/7")
+            .match_header("PRIVATE-TOKEN", "test-token")
+            .match_body(Matcher::Json(serde_json::json!({ "paused": true })))
+            .with_status(200)
+            .with_body(
+                r#"{
+                    "id": 7,
+                    "runner_type": "group_type",
+                    "active": false,
+                    "paused": true,
+                    "description": "Paused Runner",
+                    "is_shared": false,
+                    "status": "online"
+                }"#,
+            )
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(server.url(), "test-token".to_string()).unwrap();
+        let runner = client.set_runner_paused(7, true).await.unwrap();
+
+        mock.assert_async().await;
+        assert!(runner.paused);
     }
 
     #[tokio::test]
@@ -1,16 +1,26 @@
-use crate::client::GitLabClient;
 use crate::models::runner::{Runner, RunnerFilters};
+use crate::models::server::ServerVersion;
+use crate::source::RunnerSource;
 use anyhow::Result;
-use chrono::{DateTime, Utc};
+use chrono::Utc;
 use futures::stream::{self, StreamExt};
 
-pub struct Conductor {
-    client: GitLabClient,
+/// Default age in seconds after which `flames` treats a runner as uncontacted
+pub const DEFAULT_FLAMES_THRESHOLD_SECS: u64 = 3600;
+
+pub struct Conductor<S> {
+    source: S,
 }
 
-impl Conductor {
-    pub fn new(client: GitLabClient) -> Self {
-        Self { client }
+impl<S: RunnerSource> Conductor<S> {
+    pub fn new(source: S) -> Self {
+        Self { source }
+    }
+
+    /// The source, for tests that change the fleet between polls
+    #[cfg(test)]
+    pub fn source_mut(&mut self) -> &mut S {
+        &mut self.source
     }
 
     pub async fn fetch_runners(&self, filters: RunnerFilters) -> Result<Vec<Runner>> {
@@ -19,7 +29,7 @@ impl Conductor {
         let per_page = 100;
 
         loop {
-            let runners = self.client.fetch_runners(&filters, page, per_page).await?;
+            let runners = self.source.fetch_runners(&filters, page, per_page).await?;
             if runners.is_empty() {
                 break;
             }
@@ -28,17 +38,17 @@ impl Conductor {
 
             // Enrich each runner with detail (tags, version) and managers
             // Use buffer_unordered to limit concurrent API requests
+            let source = &self.source;
             let enriched: Vec<Runner> = stream::iter(runners.into_iter().map(|r| {
-                let client = self.client.clone();
                 async move {
-                    let mut detail = match client.fetch_runner_detail(r.id).await {
+                    let mut detail = match source.fetch_runner_detail(r.id).await {
                         Ok(d) => d,
                         Err(e) => {
                             tracing::warn!(runner_id = r.id, error = %e, "Failed to fetch runner detail, using list data");
                             r
                         }
                     };
-                    match client.fetch_runner_managers(detail.id).await {
+                    match source.fetch_runner_managers(detail.id).await {
                         Ok(managers) => detail.managers = managers,
                         Err(e) => {
                             tracing::warn!(runner_id = detail.id, error = %e, "Failed to fetch runner managers");
@@ -70,7 +80,7 @@ impl Conductor {
                     return false;
                 }
                 // Runner is offline if none of its managers are online
-                !r.managers.iter().any(|m| m.status == "online")
+                !r.managers.iter().any(|m| m.status.is_online())
             })
             .collect();
         Ok(offline)
@@ -91,19 +101,12 @@ impl Conductor {
                     return false;
                 }
                 // Runner is uncontacted if ALL managers are past the threshold
-                r.managers.iter().all(|m| {
-                    match &m.contacted_at {
-                        Some(contacted_at_str) => {
-                            match DateTime::parse_from_rfc3339(contacted_at_str) {
-                                Ok(contacted_at) => {
-                                    let duration = now.signed_duration_since(contacted_at);
-                                    duration.num_seconds() > threshold_secs as i64
-                                }
-                                Err(_) => true, // Unparseable timestamp treated as uncontacted
-                            }
-                        }
-                        None => true, // Missing contacted_at treated as uncontacted
+                r.managers.iter().all(|m| match m.contacted_at {
+                    Some(contacted_at) => {
+                        let duration = now.signed_duration_since(contacted_at);
+                        duration.num_seconds() > threshold_secs as i64
                     }
+                    None => true, // Missing contacted_at treated as uncontacted
                 })
             })
             .collect();
@@ -117,7 +120,7 @@ impl Conductor {
         let total = runners.len();
         let online = runners
             .iter()
-            .filter(|r| r.managers.iter().any(|m| m.status == "online"))
+            .filter(|r| r.managers.iter().any(|m| m.status.is_online()))
             .count();
         Ok((online, total))
     }
@@ -134,230 +137,134 @@ impl Conductor {
         Ok(empty)
     }
 
-    pub async fn detect_rotating_runners(&self, filters: RunnerFilters) -> Result<Vec<Runner>> {
-        let runners = self.fetch_runners(filters).await?;
-        let rotating = runners
-            .into_iter()
-            .filter(|r| r.managers.len() > 1)
-            .collect();
-        Ok(rotating)
+    pub async fn server_version(&self) -> Result<ServerVersion> {
+        self.source.fetch_server_version().await
+    }
+
+    pub async fn set_runner_paused(&self, runner_id: u64, paused: bool) -> Result<Runner> {
+        self.source.set_runner_paused(runner_id, paused).await
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use mockito::{Matcher, Server};
-
-    fn list_response_body(id: u64, status: &str) -> String {
-        format!(
-            r#"{{
-                "id": {},
-                "runner_type": "group_type",
-                "active": true,
-                "paused": false,
-                "description": "Runner {}",
-                "ip_address": "",
-                "is_shared": false,
-                "status": "{}",
-                "name": null,
-                "online": {}
-            }}"#,
-            id,
-            id,
+    use crate::models::manager::RunnerManager;
+    use crate::models::status::RunnerStatus;
+    use crate::models::test_support;
+    use crate::source::fake::FakeSource;
+
+    fn manager(id: u64, status: RunnerStatus) -> RunnerManager {
+        RunnerManager {
+            contacted_at: Some("2024-01-20T14:22:00.000Z".parse().unwrap()),
             status,
-            status == "online"
-        )
+            version: Some("17.5.0".to_string()),
+            ..test_support::manager(id, &format!("host-{}", id))
+        }
     }
 
-    fn detail_response_body(id: u64, status: &str, tags: &[&str]) -> String {
-        let tags_json: Vec<String> = tags.iter().map(|t| format!("\"{}\"", t)).collect();
-        format!(
-            r#"{{
-                "id": {},
-                "runner_type": "group_type",
-                "active": true,
-                "paused": false,
-                "description": "Runner {}",
-                "ip_address": "",
-                "is_shared": false,
-                "status": "{}",
-                "version": "17.5.0",
-                "revision": "abc123",
-                "tag_list": [{}]
-            }}"#,
-            id,
-            id,
-            status,
-            tags_json.join(", ")
-        )
+    fn runner(id: u64, tags: &[&str], managers: Vec<RunnerManager>) -> Runner {
+        Runner {
+            description: None,
+            version: Some("17.5.0".to_string()),
+            tag_list: test_support::tags(tags),
+            managers,
+            ..test_support::runner(id)
+        }
     }
 
-    fn manager_response_body(id: u64, runner_id: u64, status: &str) -> String {
-        format!(
-            r#"{{
-                "id": {},
-                "system_id": "host-{}",
-                "created_at": "2024-01-15T10:30:00.000Z",
-                "contacted_at": "2024-01-20T14:22:00.000Z",
-                "ip_address": "10.0.1.1",
-                "status": "{}",
-                "version": "17.5.0",
-                "revision": "abc123"
-            }}"#,
-            id, runner_id, status
-        )
+    fn conductor(runners: Vec<Runner>) -> Conductor<FakeSource> {
+        Conductor::new(FakeSource::new(runners))
     }
 
-    type RunnerSpec<'a> = (u64, &'a str, &'a [&'a str], &'a [(u64, &'a str)]);
-
-    async fn setup_runner_mocks(
-        server: &mut Server,
-        runners: &[RunnerSpec<'_>],
-    ) -> Vec<mockito::Mock> {
-        let mut mocks = Vec::new();
-
-        // List endpoint
-        let list_bodies: Vec<String> = runners
-            .iter()
-            .map(|(id, status, _, _)| list_response_body(*id, status))
-            .collect();
-        let list_body = format!("[{}]", list_bodies.join(","));
-
-        mocks.push(
-            server
-                .mock("GET", "/api/v4/runners/all")
-                .match_query(Matcher::AllOf(vec![
-                    Matcher::UrlEncoded("per_page".into(), "100".into()),
-                    Matcher::UrlEncoded("page".into(), "1".into()),
-                ]))
-                .with_status(200)
-                .with_body(list_body)
-                .create_async()
-                .await,
-        );
-
-        // Detail + manager endpoints per runner
-        for (id, status, tags, managers) in runners {
-            mocks.push(
-                server
-                    .mock("GET", format!("/api/v4/runners/{}", id).as_str())
-                    .with_status(200)
-                    .with_body(detail_response_body(*id, status, tags))
-                    .create_async()
-                    .await,
-            );
-
-            let manager_bodies: Vec<String> = managers
-                .iter()
-                .map(|(mid, mstatus)| manager_response_body(*mid, *id, mstatus))
-                .collect();
-            let managers_body = format!("[{}]", manager_bodies.join(","));
-            mocks.push(
-                server
-                    .mock("GET", format!("/api/v4/runners/{}/managers", id).as_str())
-                    .with_status(200)
-                    .with_body(managers_body)
-                    .create_async()
-                    .await,
-            );
-        }
-
-        mocks
+    fn ids(runners: &[Runner]) -> Vec<u64> {
+        let mut ids: Vec<u64> = runners.iter().map(|r| r.id).collect();
+        ids.sort();
+        ids
     }
 
     #[tokio::test]
     async fn test_enrichment_adds_tags_from_detail() {
-        let mut server = Server::new_async().await;
-        let mocks = setup_runner_mocks(
-            &mut server,
-            &[(1, "online", &["alm", "production"], &[(10, "online")])],
-        )
-        .await;
-
-        let client = GitLabClient::new(server.url(), "test-token".to_string()).unwrap();
-        let conductor = Conductor::new(client);
+        let conductor = conductor(vec![runner(
+            1,
+            &["alm", "production"],
+            vec![manager(10, RunnerStatus::Online)],
+        )]);
 
         let runners = conductor
             .fetch_runners(RunnerFilters::default())
             .await
             .unwrap();
 
+        // The list endpoint has neither tags nor managers
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].tag_list, vec!["alm", "production"]);
         assert_eq!(runners[0].version, Some("17.5.0".to_string()));
         assert_eq!(runners[0].managers.len(), 1);
-
-        for mock in &mocks {
-            mock.assert_async().await;
-        }
     }
 
     #[tokio::test]
-    async fn test_enrichment_degrades_gracefully_on_detail_failure() {
-        let mut server = Server::new_async().await;
-
-        // List returns one runner
-        let list_mock = server
-            .mock("GET", "/api/v4/runners/all")
-            .match_query(Matcher::AllOf(vec![
-                Matcher::UrlEncoded("per_page".into(), "100".into()),
-                Matcher::UrlEncoded("page".into(), "1".into()),
-            ]))
-            .with_status(200)
-            .with_body(format!("[{}]", list_response_body(1, "online")))
-            .create_async()
-            .await;
-
-        // Detail returns 500
-        let detail_mock = server
-            .mock("GET", "/api/v4/runners/1")
-            .with_status(500)
-            .with_body(r#"{"message":"Internal Server Error"}"#)
-            .create_async()
-            .await;
+    async fn test_fetches_every_page() {
+        let fleet = (1..=250)
+            .map(|id| runner(id, &["alm"], vec![manager(id * 10, RunnerStatus::Online)]))
+            .collect();
 
-        // Managers still succeeds
-        let managers_mock = server
-            .mock("GET", "/api/v4/runners/1/managers")
-            .with_status(200)
-            .with_body("[]")
-            .create_async()
-            .await;
+        let runners = conductor(fleet)
+            .fetch_runners(RunnerFilters::default())
+            .await
+            .unwrap();
 
-        let client = GitLabClient::new(server.url(), "test-token".to_string()).unwrap();
-        let conductor = Conductor::new(client);
+        assert_eq!(ids(&runners), (1..=250).collect::<Vec<u64>>());
+    }
 
-        let runners = conductor
-            .fetch_runners(RunnerFilters::default())
+    #[tokio::test]
+    async fn test_detail_failure_keeps_list_data() {
+        let source = FakeSource::new(vec![
+            runner(1, &["alm"], vec![manager(10, RunnerStatus::Offline)]),
+            runner(2, &["alm"], vec![manager(20, RunnerStatus::Offline)]),
+        ])
+        .with_failing_detail(1);
+        let conductor = Conductor::new(source);
+
+        let mut offline = conductor
+            .list_offline_runners(RunnerFilters::default())
             .await
             .unwrap();
 
-        // Should still get the runner, just without enriched tags
-        assert_eq!(runners.len(), 1);
-        assert_eq!(runners[0].id, 1);
-        assert!(runners[0].tag_list.is_empty());
+        offline.sort_by_key(|r| r.id);
+        assert_eq!(offline.len(), 2);
+        // Runner 1 fell back to list data: managers but no tags
+        assert!(offline[0].tag_list.is_empty());
+        assert_eq!(offline[0].managers.len(), 1);
+        assert_eq!(offline[1].tag_list, vec!["alm"]);
+    }
 
-        list_mock.assert_async().await;
-        detail_mock.assert_async().await;
-        managers_mock.assert_async().await;
+    #[tokio::test]
+    async fn test_passes_filters_through() {
+        let paused = Runner {
+            paused: true,
+            ..runner(2, &["alm"], vec![])
+        };
+        let conductor = conductor(vec![runner(1, &["alm"], vec![]), paused]);
+
+        let filters = RunnerFilters {
+            paused: Some(true),
+            ..Default::default()
+        };
+        let empty = conductor
+            .list_runners_without_managers(filters)
+            .await
+            .unwrap();
+        assert_eq!(ids(&empty), vec![2]);
     }
 
     #[tokio::test]
     async fn test_list_offline_runners() {
-        let mut server = Server::new_async().await;
-        let mocks = setup_runner_mocks(
-            &mut server,
-            &[
-                (1, "online", &["prod"], &[(10, "online")]),
-                (2, "offline", &["staging"], &[(20, "offline")]),
-                (3, "online", &["dev"], &[]),
-            ],
-        )
-        .await;
-
-        let client = GitLabClient::new(server.url(), "test-token".to_string()).unwrap();
-        let conductor = Conductor::new(client);
+        let conductor = conductor(vec![
+            runner(1, &["prod"], vec![manager(10, RunnerStatus::Online)]),
+            runner(2, &["staging"], vec![manager(20, RunnerStatus::Offline)]),
+            runner(3, &["dev"], vec![]),
+        ]);
 
         let offline = conductor
             .list_offline_runners(RunnerFilters::default())
@@ -365,199 +272,74 @@ mod tests {
             .unwrap();
 
         // Only runner 2 has an offline manager
-        assert_eq!(offline.len(), 1);
-        assert_eq!(offline[0].id, 2);
-
-        for mock in &mocks {
-            mock.assert_async().await;
-        }
+        assert_eq!(ids(&offline), vec![2]);
     }
 
     #[tokio::test]
     async fn test_list_offline_runners_with_multiple_managers() {
-        let mut server = Server::new_async().await;
-        let mocks = setup_runner_mocks(
-            &mut server,
-            &[
-                // Runner 1: two managers, one online + one offline → NOT offline
-                (1, "online", &["prod"], &[(10, "online"), (11, "offline")]),
-                // Runner 2: two managers, both offline → IS offline
-                (
-                    2,
-                    "offline",
-                    &["staging"],
-                    &[(20, "offline"), (21, "offline")],
-                ),
-                // Runner 3: one manager, online → NOT offline
-                (3, "online", &["dev"], &[(30, "online")]),
-            ],
-        )
-        .await;
-
-        let client = GitLabClient::new(server.url(), "test-token".to_string()).unwrap();
-        let conductor = Conductor::new(client);
+        let conductor = conductor(vec![
+            // One online + one offline manager → NOT offline
+            runner(
+                1,
+                &["prod"],
+                vec![
+                    manager(10, RunnerStatus::Online),
+                    manager(11, RunnerStatus::Offline),
+                ],
+            ),
+            // Both managers offline → IS offline
+            runner(
+                2,
+                &["staging"],
+                vec![
+                    manager(20, RunnerStatus::Offline),
+                    manager(21, RunnerStatus::Offline),
+                ],
+            ),
+            runner(3, &["dev"], vec![manager(30, RunnerStatus::Online)]),
+        ]);
 
         let offline = conductor
             .list_offline_runners(RunnerFilters::default())
             .await
             .unwrap();
 
-        // Only runner 2 should be offline (all managers offline)
-        assert_eq!(offline.len(), 1);
-        assert_eq!(offline[0].id, 2);
-
-        for mock in &mocks {
-            mock.assert_async().await;
-        }
+        assert_eq!(ids(&offline), vec![2]);
     }
 
     #[tokio::test]
     async fn test_list_runners_without_managers() {
-        let mut server = Server::new_async().await;
-        let mocks = setup_runner_mocks(
-            &mut server,
-            &[
-                (1, "online", &["prod"], &[(10, "online")]),
-                (2, "online", &["staging"], &[]),
-            ],
-        )
-        .await;
-
-        let client = GitLabClient::new(server.url(), "test-token".to_string()).unwrap();
-        let conductor = Conductor::new(client);
+        let conductor = conductor(vec![
+            runner(1, &["prod"], vec![manager(10, RunnerStatus::Online)]),
+            runner(2, &["staging"], vec![]),
+        ]);
 
         let empty = conductor
             .list_runners_without_managers(RunnerFilters::default())
             .await
             .unwrap();
 
-        // Only runner 2 has no managers
-        assert_eq!(empty.len(), 1);
-        assert_eq!(empty[0].id, 2);
-
-        for mock in &mocks {
-            mock.assert_async().await;
-        }
-    }
-
-    #[tokio::test]
-    async fn test_detect_rotating_runners_finds_multi_manager() {
-        let mut server = Server::new_async().await;
-        let mocks = setup_runner_mocks(
-            &mut server,
-            &[
-                // Runner 1: two managers (rotation in progress)
-                (1, "online", &["prod"], &[(10, "online"), (11, "offline")]),
-                // Runner 2: single manager (no rotation)
-                (2, "online", &["staging"], &[(20, "online")]),
-            ],
-        )
-        .await;
-
-        let client = GitLabClient::new(server.url(), "test-token".to_string()).unwrap();
-        let conductor = Conductor::new(client);
-
-        let rotating = conductor
-            .detect_rotating_runners(RunnerFilters::default())
-            .await
-            .unwrap();
-
-        assert_eq!(rotating.len(), 1);
-        assert_eq!(rotating[0].id, 1);
-        assert_eq!(rotating[0].managers.len(), 2);
-
-        for mock in &mocks {
-            mock.assert_async().await;
-        }
-    }
-
-    #[tokio::test]
-    async fn test_detect_rotating_runners_empty_when_no_rotation() {
-        let mut server = Server::new_async().await;
-        let mocks = setup_runner_mocks(
-            &mut server,
-            &[
-                (1, "online", &["prod"], &[(10, "online")]),
-                (2, "online", &["staging"], &[(20, "online")]),
-            ],
-        )
-        .await;
-
-        let client = GitLabClient::new(server.url(), "test-token".to_string()).unwrap();
-        let conductor = Conductor::new(client);
-
-        let rotating = conductor
-            .detect_rotating_runners(RunnerFilters::default())
-            .await
-            .unwrap();
-
-        assert!(rotating.is_empty());
-
-        for mock in &mocks {
-            mock.assert_async().await;
-        }
+        assert_eq!(ids(&empty), vec![2]);
     }
 
     #[tokio::test]
-    async fn test_detect_rotating_runners_excludes_no_managers() {
-        let mut server = Server::new_async().await;
-        let mocks = setup_runner_mocks(
-            &mut server,
-            &[
-                (1, "online", &["prod"], &[]),
-                (
-                    2,
-                    "online",
-                    &["staging"],
-                    &[(20, "offline"), (21, "online")],
-                ),
-            ],
-        )
-        .await;
-
-        let client = GitLabClient::new(server.url(), "test-token".to_string()).unwrap();
-        let conductor = Conductor::new(client);
-
-        let rotating = conductor
-            .detect_rotating_runners(RunnerFilters::default())
+    async fn test_list_uncontacted_runners_uses_contacted_at() {
+        let conductor = conductor(vec![
+            runner(1, &["prod"], vec![manager(10, RunnerStatus::Online)]),
+            runner(2, &["staging"], vec![]),
+        ]);
+
+        // Managers were last contacted in January 2024
+        let uncontacted = conductor
+            .list_uncontacted_runners(RunnerFilters::default(), 3600)
             .await
             .unwrap();
+        assert_eq!(ids(&uncontacted), vec![1]);
 
-        assert_eq!(rotating.len(), 1);
-        assert_eq!(rotating[0].id, 2);
-
-        for mock in &mocks {
-            mock.assert_async().await;
-        }
-    }
-
-    #[tokio::test]
-    async fn test_detect_rotating_runners_three_managers() {
-        let mut server = Server::new_async().await;
-        let mocks = setup_runner_mocks(
-            &mut server,
-            &[(
-                1,
-                "online",
-                &["prod"],
-                &[(10, "offline"), (11, "stale"), (12, "online")],
-            )],
-        )
-        .await;
-
-        let client = GitLabClient::new(server.url(), "test-token".to_string()).unwrap();
-        let conductor = Conductor::new(client);
-
-        let rotating = conductor
-            .detect_rotating_runners(RunnerFilters::default())
+        let uncontacted = conductor
+            .list_uncontacted_runners(RunnerFilters::default(), u32::MAX as u64 * 4)
             .await
             .unwrap();
-
-        assert_eq!(rotating.len(), 1);
-        assert_eq!(rotating[0].managers.len(), 3);
-
-        for mock in &mocks {
-            mock.assert_async().await;
-        }
+        assert!(uncontacted.is_empty());
     }
 }
@@ -0,0 +1,141 @@
+use super::AppConfig;
+use std::ops::Range;
+use toml_edit::ImDocument;
+
+/// Something wrong with a config file, at a line when it can be placed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+/// Syntax errors, unknown keys and invalid values in the contents of a
+/// config file, in line order. Parsing stops at the first invalid value, so
+/// at most one is reported next to every unknown key.
+pub fn check(contents: &str) -> Vec<Problem> {
+    let document = match ImDocument::parse(contents) {
+        Ok(document) => document,
+        Err(e) => {
+            return vec![Problem {
+                line: e.span().map(|span| line_at(contents, span)),
+                message: e.message().to_string(),
+            }]
+        }
+    };
+
+    let mut unknown = Vec::new();
+    let parsed: Result<AppConfig, toml::de::Error> =
+        serde_ignored::deserialize(toml::Deserializer::new(contents), |path| {
+            unknown.push(path.to_string())
+        });
+    let mut problems: Vec<Problem> = unknown
+        .into_iter()
+        .map(|key| Problem {
+            line: line_of(&document, &key),
+            message: format!("unknown key `{}`", key),
+        })
+        .collect();
+
+    match parsed {
+        Err(e) => problems.push(Problem {
+            line: e.span().map(|span| line_at(contents, span)),
+            message: e.message().trim_end().to_string(),
+        }),
+        Ok(config) => {
+            if let Err(e) = config.validate() {
+                // Validation errors start with the offending setting,
+                // e.g. "notify.sinks[0]: …"
+                let message = format!("{:#}", e);
+                let line = message
+                    .split_once(": ")
+                    .filter(|(path, _)| !path.contains(' '))
+                    .and_then(|(path, _)| line_of(&document, path));
+                problems.push(Problem { line, message });
+            }
+        }
+    }
+
+    problems.sort_by_key(|problem| problem.line.unwrap_or(usize::MAX));
+    problems
+}
+
+/// 1-based line of the start of `span`
+fn line_at(contents: &str, span: Range<usize>) -> usize {
+    contents[..span.start].matches('\n').count() + 1
+}
+
+/// Line of the setting at `path` (`notify.sinks[0]` or `notify.sinks.0`),
+/// or of its closest parent that exists
+fn line_of(document: &ImDocument<&str>, path: &str) -> Option<usize> {
+    let mut item = document.as_item();
+    let mut span = None;
+    let path = path.replace('[', ".").replace(']', "");
+    for segment in path.split('.').filter(|s| !s.is_empty() && *s != "?") {
+        let next = match segment.parse::<usize>() {
+            Ok(index) => item.get(index).map(|next| (next.span(), next)),
+            Err(_) => item
+                .as_table_like()
+                .and_then(|table| table.get_key_value(segment))
+                .map(|(key, next)| (key.span().or_else(|| next.span()), next)),
+        };
+        let Some((next_span, next)) = next else {
+            break;
+        };
+        span = next_span.or(span);
+        item = next;
+    }
+    span.map(|span| line_at(document.raw(), span))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(problems: &[Problem]) -> Vec<Option<usize>> {
+        problems.iter().map(|p| p.line).collect()
+    }
+
+    #[test]
+    fn test_valid_file_has_no_problems() {
+        assert!(check("poll_interval_secs = 10\n[notify]\nhealth_threshold = 50.0\n").is_empty());
+    }
+
+    #[test]
+    fn test_reports_every_unknown_key_and_the_invalid_value() {
+        let contents = "\
+poll_intervall_secs = 10
+gitlab_host = \"https://gitlab.example.com\"
+colour = true
+poll_timeout_secs = \"soon\"
+";
+        let problems = check(contents);
+        assert_eq!(lines(&problems), vec![Some(1), Some(3), Some(4)]);
+        assert_eq!(problems[0].message, "unknown key `poll_intervall_secs`");
+        assert_eq!(problems[1].message, "unknown key `colour`");
+        assert!(problems[2].message.contains("invalid type"));
+    }
+
+    #[test]
+    fn test_places_validation_errors_and_nested_unknown_keys() {
+        let contents = "\
+poll_interval_secs = 10
+
+[[notify.sinks]]
+type = \"webhook\"
+url = \"example.com\"
+";
+        let problems = check(contents);
+        assert_eq!(lines(&problems), vec![Some(3)]);
+        assert!(problems[0].message.starts_with("notify.sinks[0]: "));
+
+        let problems = check("[columns]\nrunners = [\"id\"]\nworker = [\"id\"]\n");
+        assert_eq!(lines(&problems), vec![Some(3)]);
+        assert!(problems[0].message.contains("unknown field `worker`"));
+    }
+
+    #[test]
+    fn test_reports_syntax_errors() {
+        let problems = check("poll_interval_secs = 10\nflap_window_secs =\n");
+        assert_eq!(lines(&problems), vec![Some(2)]);
+    }
+}
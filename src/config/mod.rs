@@ -0,0 +1,733 @@
+mod check;
+mod show;
+
+use crate::alerts::{AlertEngine, AlertRule};
+use crate::cli::ConfigCommand;
+use crate::models::coverage::normalize_tag_set;
+use crate::models::timeline::FlapPolicy;
+use crate::models::version::parse_version;
+use crate::notify::NotifyConfig;
+use crate::presets::{self, Preset};
+use crate::tui::columns::ColumnsConfig;
+use crate::tui::keymap::KeysConfig;
+use anyhow::{bail, Context, Result};
+use chrono::Duration;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use toml_edit::{ArrayOfTables, DocumentMut, Item};
+
+/// GitLab instance used when no host is configured anywhere
+pub const DEFAULT_GITLAB_HOST: &str = "https://gitlab.com";
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct AppConfig {
+    pub poll_interval_secs: u64,
+    pub poll_timeout_secs: u64,
+    pub gitlab_host: Option<String>,
+    pub gitlab_token: Option<String>,
+    /// Runner version the `drift` command compares against; defaults to the
+    /// newest version seen in the fleet
+    pub target_version: Option<String>,
+    /// Minor releases a runner may trail the GitLab server before the
+    /// workers view flags it
+    pub max_minors_behind: u64,
+    /// Tag sets the `coverage` command checks, e.g. `[["docker", "arm64"]]`
+    pub required_tag_sets: Vec<Vec<String>>,
+    /// `.gitlab-ci.yml` whose jobs `coverage` and `pipeline` check
+    pub ci_file: Option<PathBuf>,
+    /// Manager status changes (or returns) a runner may have within
+    /// `flap_window_secs` before the rotation view flags it as flapping
+    pub max_manager_flaps: usize,
+    pub flap_window_secs: u64,
+    /// Notifications on fleet changes between polls, see `[notify]`
+    pub notify: NotifyConfig,
+    /// Alert rules evaluated on every poll, see `[[alerts]]`
+    pub alerts: Vec<AlertRule>,
+    /// Column layout per results view, see `[columns]` in the README
+    pub columns: ColumnsConfig,
+    /// Saved queries, see `[[presets]]`
+    pub presets: Vec<Preset>,
+    /// Key bindings of the TUI, see `[keys]`
+    pub keys: KeysConfig,
+    /// Capture the mouse for clicks and scrolling; off leaves the terminal's
+    /// own text selection working
+    pub mouse: bool,
+    /// Hold TUI polling while the terminal window is unfocused
+    pub pause_when_unfocused: bool,
+    /// Other GitLab instances the TUI can switch to, see `[profiles]`
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// A GitLab instance under `[profiles.<name>]`; without a token of its own
+/// it uses the one the TUI started with
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    pub gitlab_host: String,
+    pub gitlab_token: Option<String>,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval_secs: 30,
+            poll_timeout_secs: 1800,
+            gitlab_host: None,
+            gitlab_token: None,
+            target_version: None,
+            max_minors_behind: 2,
+            required_tag_sets: Vec::new(),
+            ci_file: None,
+            max_manager_flaps: 3,
+            flap_window_secs: 3600,
+            notify: NotifyConfig::default(),
+            alerts: Vec::new(),
+            columns: ColumnsConfig::default(),
+            presets: Vec::new(),
+            keys: KeysConfig::default(),
+            mouse: true,
+            pause_when_unfocused: false,
+            profiles: BTreeMap::new(),
+        }
+    }
+}
+
+impl AppConfig {
+    /// The file to read: `explicit` (from `--config`) when given, otherwise
+    /// the first of `./config.toml` and `~/.config/igor/config.toml` that
+    /// exists
+    pub fn find(explicit: Option<&Path>) -> Option<PathBuf> {
+        match explicit {
+            Some(path) => Some(path.to_path_buf()),
+            None => config_paths().into_iter().find(|path| path.exists()),
+        }
+    }
+
+    /// Load the file `find` picks, or the defaults when there is none. An
+    /// unreadable or invalid file is an error rather than the defaults.
+    pub fn load(explicit: Option<&Path>) -> Result<Self> {
+        match Self::find(explicit) {
+            Some(path) => Self::load_from(&path),
+            None => Ok(AppConfig::default()),
+        }
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::load_from_str(&contents)
+            .with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    pub fn load_from_str(toml_str: &str) -> Result<Self> {
+        let config: AppConfig = toml::from_str(toml_str)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.poll_interval_secs == 0 {
+            bail!("poll_interval_secs: must be at least 1");
+        }
+        if let Some(raw) = &self.target_version {
+            if parse_version(raw).is_none() {
+                bail!("target_version: '{}' is not a runner version", raw);
+            }
+        }
+        if let Some(i) = self
+            .required_tag_sets
+            .iter()
+            .position(|set| normalize_tag_set(set).is_empty())
+        {
+            bail!("required_tag_sets[{}]: a tag set needs at least one tag", i);
+        }
+        if self.flap_window_secs == 0 {
+            bail!("flap_window_secs: must be at least 1");
+        }
+        self.notify.validate()?;
+        // Compiling the rules checks their expressions
+        AlertEngine::new(&self.alerts)?;
+        presets::validate(&self.presets)?;
+        for (name, profile) in &self.profiles {
+            let host = &profile.gitlab_host;
+            if !host.starts_with("https://") && !host.starts_with("http://") {
+                bail!(
+                    "profiles.{}.gitlab_host: '{}' is not an http(s) URL",
+                    name,
+                    host
+                );
+            }
+        }
+        self.keys.keymap()?;
+        self.columns.validate()
+    }
+
+    /// GitLab URL by priority: flag, env, config.toml, then gitlab.com
+    pub fn resolve_host(&self, overrides: &Overrides) -> (String, Source) {
+        if let Some(host) = &overrides.host {
+            return host.clone();
+        }
+        match &self.gitlab_host {
+            Some(host) => (host.clone(), Source::File),
+            None => (DEFAULT_GITLAB_HOST.to_string(), Source::Default),
+        }
+    }
+
+    /// Access token by priority: flag, env, then config.toml
+    pub fn resolve_token(&self, overrides: &Overrides) -> Option<(String, Source)> {
+        overrides
+            .token
+            .clone()
+            .or_else(|| self.gitlab_token.clone().map(|token| (token, Source::File)))
+    }
+
+    pub fn flap_policy(&self) -> FlapPolicy {
+        FlapPolicy {
+            max_flaps: self.max_manager_flaps,
+            window: Duration::seconds(self.flap_window_secs as i64),
+        }
+    }
+
+    /// Human-readable differences from `self` to `new`, one per changed
+    /// setting. Tables are named rather than spelled out and the token is
+    /// never shown.
+    pub fn changes(&self, new: &AppConfig) -> Vec<String> {
+        let mut changes = Vec::new();
+        macro_rules! value {
+            ($($field:ident),*) => {$(
+                if self.$field != new.$field {
+                    changes.push(format!(
+                        "{}: {:?} → {:?}",
+                        stringify!($field),
+                        self.$field,
+                        new.$field
+                    ));
+                }
+            )*};
+        }
+        macro_rules! named {
+            ($($field:ident),*) => {$(
+                if self.$field != new.$field {
+                    changes.push(format!("{} changed", stringify!($field)));
+                }
+            )*};
+        }
+        value!(
+            poll_interval_secs,
+            poll_timeout_secs,
+            gitlab_host,
+            target_version,
+            max_minors_behind,
+            max_manager_flaps,
+            flap_window_secs,
+            mouse,
+            pause_when_unfocused
+        );
+        named!(
+            gitlab_token,
+            required_tag_sets,
+            ci_file,
+            notify,
+            alerts,
+            columns,
+            presets,
+            keys,
+            profiles
+        );
+        changes
+    }
+
+    /// The configured drift target, parsed
+    pub fn target_version(&self) -> Option<Version> {
+        self.target_version.as_deref().and_then(parse_version)
+    }
+}
+
+/// Where an effective setting came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Flag,
+    Env,
+    File,
+    Default,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Flag => write!(f, "flag"),
+            Source::Env => write!(f, "env"),
+            Source::File => write!(f, "file"),
+            Source::Default => write!(f, "default"),
+        }
+    }
+}
+
+/// Host and token given as `--host`/`--token` or `GITLAB_HOST`/`GITLAB_TOKEN`,
+/// which win over config.toml
+#[derive(Debug, Clone, Default)]
+pub struct Overrides {
+    pub host: Option<(String, Source)>,
+    pub token: Option<(String, Source)>,
+}
+
+/// `config check` and `config show`
+pub fn run_command(
+    command: &ConfigCommand,
+    explicit: Option<&Path>,
+    overrides: &Overrides,
+) -> Result<()> {
+    let path = AppConfig::find(explicit);
+    let contents = path
+        .as_deref()
+        .map(|path| {
+            std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))
+        })
+        .transpose()?;
+
+    match command {
+        ConfigCommand::Check => {
+            let (Some(path), Some(contents)) = (&path, &contents) else {
+                let searched: Vec<String> = config_paths()
+                    .iter()
+                    .map(|p| p.display().to_string())
+                    .collect();
+                println!(
+                    "No config file found ({}); defaults apply",
+                    searched.join(", ")
+                );
+                return Ok(());
+            };
+            let problems = check::check(contents);
+            if problems.is_empty() {
+                println!("✓ {} is valid", path.display());
+                return Ok(());
+            }
+            for problem in &problems {
+                match problem.line {
+                    Some(line) => println!("{}:{}: {}", path.display(), line, problem.message),
+                    None => println!("{}: {}", path.display(), problem.message),
+                }
+            }
+            bail!("{} problem(s) in {}", problems.len(), path.display())
+        }
+        ConfigCommand::Show => {
+            let config = match (&path, &contents) {
+                (Some(path), Some(contents)) => {
+                    AppConfig::load_from_str(contents).with_context(|| {
+                        format!(
+                            "Invalid config file {}; run `config check` for details",
+                            path.display()
+                        )
+                    })?
+                }
+                _ => AppConfig::default(),
+            };
+            let file = path.as_deref().zip(contents.as_deref());
+            print!("{}", show::render(&config, file, overrides)?);
+            Ok(())
+        }
+    }
+}
+
+/// Notices when the config file `AppConfig::load` would read is edited,
+/// created or replaced, by comparing modification times on each check
+pub struct ConfigWatcher {
+    paths: Vec<PathBuf>,
+    seen: Option<(PathBuf, SystemTime)>,
+}
+
+impl ConfigWatcher {
+    /// Watch `explicit` (from `--config`), or the default locations
+    pub fn new(explicit: Option<&Path>) -> Self {
+        match explicit {
+            Some(path) => Self::with_paths(vec![path.to_path_buf()]),
+            None => Self::with_paths(config_paths()),
+        }
+    }
+
+    fn with_paths(paths: Vec<PathBuf>) -> Self {
+        let mut watcher = Self { paths, seen: None };
+        watcher.seen = watcher.current();
+        watcher
+    }
+
+    /// The first existing candidate and its modification time
+    fn current(&self) -> Option<(PathBuf, SystemTime)> {
+        self.paths.iter().find_map(|path| {
+            let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+            Some((path.clone(), modified))
+        })
+    }
+
+    /// The re-read config if the file changed since the last check. A
+    /// removed file is ignored rather than reverting to the defaults.
+    pub fn check(&mut self) -> Option<(PathBuf, Result<AppConfig>)> {
+        let current = self.current()?;
+        if self.seen.as_ref() == Some(&current) {
+            return None;
+        }
+        let (path, _) = current.clone();
+        self.seen = Some(current);
+        let config = AppConfig::load_from(&path);
+        Some((path, config))
+    }
+}
+
+fn config_paths() -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+
+    // CWD config.toml
+    if let Ok(cwd) = std::env::current_dir() {
+        paths.push(cwd.join("config.toml"));
+    }
+
+    // ~/.config/igor/config.toml
+    paths.extend(user_config_path());
+
+    paths
+}
+
+fn user_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("igor").join("config.toml"))
+}
+
+/// Add `preset` to the `[[presets]]` of the config file, replacing one with
+/// the same name and keeping the rest of the file as written. Without a
+/// config file, `~/.config/igor/config.toml` is created. Returns the file.
+pub fn save_preset(explicit: Option<&Path>, preset: &Preset) -> Result<PathBuf> {
+    let path = AppConfig::find(explicit)
+        .or_else(user_config_path)
+        .context("No config directory to save the preset in")?;
+    let contents = if path.exists() {
+        std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?
+    } else {
+        String::new()
+    };
+    let mut document: DocumentMut = contents
+        .parse()
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    let entry: DocumentMut = toml::to_string(preset)?.parse()?;
+    let entry = entry.as_table().clone();
+
+    let presets = document
+        .entry("presets")
+        .or_insert_with(|| Item::ArrayOfTables(ArrayOfTables::new()))
+        .as_array_of_tables_mut()
+        .with_context(|| format!("presets in {} is not a list of tables", path.display()))?;
+    let existing = presets
+        .iter_mut()
+        .find(|table| table.get("name").and_then(Item::as_str) == Some(preset.name.as_str()));
+    match existing {
+        Some(table) => *table = entry,
+        None => presets.push(entry),
+    }
+
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    std::fs::write(&path, document.to_string())
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::Severity;
+
+    #[test]
+    fn test_default_config() {
+        let config = AppConfig::default();
+        assert_eq!(config.poll_interval_secs, 30);
+        assert_eq!(config.poll_timeout_secs, 1800);
+        assert!(config.gitlab_host.is_none());
+        assert!(config.gitlab_token.is_none());
+    }
+
+    #[test]
+    fn test_load_from_full_toml() {
+        let toml_str = r#"
+            poll_interval_secs = 60
+            poll_timeout_secs = 900
+            gitlab_host = "https://gitlab.example.com"
+            gitlab_token = "glpat-test-token"
+        "#;
+
+        let config = AppConfig::load_from_str(toml_str).unwrap();
+        assert_eq!(config.poll_interval_secs, 60);
+        assert_eq!(config.poll_timeout_secs, 900);
+        assert_eq!(
+            config.gitlab_host,
+            Some("https://gitlab.example.com".to_string())
+        );
+        assert_eq!(config.gitlab_token, Some("glpat-test-token".to_string()));
+    }
+
+    #[test]
+    fn test_load_from_partial_toml_uses_defaults() {
+        let toml_str = r#"
+            poll_interval_secs = 10
+        "#;
+
+        let config = AppConfig::load_from_str(toml_str).unwrap();
+        assert_eq!(config.poll_interval_secs, 10);
+        assert_eq!(config.poll_timeout_secs, 1800);
+        assert!(config.gitlab_host.is_none());
+        assert!(config.gitlab_token.is_none());
+    }
+
+    #[test]
+    fn test_load_from_empty_toml_uses_defaults() {
+        let toml_str = "";
+        let config = AppConfig::load_from_str(toml_str).unwrap();
+        assert_eq!(config, AppConfig::default());
+    }
+
+    #[test]
+    fn test_load_from_invalid_toml_returns_error() {
+        let toml_str = "this is not valid toml [[[";
+        let result = AppConfig::load_from_str(toml_str);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_load_from_toml_with_only_host() {
+        let toml_str = r#"
+            gitlab_host = "https://gitlab.com"
+        "#;
+
+        let config = AppConfig::load_from_str(toml_str).unwrap();
+        assert_eq!(config.gitlab_host, Some("https://gitlab.com".to_string()));
+        assert_eq!(config.poll_interval_secs, 30);
+    }
+
+    #[test]
+    fn test_load_columns_table() {
+        let toml_str = r#"
+            [columns]
+            runners = ["id", "status", "version:12", "tags:30%"]
+        "#;
+
+        let config = AppConfig::load_from_str(toml_str).unwrap();
+        let runners = config.columns.runners.unwrap();
+        assert_eq!(runners.len(), 4);
+        assert_eq!(runners[2].to_string(), "version:12");
+        assert!(config.columns.workers.is_none());
+    }
+
+    #[test]
+    fn test_load_columns_rejects_column_from_other_view() {
+        let toml_str = r#"
+            [columns]
+            rotation = ["runner_id", "contacted"]
+        "#;
+        assert!(AppConfig::load_from_str(toml_str).is_err());
+    }
+
+    #[test]
+    fn test_load_target_version() {
+        let config = AppConfig::load_from_str(r#"target_version = "v17.5""#).unwrap();
+        assert_eq!(config.target_version(), Some(Version::new(17, 5, 0)));
+
+        assert!(AppConfig::load_from_str(r#"target_version = "latest""#).is_err());
+    }
+
+    #[test]
+    fn test_load_required_tag_sets() {
+        let config = AppConfig::load_from_str(
+            r#"
+            required_tag_sets = [["docker", "arm64"], ["windows"]]
+            ci_file = ".gitlab-ci.yml"
+        "#,
+        )
+        .unwrap();
+        assert_eq!(config.required_tag_sets.len(), 2);
+        assert_eq!(config.ci_file, Some(PathBuf::from(".gitlab-ci.yml")));
+
+        assert!(AppConfig::load_from_str(r#"required_tag_sets = [[" "]]"#).is_err());
+    }
+
+    #[test]
+    fn test_load_profiles() {
+        let config = AppConfig::load_from_str(
+            r#"
+            [profiles.staging]
+            gitlab_host = "https://staging.example.com"
+
+            [profiles.prod]
+            gitlab_host = "https://gitlab.example.com"
+            gitlab_token = "glpat-prod"
+        "#,
+        )
+        .unwrap();
+        let names: Vec<&str> = config.profiles.keys().map(String::as_str).collect();
+        assert_eq!(names, vec!["prod", "staging"]);
+        assert_eq!(config.profiles["staging"].gitlab_token, None);
+
+        let err = AppConfig::load_from_str("[profiles.x]\ngitlab_host = \"gitlab.example.com\"")
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "profiles.x.gitlab_host: 'gitlab.example.com' is not an http(s) URL"
+        );
+        assert!(AppConfig::load_from_str("[profiles.x]\ngitlab_token = \"t\"").is_err());
+    }
+
+    #[test]
+    fn test_load_rejects_zero_poll_interval() {
+        let err = AppConfig::load_from_str("poll_interval_secs = 0").unwrap_err();
+        assert_eq!(err.to_string(), "poll_interval_secs: must be at least 1");
+    }
+
+    #[test]
+    fn test_load_flap_policy() {
+        let config =
+            AppConfig::load_from_str("max_manager_flaps = 5\nflap_window_secs = 600").unwrap();
+        let policy = config.flap_policy();
+        assert_eq!(policy.max_flaps, 5);
+        assert_eq!(policy.window, Duration::minutes(10));
+
+        assert!(AppConfig::load_from_str("flap_window_secs = 0").is_err());
+    }
+
+    #[test]
+    fn test_load_notify_sinks() {
+        let config = AppConfig::load_from_str(
+            r#"
+            [notify]
+            health_threshold = 80.0
+
+            [[notify.sinks]]
+            type = "slack"
+            url = "https://hooks.slack.com/services/T000/B000/XXX"
+
+            [[notify.sinks]]
+            type = "command"
+            command = ["notify-send", "GitLab runners"]
+        "#,
+        )
+        .unwrap();
+        assert_eq!(config.notify.health_threshold, 80.0);
+        assert_eq!(config.notify.sinks.len(), 2);
+
+        let bad = "[[notify.sinks]]\ntype = \"webhook\"\nurl = \"ftp://x\"";
+        let err = AppConfig::load_from_str(bad).unwrap_err();
+        assert!(err.to_string().starts_with("notify.sinks[0]"));
+    }
+
+    #[test]
+    fn test_load_alert_rules() {
+        let config = AppConfig::load_from_str(
+            r#"
+            [[alerts]]
+            name = "prod-stale"
+            selector = "tags contains prod"
+            condition = "uncontacted > 1h and managers > 0"
+            severity = "critical"
+            for_secs = 300
+
+            [[alerts]]
+            name = "paused"
+            condition = "paused"
+        "#,
+        )
+        .unwrap();
+        assert_eq!(config.alerts.len(), 2);
+        assert_eq!(config.alerts[0].severity, Severity::Critical);
+        assert_eq!(config.alerts[1].severity, Severity::Warning);
+        assert_eq!(config.alerts[1].for_secs, 0);
+
+        let bad = "[[alerts]]\nname = \"x\"\ncondition = \"uncontacted > soon\"";
+        let err = AppConfig::load_from_str(bad).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("alerts[0]: condition: invalid duration"));
+    }
+
+    #[test]
+    fn test_changes_name_settings_and_hide_token() {
+        let old = AppConfig::default();
+        let new = AppConfig::load_from_str(
+            "poll_interval_secs = 10\ngitlab_token = \"glpat-secret\"\n[[alerts]]\nname = \"a\"\ncondition = \"paused\"",
+        )
+        .unwrap();
+        let changes = old.changes(&new);
+        assert_eq!(
+            changes,
+            vec![
+                "poll_interval_secs: 30 → 10".to_string(),
+                "gitlab_token changed".to_string(),
+                "alerts changed".to_string(),
+            ]
+        );
+        assert!(old.changes(&old.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_save_preset_keeps_the_file_and_replaces_by_name() {
+        let dir = std::env::temp_dir().join(format!("config-preset-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+        std::fs::write(&path, "# polling\npoll_interval_secs = 5\n").unwrap();
+
+        let form = crate::tui::filter_form::FilterForm {
+            tags: "docker".to_string(),
+            ..Default::default()
+        };
+        let mut preset = form.to_preset("docker", crate::tui::app::Command::Fetch);
+        save_preset(Some(&path), &preset).unwrap();
+        preset.sort = Some("version".to_string());
+        save_preset(Some(&path), &preset).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(contents.starts_with("# polling\npoll_interval_secs = 5\n"));
+        let config = AppConfig::load_from(&path).unwrap();
+        assert_eq!(config.presets, vec![preset]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_watcher_reloads_on_change() {
+        let dir = std::env::temp_dir().join(format!("config-watch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+        let mut watcher = ConfigWatcher::with_paths(vec![path.clone()]);
+        assert!(watcher.check().is_none());
+
+        std::fs::write(&path, "poll_interval_secs = 5").unwrap();
+        let (loaded, config) = watcher.check().unwrap();
+        assert_eq!(loaded, path);
+        assert_eq!(config.unwrap().poll_interval_secs, 5);
+        assert!(watcher.check().is_none());
+
+        // Force a different mtime even on coarse-grained filesystems
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        std::fs::write(&path, "flap_window_secs = 0").unwrap();
+        file.set_modified(SystemTime::now() + std::time::Duration::from_secs(5))
+            .unwrap();
+        let (_, config) = watcher.check().unwrap();
+        assert!(config.is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(watcher.check().is_none());
+    }
+
+    #[test]
+    fn test_config_paths_includes_cwd() {
+        let paths = config_paths();
+        assert!(!paths.is_empty());
+        // First path should be CWD/config.toml
+        let cwd = std::env::current_dir().unwrap();
+        assert_eq!(paths[0], cwd.join("config.toml"));
+    }
+}
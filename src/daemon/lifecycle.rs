@@ -0,0 +1,134 @@
+use anyhow::Result;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::interval;
+
+/// What a signal asks the daemon to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    /// SIGTERM or SIGINT: finish the current poll and exit
+    Shutdown,
+    /// SIGHUP: re-read config.toml
+    Reload,
+}
+
+#[cfg(unix)]
+pub struct Signals {
+    term: tokio::signal::unix::Signal,
+    int: tokio::signal::unix::Signal,
+    hup: tokio::signal::unix::Signal,
+}
+
+#[cfg(unix)]
+impl Signals {
+    pub fn new() -> Result<Self> {
+        use tokio::signal::unix::{signal, SignalKind};
+        Ok(Self {
+            term: signal(SignalKind::terminate())?,
+            int: signal(SignalKind::interrupt())?,
+            hup: signal(SignalKind::hangup())?,
+        })
+    }
+
+    pub async fn recv(&mut self) -> Signal {
+        tokio::select! {
+            _ = self.term.recv() => Signal::Shutdown,
+            _ = self.int.recv() => Signal::Shutdown,
+            _ = self.hup.recv() => Signal::Reload,
+        }
+    }
+}
+
+/// Only Ctrl-C is available; there is no reload signal
+#[cfg(not(unix))]
+pub struct Signals;
+
+#[cfg(not(unix))]
+impl Signals {
+    pub fn new() -> Result<Self> {
+        Ok(Self)
+    }
+
+    pub async fn recv(&mut self) -> Signal {
+        let _ = tokio::signal::ctrl_c().await;
+        Signal::Shutdown
+    }
+}
+
+/// A state change reported to the service manager
+#[derive(Debug, Clone, Copy)]
+pub enum ServiceState<'a> {
+    Ready,
+    Reloading,
+    Stopping,
+    Watchdog,
+    Status(&'a str),
+}
+
+/// `sd_notify` messages to systemd. Disabled, or outside a `Type=notify`
+/// unit (no `NOTIFY_SOCKET`), every call is a no-op.
+#[derive(Debug, Clone, Copy)]
+pub struct Systemd {
+    enabled: bool,
+    watchdog: Option<Duration>,
+}
+
+impl Systemd {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            watchdog: if enabled { watchdog_interval() } else { None },
+        }
+    }
+
+    /// Ping the watchdog at half of `WatchdogSec`, when the unit sets one,
+    /// from its own task so a slow poll doesn't hold the pings up
+    pub fn spawn_watchdog(self) -> Option<JoinHandle<()>> {
+        let period = self.watchdog?;
+        Some(tokio::spawn(async move {
+            let mut pings = interval(period);
+            loop {
+                pings.tick().await;
+                self.notify(ServiceState::Watchdog);
+            }
+        }))
+    }
+
+    pub fn notify(&self, state: ServiceState) {
+        if self.enabled {
+            if let Err(e) = send(state) {
+                tracing::warn!(error = %e, "Failed to notify systemd");
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn watchdog_interval() -> Option<Duration> {
+    let mut usec = 0;
+    // `interval` panics on a zero period
+    sd_notify::watchdog_enabled(false, &mut usec).then(|| Duration::from_micros((usec / 2).max(1)))
+}
+
+#[cfg(not(unix))]
+fn watchdog_interval() -> Option<Duration> {
+    None
+}
+
+#[cfg(unix)]
+fn send(state: ServiceState) -> std::io::Result<()> {
+    use sd_notify::NotifyState;
+    let state = match state {
+        ServiceState::Ready => NotifyState::Ready,
+        ServiceState::Reloading => NotifyState::Reloading,
+        ServiceState::Stopping => NotifyState::Stopping,
+        ServiceState::Watchdog => NotifyState::Watchdog,
+        ServiceState::Status(status) => NotifyState::Status(status),
+    };
+    sd_notify::notify(false, &[state])
+}
+
+#[cfg(not(unix))]
+fn send(_state: ServiceState) -> std::io::Result<()> {
+    Ok(())
+}
@@ -0,0 +1,321 @@
+mod lifecycle;
+
+use crate::alerts::{AlertEngine, AlertState};
+use crate::cli::{DaemonArgs, FilterArgs};
+use crate::conductor::Conductor;
+use crate::config::AppConfig;
+use crate::models::runner::{Runner, RunnerFilters};
+use crate::notify::Notifier;
+use crate::query::Query;
+use crate::source::RunnerSource;
+use crate::tui::app::HealthSummary;
+use anyhow::Result;
+use chrono::Utc;
+use lifecycle::{ServiceState, Signal, Signals, Systemd};
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::time::{interval, Interval, MissedTickBehavior};
+
+/// Polls the fleet until told to stop, sending fleet changes to the
+/// notification sinks and tracking alerts
+struct Daemon<S> {
+    conductor: Conductor<S>,
+    config: AppConfig,
+    /// `--config`, re-read on SIGHUP; the default locations when unset
+    config_path: Option<PathBuf>,
+    /// Filter flags and `--preset`, resolved again when the config reloads
+    filter_args: FilterArgs,
+    filters: RunnerFilters,
+    query: Option<Query>,
+    notifier: Notifier,
+    alerts: AlertEngine,
+}
+
+fn poll_interval(config: &AppConfig) -> Interval {
+    let mut polls = interval(Duration::from_secs(config.poll_interval_secs.max(1)));
+    // A slow poll delays the next one rather than triggering a burst
+    polls.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    polls
+}
+
+impl<S: RunnerSource> Daemon<S> {
+    /// One poll; failures are logged and the next poll tries again
+    async fn poll(&mut self) -> String {
+        let runners = match self.conductor.fetch_runners(self.filters.clone()).await {
+            Ok(runners) => runners,
+            Err(e) => {
+                tracing::error!(error = format!("{:#}", e), "Poll failed");
+                return "Poll failed".to_string();
+            }
+        };
+        let runners: Vec<Runner> = match &self.query {
+            Some(query) => query.filter_runners(runners),
+            None => runners,
+        };
+
+        self.notifier.scope(&self.filters);
+        let events = self.notifier.observe(&runners);
+        for event in &events {
+            tracing::info!(event = %event, "Fleet change");
+        }
+        self.notifier.dispatcher().send(&events).await;
+
+        for alert in self.alerts.evaluate(&runners, Utc::now()) {
+            match alert.state {
+                AlertState::Resolved => tracing::info!(
+                    rule = %alert.rule,
+                    severity = %alert.severity,
+                    runner_id = alert.runner_id,
+                    "Alert resolved"
+                ),
+                _ => tracing::warn!(
+                    rule = %alert.rule,
+                    severity = %alert.severity,
+                    runner_id = alert.runner_id,
+                    "Alert firing"
+                ),
+            }
+        }
+
+        let health = HealthSummary::from_runners(&runners);
+        let firing = self.alerts.firing();
+        tracing::info!(
+            runners = health.total_count,
+            online = health.online_count,
+            alerts_firing = firing,
+            "Poll complete"
+        );
+        format!(
+            "{} of {} runners online, {} alerts firing",
+            health.online_count, health.total_count, firing
+        )
+    }
+
+    /// Apply a re-read config.toml. Connection settings (host, token) need
+    /// a restart; an invalid file leaves the running config untouched, and
+    /// a `--preset` that no longer resolves keeps the current filters.
+    fn reload(&mut self) -> Result<bool> {
+        let config = AppConfig::load(self.config_path.as_deref())?;
+        self.notifier.reconfigure(&config.notify)?;
+        self.alerts.reload(&config.alerts)?;
+        match self.filter_args.resolve(&config.presets) {
+            Ok((filters, query)) => {
+                self.filters = filters;
+                self.query = query;
+            }
+            Err(e) => tracing::error!(
+                error = format!("{:#}", e),
+                "Preset no longer resolves, keeping the current filters"
+            ),
+        }
+        if config.gitlab_host != self.config.gitlab_host
+            || config.gitlab_token != self.config.gitlab_token
+        {
+            tracing::warn!("GitLab host or token changed; restart the daemon to apply");
+        }
+        let interval_changed = config.poll_interval_secs != self.config.poll_interval_secs;
+        self.config = config;
+        Ok(interval_changed)
+    }
+}
+
+pub async fn run_daemon<S: RunnerSource>(
+    conductor: Conductor<S>,
+    config: AppConfig,
+    config_path: Option<PathBuf>,
+    args: DaemonArgs,
+) -> Result<()> {
+    let mut signals = Signals::new()?;
+    let systemd = Systemd::new(args.sd_notify);
+    let (filters, query) = args.filters.resolve(&config.presets)?;
+    let mut daemon = Daemon {
+        notifier: Notifier::new(&config.notify)?,
+        alerts: AlertEngine::new(&config.alerts)?,
+        conductor,
+        filter_args: args.filters,
+        filters,
+        query,
+        config,
+        config_path,
+    };
+
+    let mut polls = poll_interval(&daemon.config);
+    let watchdog = systemd.spawn_watchdog();
+    let mut ready = false;
+    tracing::info!(
+        poll_interval_secs = daemon.config.poll_interval_secs,
+        alert_rules = daemon.config.alerts.len(),
+        "Daemon started"
+    );
+
+    loop {
+        tokio::select! {
+            _ = polls.tick() => {
+                let status = daemon.poll().await;
+                systemd.notify(ServiceState::Status(&status));
+                // Ready once the first poll has been attempted
+                if !ready {
+                    systemd.notify(ServiceState::Ready);
+                    ready = true;
+                }
+            }
+            signal = signals.recv() => match signal {
+                Signal::Shutdown => {
+                    tracing::info!("Shutting down");
+                    systemd.notify(ServiceState::Stopping);
+                    break;
+                }
+                Signal::Reload => {
+                    systemd.notify(ServiceState::Reloading);
+                    match daemon.reload() {
+                        Ok(interval_changed) => {
+                            if interval_changed {
+                                polls = poll_interval(&daemon.config);
+                            }
+                            tracing::info!(
+                                poll_interval_secs = daemon.config.poll_interval_secs,
+                                alert_rules = daemon.config.alerts.len(),
+                                "Configuration reloaded"
+                            );
+                        }
+                        Err(e) => tracing::error!(
+                            error = format!("{:#}", e),
+                            "Configuration rejected, keeping the running one"
+                        ),
+                    }
+                    systemd.notify(ServiceState::Ready);
+                }
+            },
+        }
+    }
+
+    if let Some(watchdog) = watchdog {
+        watchdog.abort();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::{AlertRule, Severity};
+    use crate::models::status::RunnerStatus;
+    use crate::models::test_support;
+    use crate::source::fake::FakeSource;
+
+    fn runner(id: u64, description: &str) -> Runner {
+        Runner {
+            description: Some(description.to_string()),
+            status: RunnerStatus::Offline,
+            ..test_support::runner(id)
+        }
+    }
+
+    fn daemon(
+        source: FakeSource,
+        config: AppConfig,
+        config_path: Option<PathBuf>,
+    ) -> Daemon<FakeSource> {
+        Daemon {
+            conductor: Conductor::new(source),
+            filter_args: FilterArgs::default(),
+            filters: RunnerFilters::default(),
+            query: None,
+            notifier: Notifier::new(&config.notify).unwrap(),
+            alerts: AlertEngine::new(&config.alerts).unwrap(),
+            config,
+            config_path,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_poll_applies_where_before_alerting() {
+        let source = FakeSource::new(vec![runner(1, "prod-a"), runner(2, "dev-b")]);
+        let config = AppConfig {
+            alerts: vec![AlertRule {
+                name: "down".to_string(),
+                selector: None,
+                condition: "status = offline".to_string(),
+                severity: Severity::Critical,
+                for_secs: 0,
+            }],
+            ..AppConfig::default()
+        };
+        let mut daemon = daemon(source, config, None);
+        daemon.query = Some(Query::parse("description ~ ^prod").unwrap());
+
+        let status = daemon.poll().await;
+        assert_eq!(status, "0 of 1 runners online, 1 alerts firing");
+        assert_eq!(daemon.alerts.alerts()[0].runner_id, 1);
+    }
+
+    #[test]
+    fn test_reload_applies_a_valid_config_and_keeps_the_old_one_otherwise() {
+        let dir = std::env::temp_dir().join(format!("daemon-reload-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+        let mut daemon = daemon(
+            FakeSource::new(vec![]),
+            AppConfig::default(),
+            Some(path.clone()),
+        );
+
+        std::fs::write(
+            &path,
+            "poll_interval_secs = 5\n\n[[alerts]]\nname = \"down\"\ncondition = \"status = offline\"\n",
+        )
+        .unwrap();
+        assert!(daemon.reload().unwrap());
+        assert_eq!(daemon.config.poll_interval_secs, 5);
+        assert!(daemon.alerts.has_rules());
+
+        // Same interval: nothing to reschedule
+        std::fs::write(&path, "poll_interval_secs = 5\n").unwrap();
+        assert!(!daemon.reload().unwrap());
+        assert!(!daemon.alerts.has_rules());
+
+        std::fs::write(&path, "poll_interval_secs = 0\n").unwrap();
+        assert!(daemon.reload().is_err());
+        assert_eq!(daemon.config.poll_interval_secs, 5);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_reload_resolves_the_preset_again() {
+        let dir = std::env::temp_dir().join(format!("daemon-preset-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+        let mut daemon = daemon(
+            FakeSource::new(vec![]),
+            AppConfig::default(),
+            Some(path.clone()),
+        );
+        daemon.filter_args.preset = Some("prod".to_string());
+
+        let preset = |tag: &str| {
+            format!(
+                "[[presets]]\nname = \"prod\"\ncommand = \"fetch\"\ntags = [\"{}\"]\nwhere = \"paused\"\n",
+                tag
+            )
+        };
+        std::fs::write(&path, preset("production")).unwrap();
+        daemon.reload().unwrap();
+        assert_eq!(
+            daemon.filters.tag_list,
+            Some(vec!["production".to_string()])
+        );
+        assert!(daemon.query.is_some());
+
+        std::fs::write(&path, preset("linux")).unwrap();
+        daemon.reload().unwrap();
+        assert_eq!(daemon.filters.tag_list, Some(vec!["linux".to_string()]));
+
+        // A deleted preset keeps the filters in use
+        std::fs::write(&path, "poll_interval_secs = 5\n").unwrap();
+        daemon.reload().unwrap();
+        assert_eq!(daemon.filters.tag_list, Some(vec!["linux".to_string()]));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
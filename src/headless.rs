@@ -0,0 +1,355 @@
+use crate::alerts::{AlertEngine, AlertState};
+use crate::ci::{job_coverage, pipeline_jobs, required_tag_sets, CiJob, JobCoverage};
+use crate::cli::HeadlessQuery;
+use crate::conductor::Conductor;
+use crate::config::AppConfig;
+use crate::models::coverage::tag_coverage;
+use crate::models::drift::DriftReport;
+use crate::models::runner::Runner;
+use crate::models::server::CompatPolicy;
+use crate::models::timeline::ManagerTracker;
+use crate::notify::Notifier;
+use crate::source::RunnerSource;
+use crate::tui::app::{Command, HealthSummary, ManagerRow};
+use crate::tui::sort::sort_rows;
+use anyhow::Result;
+use chrono::{SecondsFormat, Utc};
+use semver::Version;
+use std::time::{Duration, Instant};
+
+/// Command-specific inputs resolved once, before polling starts, plus
+/// state carried between polls
+struct ReportContext {
+    /// Drift target
+    target: Option<Version>,
+    /// Workers compatibility rules, when the server version is known
+    policy: Option<CompatPolicy>,
+    /// Coverage tag sets
+    tag_sets: Vec<Vec<String>>,
+    /// Pipeline jobs
+    jobs: Vec<CiJob>,
+    /// Rotate: manager history across `--watch` polls
+    tracker: ManagerTracker,
+}
+
+/// Print fleet changes since the previous poll and send them to the sinks
+async fn notify(notifier: &mut Notifier, runners: &[Runner]) {
+    let events = notifier.observe(runners);
+    for event in &events {
+        println!("  ! {}", event);
+    }
+    notifier.dispatcher().send(&events).await;
+}
+
+/// Print the alerts that fired or resolved in this poll
+fn evaluate_alerts(alerts: &mut AlertEngine, runners: &[Runner]) {
+    for alert in alerts.evaluate(runners, Utc::now()) {
+        let symbol = match alert.state {
+            AlertState::Resolved => "✓",
+            _ => "⚠",
+        };
+        println!("  {} {}", symbol, alert);
+    }
+}
+
+pub async fn run_headless<S: RunnerSource>(
+    conductor: Conductor<S>,
+    config: AppConfig,
+    query: HeadlessQuery,
+) -> Result<()> {
+    let query = query.with_preset(&config.presets)?;
+    let poll_interval = Duration::from_secs(config.poll_interval_secs);
+    let started_at = Instant::now();
+    let mut iteration = 0u64;
+    let mut notifier = Notifier::new(&config.notify)?;
+    let mut alerts = AlertEngine::new(&config.alerts)?;
+
+    let mut context = ReportContext {
+        target: query.target_version.clone().or(config.target_version()),
+        policy: None,
+        tag_sets: Vec::new(),
+        jobs: Vec::new(),
+        tracker: ManagerTracker::new(config.flap_policy()),
+    };
+
+    if query.command == Command::Coverage {
+        // Tag sets on the command line replace those from config
+        context.tag_sets = if query.tag_sets.is_empty() && query.ci_file.is_none() {
+            required_tag_sets(&config.required_tag_sets, config.ci_file.as_deref())?
+        } else {
+            required_tag_sets(&query.tag_sets, query.ci_file.as_deref())?
+        };
+    }
+
+    if query.command == Command::Pipeline {
+        let ci_file = query.ci_file.as_deref().or(config.ci_file.as_deref());
+        context.jobs = pipeline_jobs(ci_file)?;
+    }
+
+    // Only the workers output compares managers against the server
+    if query.command == Command::Workers {
+        match conductor.server_version().await {
+            Ok(server) => {
+                println!("GitLab {}", server.version);
+                context.policy = server.parsed().map(|server| CompatPolicy {
+                    server,
+                    max_minors_behind: config.max_minors_behind,
+                });
+            }
+            Err(e) => tracing::warn!(error = %e, "Failed to fetch GitLab version"),
+        }
+    }
+
+    loop {
+        iteration += 1;
+        let elapsed = started_at.elapsed().as_secs();
+
+        match run_query(&conductor, &query, &mut notifier, &mut alerts).await {
+            Ok(runners) => {
+                let mut runners = if query.command == Command::Rotate {
+                    let now = Utc::now();
+                    context.tracker.observe(&runners, now);
+                    context.tracker.rotating(runners, now)
+                } else {
+                    runners
+                };
+                if let Some((column, descending)) = query.sort {
+                    sort_rows(&mut runners, column, descending);
+                }
+                println!(
+                    "[{:02}:{:02}] Poll #{} — {} runners matched (command: {})",
+                    elapsed / 60,
+                    elapsed % 60,
+                    iteration,
+                    runners.len(),
+                    query.command,
+                );
+                print_results(query.command, &runners, &context);
+            }
+            Err(e) => {
+                eprintln!("Error: {:#}", e);
+                if !query.watch {
+                    return Err(e);
+                }
+            }
+        }
+
+        if !query.watch {
+            break;
+        }
+
+        // Check timeout
+        if started_at.elapsed().as_secs() >= config.poll_timeout_secs {
+            println!(
+                "\nPoll timeout reached ({} seconds). Exiting.",
+                config.poll_timeout_secs
+            );
+            break;
+        }
+
+        tokio::time::sleep(poll_interval).await;
+    }
+
+    Ok(())
+}
+
+async fn run_query<S: RunnerSource>(
+    conductor: &Conductor<S>,
+    query: &HeadlessQuery,
+    notifier: &mut Notifier,
+    alerts: &mut AlertEngine,
+) -> Result<Vec<Runner>> {
+    let filters = query.filters.clone();
+    let runners = match query.command {
+        Command::Fetch
+        | Command::Lights
+        | Command::Workers
+        | Command::Drift
+        | Command::Coverage
+        | Command::Pipeline => conductor.fetch_runners(filters).await,
+        Command::Switch => conductor.list_offline_runners(filters).await,
+        Command::Flames => {
+            conductor
+                .list_uncontacted_runners(filters, query.threshold_secs)
+                .await
+        }
+        Command::Empty => conductor.list_runners_without_managers(filters).await,
+        // Every runner, so the tracker sees managers drop out
+        Command::Rotate => conductor.fetch_runners(filters).await,
+    }?;
+    if query.command.fetches_fleet() {
+        notify(notifier, &runners).await;
+        evaluate_alerts(alerts, &runners);
+    }
+    Ok(query.command.apply_query(query.query.as_ref(), runners))
+}
+
+fn print_results(command: Command, runners: &[Runner], context: &ReportContext) {
+    match command {
+        Command::Lights => {
+            let summary = HealthSummary::from_runners(runners);
+            let symbol = if summary.is_healthy() { "✓" } else { "✗" };
+            println!(
+                "  {} {} of {} runners online ({:.1}%)",
+                symbol,
+                summary.online_count,
+                summary.total_count,
+                summary.percentage()
+            );
+            print_runners(runners);
+        }
+        Command::Workers => {
+            let policy = context.policy.as_ref();
+            let rows = ManagerRow::from_runners(runners, policy);
+            for row in &rows {
+                println!(
+                    "  Manager {} runner={} [{}] {} status={} version={} compat={} contacted={} ip={}",
+                    row.manager.id,
+                    row.runner_id,
+                    row.runner_tags.join(","),
+                    row.manager.system_id,
+                    row.manager.status,
+                    row.manager.version.as_deref().unwrap_or("-"),
+                    row.compatibility,
+                    row.manager
+                        .contacted_at
+                        .map(|t| t.to_rfc3339_opts(SecondsFormat::Secs, true))
+                        .unwrap_or_else(|| "Never".to_string()),
+                    row.manager.ip_address.as_deref().unwrap_or("-"),
+                );
+            }
+            if let Some(policy) = policy {
+                let flagged = rows.iter().filter(|r| r.compatibility.is_flagged()).count();
+                if flagged > 0 {
+                    println!(
+                        "  ✗ {} managers incompatible with GitLab {} (or more than {} minors behind)",
+                        flagged, policy.server, policy.max_minors_behind
+                    );
+                }
+            }
+        }
+        Command::Rotate => {
+            print_runners(runners);
+            if runners.is_empty() {
+                println!("  ✓ No rotation detected — all runners have single managers");
+            }
+            let now = Utc::now();
+            let tracker = &context.tracker;
+            let policy = tracker.policy();
+            for runner in runners.iter().filter(|r| tracker.is_flapping(r.id, now)) {
+                println!(
+                    "  ✗ Runner {} is flapping: {} manager changes in {}m (limit {})",
+                    runner.id,
+                    tracker.flaps(runner.id, now),
+                    policy.window.num_minutes(),
+                    policy.max_flaps
+                );
+                for event in tracker.timeline(runner.id).iter().rev().take(5) {
+                    println!(
+                        "      {} {} {}",
+                        event.at.to_rfc3339_opts(SecondsFormat::Secs, true),
+                        event.system_id,
+                        event.kind
+                    );
+                }
+            }
+        }
+        Command::Drift => {
+            let report = DriftReport::from_runners(runners, context.target.as_ref());
+            println!("  Target: {}", report.target_label());
+            for v in &report.versions {
+                println!(
+                    "  {:<10} {:<16} {:>5} runners {:>5} managers",
+                    v.version.to_string(),
+                    v.lag.to_string(),
+                    v.runners,
+                    v.managers
+                );
+            }
+            if report.unknown > 0 {
+                println!(
+                    "  {:<27} {:>5} missing or unparseable",
+                    "unknown", report.unknown
+                );
+            }
+            if report.outdated.is_empty() {
+                println!("  ✓ Every runner is on the target version or newer");
+            } else {
+                println!("  {} runners must be upgraded:", report.outdated.len());
+                print_runners(&report.outdated_runners(runners.to_vec()));
+            }
+        }
+        Command::Coverage => {
+            let coverage = tag_coverage(runners, &context.tag_sets);
+            let uncovered = coverage.iter().filter(|c| !c.is_covered()).count();
+            if uncovered == 0 {
+                println!("  ✓ Every tag set has an online runner");
+            } else {
+                println!(
+                    "  ✗ {} of {} tag sets have no online runner",
+                    uncovered,
+                    coverage.len()
+                );
+            }
+            for set in &coverage {
+                println!(
+                    "  {} [{}] online={} offline={} paused={}",
+                    if set.is_covered() { "✓" } else { "✗" },
+                    set.tags.join(","),
+                    set.online,
+                    set.offline,
+                    set.paused
+                );
+            }
+        }
+        Command::Pipeline => {
+            let jobs = job_coverage(runners, &context.jobs);
+            let stuck: Vec<&JobCoverage> = jobs.iter().filter(|j| j.is_stuck()).collect();
+            if stuck.is_empty() {
+                println!("  ✓ Every job has an online runner to pick it up");
+            } else {
+                println!(
+                    "  ✗ {} of {} jobs would be stuck pending:",
+                    stuck.len(),
+                    jobs.len()
+                );
+                for job in stuck {
+                    let tags = if job.coverage.tags.is_empty() {
+                        "untagged".to_string()
+                    } else {
+                        job.coverage.tags.join(",")
+                    };
+                    println!(
+                        "    {} [{}] offline={} paused={}",
+                        job.name, tags, job.coverage.offline, job.coverage.paused
+                    );
+                }
+            }
+        }
+        _ => print_runners(runners),
+    }
+}
+
+fn print_runners(runners: &[Runner]) {
+    for runner in runners {
+        let mgr_info: Vec<String> = runner
+            .managers
+            .iter()
+            .map(|m| {
+                format!(
+                    "{}({}/{})",
+                    m.system_id,
+                    m.status,
+                    m.version.as_deref().unwrap_or("-")
+                )
+            })
+            .collect();
+
+        println!(
+            "  Runner {} [{}] managers=[{}]",
+            runner.id,
+            runner.tag_list.join(","),
+            mgr_info.join(", ")
+        );
+    }
+}
@@ -1,102 +1,180 @@
+mod alerts;
+mod ci;
+mod cli;
 mod client;
 mod conductor;
 mod config;
+mod daemon;
+mod headless;
+mod mock_server;
 mod models;
+mod notify;
+mod presets;
+mod query;
+mod source;
 mod tui;
 
+use alerts::AlertEngine;
 use anyhow::Result;
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches};
+use cli::{Cli, CliCommand};
 use client::GitLabClient;
 use conductor::Conductor;
-use config::AppConfig;
+use config::{AppConfig, ConfigWatcher};
 use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture},
+    event::{
+        DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste,
+        EnableFocusChange, EnableMouseCapture,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use models::runner::RunnerFilters;
+use notify::Notifier;
 use ratatui::{backend::CrosstermBackend, Terminal};
-use std::{env, io, time::Instant};
+use source::{demo::DemoSource, RunnerSource};
+use std::io;
+use std::path::PathBuf;
+use tracing_subscriber::EnvFilter;
 use tui::{
-    app::App,
+    app::{App, ProfileSwitcher},
     event::{Event, EventHandler},
     ui,
 };
 
-#[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
-struct Args {
-    #[arg(long, env("GITLAB_HOST"))]
-    host: Option<String>,
-
-    #[arg(long, env("GITLAB_TOKEN"))]
-    token: Option<String>,
-
-    /// Run in headless mode, polling until timeout
-    #[arg(long)]
-    watch: bool,
-
-    /// Command to run in headless mode (fetch, lights, switch, workers, flames, empty, rotate)
-    #[arg(long, default_value = "rotate")]
-    command: String,
-
-    /// Comma-separated tags to filter runners
-    #[arg(long)]
-    tags: Option<String>,
-}
-
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenvy::dotenv().ok();
-    let args = Args::parse();
-    let config = AppConfig::load().unwrap_or_default();
+    let matches = Cli::command().get_matches();
+    let args = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let overrides = args.overrides(&matches);
+
+    if let Some(CliCommand::Completions { shell }) = args.command {
+        let mut cmd = Cli::command();
+        let name = cmd.get_name().to_string();
+        clap_complete::generate(shell, &mut cmd, name, &mut io::stdout());
+        return Ok(());
+    }
+
+    if let Some(CliCommand::Config { command }) = &args.command {
+        return config::run_command(command, args.config.as_deref(), &overrides);
+    }
+
+    // Setup logging: the daemon logs JSON to stdout for the journal or a log
+    // shipper, everything else to a daily file
+    let _guard = if matches!(args.command, Some(CliCommand::Daemon(_))) {
+        let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+        tracing_subscriber::fmt()
+            .json()
+            .with_env_filter(filter)
+            .with_writer(io::stdout)
+            .init();
+        None
+    } else {
+        let file_appender = tracing_appender::rolling::daily("logs", "gitlab-runner-tui.log");
+        let (non_blocking, guard) = tracing_appender::non_blocking(file_appender);
+        tracing_subscriber::fmt()
+            .with_writer(non_blocking)
+            .with_ansi(false)
+            .init();
+        Some(guard)
+    };
+
+    if let Some(CliCommand::MockServer(mock)) = &args.command {
+        return mock_server::run(mock).await;
+    }
 
-    // Setup logging
-    let file_appender = tracing_appender::rolling::daily("logs", "gitlab-runner-tui.log");
-    let (non_blocking, _guard) = tracing_appender::non_blocking(file_appender);
-    tracing_subscriber::fmt()
-        .with_writer(non_blocking)
-        .with_ansi(false)
-        .init();
+    let config = AppConfig::load(args.config.as_deref())?;
+
+    if args.demo {
+        let conductor = Conductor::new(DemoSource::new(args.demo_seed));
+        return run(conductor, None, config, args.command, args.config).await;
+    }
 
     // Priority: CLI flags > env vars > config.toml > defaults
-    let host = args
-        .host
-        .or_else(|| env::var("GITLAB_HOST").ok())
-        .or_else(|| config.gitlab_host.clone())
-        .unwrap_or_else(|| "https://gitlab.com".to_string());
-
-    let token = args
-        .token
-        .or_else(|| env::var("GITLAB_TOKEN").ok())
-        .or_else(|| config.gitlab_token.clone())
+    let (host, _) = config.resolve_host(&overrides);
+    let (token, _) = config
+        .resolve_token(&overrides)
         .expect("GITLAB_TOKEN must be set via environment variable, --token flag, or config.toml");
 
+    // A profile without a token of its own uses the one resolved here
+    let fallback_token = token.clone();
+    let switcher: ProfileSwitcher<GitLabClient> = Box::new(move |profile| {
+        let token = profile
+            .gitlab_token
+            .clone()
+            .unwrap_or_else(|| fallback_token.clone());
+        GitLabClient::new(profile.gitlab_host.clone(), token)
+    });
+
     let client = GitLabClient::new(host, token)?;
     let conductor = Conductor::new(client);
 
-    if args.watch {
-        return run_headless(conductor, config, &args.command, args.tags.as_deref()).await;
+    run(conductor, Some(switcher), config, args.command, args.config).await
+}
+
+/// Run a headless query or the TUI against any runner source
+async fn run<S: RunnerSource>(
+    conductor: Conductor<S>,
+    switcher: Option<ProfileSwitcher<S>>,
+    config: AppConfig,
+    command: Option<CliCommand>,
+    config_path: Option<PathBuf>,
+) -> Result<()> {
+    if let Some(CliCommand::Daemon(daemon)) = &command {
+        return daemon::run_daemon(conductor, config, config_path, daemon.clone()).await;
+    }
+    if let Some(query) = command.as_ref().and_then(CliCommand::headless_query) {
+        return headless::run_headless(conductor, config, query).await;
     }
 
-    let mut app = App::new(conductor, config);
+    let notifier = Notifier::new(&config.notify)?;
+    let alerts = AlertEngine::new(&config.alerts)?;
+    let mut app = App::new(conductor, config)
+        .with_notifier(notifier)
+        .with_alerts(alerts)
+        .with_config_watcher(ConfigWatcher::new(config_path.as_deref()))
+        .with_config_path(config_path);
+    if let Some(switcher) = switcher {
+        app = app.with_profile_switcher(switcher);
+    }
 
     // Setup Terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(
+        stdout,
+        EnterAlternateScreen,
+        EnableBracketedPaste,
+        EnableFocusChange
+    )?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
     let mut event_handler = EventHandler::new(std::time::Duration::from_millis(250));
 
     // Main Loop
+    let mut mouse_captured = false;
     loop {
+        // `mouse` may change when config.toml is reloaded
+        if app.config.mouse != mouse_captured {
+            mouse_captured = app.config.mouse;
+            if mouse_captured {
+                execute!(terminal.backend_mut(), EnableMouseCapture)?;
+            } else {
+                execute!(terminal.backend_mut(), DisableMouseCapture)?;
+            }
+        }
         terminal.draw(|frame| ui::render(&mut app, frame))?;
 
         if let Some(event) = event_handler.next().await {
             match event {
                 Event::Key(key) => app.handle_key(key).await,
+                Event::Mouse(mouse) => app.handle_mouse(mouse),
+                // Redrawn at the top of the loop at the new size
+                Event::Resize => {}
+                Event::Paste(text) => app.handle_paste(&text),
+                Event::FocusGained => app.set_focus(true),
+                Event::FocusLost => app.set_focus(false),
                 Event::Tick => app.tick().await,
             }
         }
@@ -114,97 +192,11 @@ async fn main() -> Result<()> {
     execute!(
         terminal.backend_mut(),
         LeaveAlternateScreen,
-        DisableMouseCapture
+        DisableMouseCapture,
+        DisableBracketedPaste,
+        DisableFocusChange
     )?;
     terminal.show_cursor()?;
 
     Ok(())
 }
-
-async fn run_headless(
-    conductor: Conductor,
-    config: AppConfig,
-    command: &str,
-    tags: Option<&str>,
-) -> Result<()> {
-    let poll_interval = std::time::Duration::from_secs(config.poll_interval_secs);
-    let started_at = Instant::now();
-    let mut iteration = 0u64;
-
-    loop {
-        iteration += 1;
-        let elapsed = started_at.elapsed().as_secs();
-
-        let mut filters = RunnerFilters::default();
-        if let Some(tag_str) = tags {
-            filters.tag_list = Some(tag_str.split(',').map(|s| s.trim().to_string()).collect());
-        }
-
-        let result = match command {
-            "fetch" => conductor.fetch_runners(filters).await,
-            "switch" => conductor.list_offline_runners(filters).await,
-            "flames" => conductor.list_uncontacted_runners(filters, 3600).await,
-            "empty" => conductor.list_runners_without_managers(filters).await,
-            "rotate" => conductor.detect_rotating_runners(filters).await,
-            other => {
-                eprintln!("Unknown command: {}", other);
-                std::process::exit(1);
-            }
-        };
-
-        match result {
-            Ok(runners) => {
-                println!(
-                    "[{:02}:{:02}] Poll #{} — {} runners matched (command: {})",
-                    elapsed / 60,
-                    elapsed % 60,
-                    iteration,
-                    runners.len(),
-                    command,
-                );
-
-                for runner in &runners {
-                    let mgr_info: Vec<String> = runner
-                        .managers
-                        .iter()
-                        .map(|m| {
-                            format!(
-                                "{}({}/{})",
-                                m.system_id,
-                                m.status,
-                                m.version.as_deref().unwrap_or("-")
-                            )
-                        })
-                        .collect();
-
-                    println!(
-                        "  Runner {} [{}] managers=[{}]",
-                        runner.id,
-                        runner.tag_list.join(","),
-                        mgr_info.join(", ")
-                    );
-                }
-
-                if runners.is_empty() && command == "rotate" {
-                    println!("  ✓ No rotation detected — all runners have single managers");
-                }
-            }
-            Err(e) => {
-                eprintln!("Error: {:#}", e);
-            }
-        }
-
-        // Check timeout
-        if started_at.elapsed().as_secs() >= config.poll_timeout_secs {
-            println!(
-                "\nPoll timeout reached ({} seconds). Exiting.",
-                config.poll_timeout_secs
-            );
-            break;
-        }
-
-        tokio::time::sleep(poll_interval).await;
-    }
-
-    Ok(())
-}
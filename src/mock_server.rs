@@ -0,0 +1,493 @@
+use crate::cli::MockServerArgs;
+use crate::models::job::Job;
+use crate::models::manager::RunnerManager;
+use crate::models::runner::{Runner, RunnerFilters, RunnerType};
+use crate::models::server::ServerVersion;
+use crate::models::status::RunnerStatus;
+use crate::source::demo::generate_fleet;
+use crate::source::fake::FakeSource;
+use crate::source::RunnerSource;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use hyper::header::{HeaderValue, CONTENT_TYPE, RETRY_AFTER};
+use hyper::server::conn::Http;
+use hyper::service::service_fn;
+use hyper::{Body, Method, Request, Response, StatusCode};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+/// GitLab's default and maximum `per_page`
+const DEFAULT_PER_PAGE: u32 = 20;
+const MAX_PER_PAGE: u32 = 100;
+
+/// Failures injected into responses, counted across all requests
+#[derive(Debug, Clone, Default)]
+pub struct Faults {
+    pub latency: Duration,
+    /// Answer every Nth request with 429 Too Many Requests
+    pub rate_limit_every: Option<u64>,
+    /// Answer every Nth request with `error_status`
+    pub error_every: Option<u64>,
+    pub error_status: u16,
+}
+
+impl Faults {
+    fn from_args(args: &MockServerArgs) -> Self {
+        Self {
+            latency: Duration::from_millis(args.latency_ms),
+            rate_limit_every: args.rate_limit_every,
+            error_every: args.error_every,
+            error_status: args.error_status,
+        }
+    }
+
+    /// The injected failure for the `count`th request, if any
+    fn status_for(&self, count: u64) -> Option<StatusCode> {
+        let hits = |every: Option<u64>| every.is_some_and(|n| n > 0 && count.is_multiple_of(n));
+        if hits(self.rate_limit_every) {
+            Some(StatusCode::TOO_MANY_REQUESTS)
+        } else if hits(self.error_every) {
+            Some(
+                StatusCode::from_u16(self.error_status)
+                    .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            )
+        } else {
+            None
+        }
+    }
+}
+
+/// Serves a fixed fleet over the subset of the GitLab REST API this tool uses
+pub struct MockServer {
+    fleet: FakeSource,
+    faults: Faults,
+    requests: AtomicU64,
+}
+
+impl MockServer {
+    pub fn new(fleet: FakeSource, faults: Faults) -> Self {
+        Self {
+            fleet,
+            faults,
+            requests: AtomicU64::new(0),
+        }
+    }
+
+    async fn handle(&self, request: Request<Body>) -> Response<Body> {
+        let count = self.requests.fetch_add(1, Ordering::SeqCst) + 1;
+        if !self.faults.latency.is_zero() {
+            tokio::time::sleep(self.faults.latency).await;
+        }
+
+        let response = match self.faults.status_for(count) {
+            Some(status) => error_response(status),
+            None => {
+                self.route(
+                    request.method(),
+                    request.uri().path(),
+                    request.uri().query(),
+                )
+                .await
+            }
+        };
+        tracing::debug!(
+            "mock {} {} -> {}",
+            request.method(),
+            request.uri(),
+            response.status()
+        );
+        response
+    }
+
+    async fn route(&self, method: &Method, path: &str, query: Option<&str>) -> Response<Body> {
+        if method != Method::GET {
+            return error_response(StatusCode::METHOD_NOT_ALLOWED);
+        }
+        let params = parse_query(query.unwrap_or_default());
+        let segments: Vec<&str> = path
+            .trim_start_matches("/api/v4/")
+            .trim_end_matches('/')
+            .split('/')
+            .collect();
+
+        match segments.as_slice() {
+            ["version"] => match self.fleet.fetch_server_version().await {
+                Ok(version) => json_response(StatusCode::OK, &version),
+                Err(_) => error_response(StatusCode::NOT_FOUND),
+            },
+            ["runners", "all"] => self.list_runners(&params).await,
+            ["runners", id] => match id.parse() {
+                Ok(id) => match self.fleet.fetch_runner_detail(id).await {
+                    Ok(runner) => json_response(StatusCode::OK, &runner),
+                    Err(_) => error_response(StatusCode::NOT_FOUND),
+                },
+                Err(_) => error_response(StatusCode::NOT_FOUND),
+            },
+            ["runners", id, "managers"] => match self.existing_runner(id).await {
+                Some(id) => match self.fleet.fetch_runner_managers(id).await {
+                    Ok(managers) => json_response(StatusCode::OK, &managers),
+                    Err(_) => error_response(StatusCode::INTERNAL_SERVER_ERROR),
+                },
+                None => error_response(StatusCode::NOT_FOUND),
+            },
+            ["runners", id, "jobs"] => match self.existing_runner(id).await {
+                Some(id) => {
+                    let per_page = per_page(&params);
+                    match self.fleet.fetch_runner_jobs(id, per_page).await {
+                        Ok(jobs) => json_response(StatusCode::OK, &jobs),
+                        Err(_) => error_response(StatusCode::INTERNAL_SERVER_ERROR),
+                    }
+                }
+                None => error_response(StatusCode::NOT_FOUND),
+            },
+            _ => error_response(StatusCode::NOT_FOUND),
+        }
+    }
+
+    /// Parse a runner id from the path, if it names a runner in the fleet
+    async fn existing_runner(&self, id: &str) -> Option<u64> {
+        let id = id.parse().ok()?;
+        self.fleet.fetch_runner_detail(id).await.ok().map(|_| id)
+    }
+
+    async fn list_runners(&self, params: &HashMap<String, Vec<String>>) -> Response<Body> {
+        let filters = filters_from_params(params);
+        let page = first(params, "page")
+            .and_then(|p| p.parse().ok())
+            .unwrap_or(1u32)
+            .max(1);
+        let per_page = per_page(params);
+
+        let (total, runners) = match (
+            self.fleet.fetch_runners(&filters, 1, u32::MAX).await,
+            self.fleet.fetch_runners(&filters, page, per_page).await,
+        ) {
+            (Ok(all), Ok(runners)) => (all.len() as u64, runners),
+            _ => return error_response(StatusCode::INTERNAL_SERVER_ERROR),
+        };
+
+        let mut response = json_response(StatusCode::OK, &runners);
+        for (name, value) in pagination_headers(page, per_page, total) {
+            if let Ok(value) = HeaderValue::from_str(&value) {
+                response.headers_mut().insert(name, value);
+            }
+        }
+        response
+    }
+}
+
+/// Query parameters by name; `tag_list[]` is folded into `tag_list`
+fn parse_query(query: &str) -> HashMap<String, Vec<String>> {
+    let mut params: HashMap<String, Vec<String>> = HashMap::new();
+    let url = reqwest::Url::parse(&format!("http://mock/?{}", query));
+    for (key, value) in url.iter().flat_map(|u| u.query_pairs()) {
+        let key = key.trim_end_matches("[]").to_string();
+        params.entry(key).or_default().push(value.into_owned());
+    }
+    params
+}
+
+fn first<'a>(params: &'a HashMap<String, Vec<String>>, key: &str) -> Option<&'a str> {
+    params.get(key).and_then(|v| v.first()).map(String::as_str)
+}
+
+fn per_page(params: &HashMap<String, Vec<String>>) -> u32 {
+    first(params, "per_page")
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(DEFAULT_PER_PAGE)
+        .clamp(1, MAX_PER_PAGE)
+}
+
+/// The `GET /runners/all` filters, accepting `tag_list=a,b` or repeated `tag_list[]`
+fn filters_from_params(params: &HashMap<String, Vec<String>>) -> RunnerFilters {
+    let tags: Vec<String> = params
+        .get("tag_list")
+        .into_iter()
+        .flatten()
+        .flat_map(|v| v.split(','))
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    RunnerFilters {
+        tag_list: (!tags.is_empty()).then_some(tags),
+        status: first(params, "status").map(RunnerStatus::from),
+        version_prefix: first(params, "version_prefix").map(String::from),
+        runner_type: first(params, "type").map(RunnerType::from),
+        paused: first(params, "paused").and_then(|p| p.parse().ok()),
+    }
+}
+
+/// GitLab's offset pagination headers for one page of `total` items
+fn pagination_headers(page: u32, per_page: u32, total: u64) -> Vec<(&'static str, String)> {
+    let total_pages = total.div_ceil(per_page as u64).max(1);
+    let page = page as u64;
+    let next = if page < total_pages {
+        (page + 1).to_string()
+    } else {
+        String::new()
+    };
+    let prev = if page > 1 {
+        (page - 1).to_string()
+    } else {
+        String::new()
+    };
+    vec![
+        ("x-page", page.to_string()),
+        ("x-per-page", per_page.to_string()),
+        ("x-total", total.to_string()),
+        ("x-total-pages", total_pages.to_string()),
+        ("x-next-page", next),
+        ("x-prev-page", prev),
+    ]
+}
+
+fn json_response<T: Serialize>(status: StatusCode, body: &T) -> Response<Body> {
+    let body = serde_json::to_vec(body).unwrap_or_default();
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response
+}
+
+/// An error body shaped like GitLab's `{"message": "..."}`
+fn error_response(status: StatusCode) -> Response<Body> {
+    let message = format!(
+        "{} {}",
+        status.as_u16(),
+        status.canonical_reason().unwrap_or("Error")
+    );
+    let mut response = json_response(status, &serde_json::json!({ "message": message }));
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        response
+            .headers_mut()
+            .insert(RETRY_AFTER, HeaderValue::from_static("1"));
+    }
+    response
+}
+
+/// Load a fixture directory laid out like the API:
+/// `runners/<id>.json`, plus optional `runners/<id>/managers.json`,
+/// `runners/<id>/jobs.json` and a top-level `version.json`
+pub fn load_fixtures(dir: &Path) -> Result<FakeSource> {
+    let runners_dir = dir.join("runners");
+    let entries = fs::read_dir(&runners_dir)
+        .with_context(|| format!("Failed to read {}", runners_dir.display()))?;
+
+    let mut runners = Vec::new();
+    let mut jobs = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let mut runner: Runner = read_json(&path)?;
+        let extra = runners_dir.join(runner.id.to_string());
+        let managers_path = extra.join("managers.json");
+        if managers_path.exists() {
+            runner.managers = read_json::<Vec<RunnerManager>>(&managers_path)?;
+        }
+        let jobs_path = extra.join("jobs.json");
+        if jobs_path.exists() {
+            jobs.push((runner.id, read_json::<Vec<Job>>(&jobs_path)?));
+        }
+        runners.push(runner);
+    }
+    runners.sort_by_key(|r| r.id);
+
+    let mut fleet = jobs
+        .into_iter()
+        .fold(FakeSource::new(runners), |fleet, (id, jobs)| {
+            fleet.with_jobs(id, jobs)
+        });
+    let version_path = dir.join("version.json");
+    if version_path.exists() {
+        let version: ServerVersion = read_json(&version_path)?;
+        fleet = fleet.with_server_version(&version.version);
+    }
+    Ok(fleet)
+}
+
+/// Load a snapshot: a JSON array of runners with their managers inline
+pub fn load_snapshot(path: &Path) -> Result<FakeSource> {
+    let mut runners: Vec<Runner> = read_json(path)?;
+    runners.sort_by_key(|r| r.id);
+    Ok(FakeSource::new(runners))
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T> {
+    let text =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&text).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Accept connections until the listener fails
+pub async fn serve(listener: TcpListener, server: Arc<MockServer>) -> Result<()> {
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let server = Arc::clone(&server);
+        tokio::spawn(async move {
+            let service = service_fn(move |request| {
+                let server = Arc::clone(&server);
+                async move { Ok::<_, Infallible>(server.handle(request).await) }
+            });
+            if let Err(e) = Http::new()
+                .http1_only(true)
+                .serve_connection(stream, service)
+                .await
+            {
+                tracing::debug!("mock connection error: {}", e);
+            }
+        });
+    }
+}
+
+/// Run the `mock-server` command until interrupted
+pub async fn run(args: &MockServerArgs) -> Result<()> {
+    let fleet = match (&args.fixtures, &args.snapshot) {
+        (Some(dir), _) => load_fixtures(dir)?,
+        (None, Some(path)) => load_snapshot(path)?,
+        (None, None) => generate_fleet(args.seed, Utc::now()),
+    };
+    let server = Arc::new(MockServer::new(fleet, Faults::from_args(args)));
+
+    let listener = TcpListener::bind(args.listen)
+        .await
+        .with_context(|| format!("Failed to bind {}", args.listen))?;
+    // Printed so scripts using port 0 can discover the address
+    println!("Mock GitLab listening on http://{}", listener.local_addr()?);
+
+    tokio::select! {
+        result = serve(listener, server) => result,
+        _ = tokio::signal::ctrl_c() => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::GitLabClient;
+    use crate::conductor::Conductor;
+    use crate::models::test_support;
+
+    fn runner(id: u64, tags: &[&str]) -> Runner {
+        Runner {
+            version: Some("17.5.0".to_string()),
+            tag_list: test_support::tags(tags),
+            managers: vec![RunnerManager {
+                version: Some("17.5.0".to_string()),
+                ..test_support::manager(id * 10, &format!("host-{}", id))
+            }],
+            ..test_support::runner(id)
+        }
+    }
+
+    async fn start(fleet: FakeSource, faults: Faults) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, Arc::new(MockServer::new(fleet, faults))));
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_conductor_pages_through_mock() {
+        let fleet = FakeSource::new((1..=150).map(|id| runner(id, &["alm"])).collect());
+        let host = start(fleet, Faults::default()).await;
+        let conductor = Conductor::new(GitLabClient::new(host, "token".to_string()).unwrap());
+
+        let runners = conductor
+            .fetch_runners(RunnerFilters::default())
+            .await
+            .unwrap();
+        assert_eq!(runners.len(), 150);
+        assert_eq!(runners[0].tag_list, vec!["alm"]);
+        assert_eq!(runners[0].managers.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_pagination_headers_and_filters() {
+        let fleet = FakeSource::new(vec![
+            runner(1, &["alm", "prod"]),
+            runner(2, &["alm"]),
+            runner(3, &["alm", "prod"]),
+        ]);
+        let host = start(fleet, Faults::default()).await;
+
+        let response = reqwest::get(format!(
+            "{}/api/v4/runners/all?tag_list[]=alm&tag_list[]=prod&per_page=1&page=1",
+            host
+        ))
+        .await
+        .unwrap();
+        let header = |name: &str| response.headers()[name].to_str().unwrap().to_string();
+        assert_eq!(header("x-total"), "2");
+        assert_eq!(header("x-total-pages"), "2");
+        assert_eq!(header("x-next-page"), "2");
+        assert_eq!(header("x-prev-page"), "");
+        let runners: Vec<Runner> = response.json().await.unwrap();
+        assert_eq!(runners[0].id, 1);
+        assert!(runners[0].tag_list.is_empty());
+
+        let missing = reqwest::get(format!("{}/api/v4/runners/99/managers", host))
+            .await
+            .unwrap();
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND.as_u16());
+    }
+
+    #[tokio::test]
+    async fn test_injected_faults() {
+        let faults = Faults {
+            rate_limit_every: Some(2),
+            error_every: Some(3),
+            error_status: 503,
+            ..Default::default()
+        };
+        let host = start(FakeSource::new(vec![runner(1, &[])]), faults).await;
+        let url = format!("{}/api/v4/runners/1", host);
+
+        let mut statuses = Vec::new();
+        for _ in 0..4 {
+            let response = reqwest::get(&url).await.unwrap();
+            if response.status() == StatusCode::TOO_MANY_REQUESTS.as_u16() {
+                assert_eq!(response.headers()["retry-after"], "1");
+            }
+            statuses.push(response.status().as_u16());
+        }
+        assert_eq!(statuses, vec![200, 429, 503, 429]);
+    }
+
+    #[test]
+    fn test_load_fixtures() {
+        let dir = std::env::temp_dir().join(format!("mock-fixtures-{}", std::process::id()));
+        let runners_dir = dir.join("runners");
+        fs::create_dir_all(runners_dir.join("7")).unwrap();
+
+        let mut fixture = runner(7, &["alm"]);
+        let managers = std::mem::take(&mut fixture.managers);
+        fs::write(
+            runners_dir.join("7.json"),
+            serde_json::to_string(&fixture).unwrap(),
+        )
+        .unwrap();
+        fs::write(
+            runners_dir.join("7").join("managers.json"),
+            serde_json::to_string(&managers).unwrap(),
+        )
+        .unwrap();
+
+        let mut fleet = load_fixtures(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        let runners = fleet.runners_mut();
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].managers, managers);
+    }
+}
@@ -0,0 +1,141 @@
+use super::runner::Runner;
+
+/// Whether a runner can pick up a job that requires `tags`: GitLab needs
+/// every job tag on the runner, and ignores extra runner tags. Untagged jobs
+/// only go to runners with `run_untagged` set.
+pub fn can_run(runner: &Runner, tags: &[String]) -> bool {
+    if tags.is_empty() {
+        return runner.accepts_untagged();
+    }
+    tags.iter().all(|t| runner.tag_list.contains(t))
+}
+
+/// A runner is online when one of its managers is, like `lights`
+fn is_online(runner: &Runner) -> bool {
+    if runner.managers.is_empty() {
+        runner.status.is_online()
+    } else {
+        runner.managers.iter().any(|m| m.status.is_online())
+    }
+}
+
+/// How many runners could pick up jobs requiring one tag set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagSetCoverage {
+    pub tags: Vec<String>,
+    /// Online and not paused: these pick up jobs right now
+    pub online: usize,
+    pub offline: usize,
+    pub paused: usize,
+}
+
+impl TagSetCoverage {
+    pub fn from_runners(runners: &[Runner], tags: &[String]) -> Self {
+        let mut coverage = Self {
+            tags: tags.to_vec(),
+            online: 0,
+            offline: 0,
+            paused: 0,
+        };
+        for runner in runners.iter().filter(|r| can_run(r, tags)) {
+            if runner.paused {
+                coverage.paused += 1;
+            } else if is_online(runner) {
+                coverage.online += 1;
+            } else {
+                coverage.offline += 1;
+            }
+        }
+        coverage
+    }
+
+    /// Jobs with these tags get picked up; otherwise they sit pending
+    pub fn is_covered(&self) -> bool {
+        self.online > 0
+    }
+}
+
+/// Coverage of every tag set, in the order given
+pub fn tag_coverage(runners: &[Runner], tag_sets: &[Vec<String>]) -> Vec<TagSetCoverage> {
+    tag_sets
+        .iter()
+        .map(|tags| TagSetCoverage::from_runners(runners, tags))
+        .collect()
+}
+
+/// Trim, sort and de-duplicate tags so equal sets compare equal
+pub fn normalize_tag_set(tags: &[String]) -> Vec<String> {
+    let mut tags: Vec<String> = tags
+        .iter()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::manager::RunnerManager;
+    use crate::models::test_support::{self, tags};
+
+    fn runner(id: u64, runner_tags: &[&str], online: bool, paused: bool) -> Runner {
+        Runner {
+            paused,
+            description: None,
+            status: test_support::status(online),
+            tag_list: tags(runner_tags),
+            managers: vec![RunnerManager {
+                status: test_support::status(online),
+                ..test_support::manager(id * 10, &format!("host-{}", id))
+            }],
+            ..test_support::runner(id)
+        }
+    }
+
+    #[test]
+    fn test_job_tags_must_be_subset_of_runner_tags() {
+        let r = runner(1, &["docker", "linux", "prod"], true, false);
+        assert!(can_run(&r, &tags(&["docker", "linux"])));
+        assert!(!can_run(&r, &tags(&["docker", "arm64"])));
+    }
+
+    #[test]
+    fn test_untagged_jobs_need_run_untagged() {
+        let mut r = runner(1, &["docker"], true, false);
+        assert!(!can_run(&r, &[]));
+        r.run_untagged = Some(true);
+        assert!(can_run(&r, &[]));
+
+        // List data has no run_untagged: only untagged runners qualify
+        assert!(can_run(&runner(2, &[], true, false), &[]));
+    }
+
+    #[test]
+    fn test_coverage_counts_by_runner_state() {
+        let runners = vec![
+            runner(1, &["docker", "arm64"], false, false),
+            runner(2, &["docker", "arm64"], true, true),
+            runner(3, &["docker", "linux"], true, false),
+        ];
+        let report = tag_coverage(&runners, &[tags(&["docker", "arm64"]), tags(&["docker"])]);
+
+        assert_eq!(
+            (report[0].online, report[0].offline, report[0].paused),
+            (0, 1, 1)
+        );
+        assert!(!report[0].is_covered());
+        assert_eq!(report[1].online, 1);
+        assert!(report[1].is_covered());
+    }
+
+    #[test]
+    fn test_normalize_tag_set() {
+        assert_eq!(
+            normalize_tag_set(&tags(&[" linux", "docker", "linux", ""])),
+            tags(&["docker", "linux"])
+        );
+    }
+}
@@ -0,0 +1,253 @@
+use super::runner::Runner;
+use super::version::parse_version;
+use semver::Version;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// How far a version trails the target, by the most significant component
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lag {
+    Current,
+    Ahead,
+    Patch(u64),
+    Minor(u64),
+    Major(u64),
+}
+
+impl Lag {
+    pub fn between(version: &Version, target: &Version) -> Lag {
+        if version == target {
+            Lag::Current
+        } else if version > target {
+            Lag::Ahead
+        } else if version.major < target.major {
+            Lag::Major(target.major - version.major)
+        } else if version.minor < target.minor {
+            Lag::Minor(target.minor - version.minor)
+        } else {
+            Lag::Patch(target.patch - version.patch)
+        }
+    }
+}
+
+impl fmt::Display for Lag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Lag::Current => write!(f, "current"),
+            Lag::Ahead => write!(f, "ahead"),
+            Lag::Patch(n) => write!(f, "{} patch behind", n),
+            Lag::Minor(n) => write!(f, "{} minor behind", n),
+            Lag::Major(n) => write!(f, "{} major behind", n),
+        }
+    }
+}
+
+/// Where the drift target came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetSource {
+    /// `target_version` in config or `--target`
+    Configured,
+    /// Newest version seen in the fleet
+    Newest,
+}
+
+/// Runners and managers running one version
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionCount {
+    pub version: Version,
+    pub runners: usize,
+    pub managers: usize,
+    pub lag: Lag,
+}
+
+/// Fleet versions grouped and compared against a target version
+#[derive(Debug, Clone, PartialEq)]
+pub struct DriftReport {
+    /// `None` only when no target is configured and no version parses
+    pub target: Option<Version>,
+    pub target_source: TargetSource,
+    /// Newest first
+    pub versions: Vec<VersionCount>,
+    /// Managers (or manager-less runners) with a missing or unparseable version
+    pub unknown: usize,
+    /// Ids of runners with at least one version behind the target
+    pub outdated: Vec<u64>,
+}
+
+/// Versions a runner is running: its managers', or its own when it has none
+fn runner_versions(runner: &Runner) -> Vec<Option<Version>> {
+    if runner.managers.is_empty() {
+        vec![runner.version.as_deref().and_then(parse_version)]
+    } else {
+        runner
+            .managers
+            .iter()
+            .map(|m| m.version.as_deref().and_then(parse_version))
+            .collect()
+    }
+}
+
+impl DriftReport {
+    pub fn from_runners(runners: &[Runner], target: Option<&Version>) -> Self {
+        let mut counts: BTreeMap<Version, (usize, usize)> = BTreeMap::new();
+        let mut unknown = 0;
+
+        for runner in runners {
+            let mut seen: Vec<&Version> = Vec::new();
+            let versions = runner_versions(runner);
+            for version in &versions {
+                match version {
+                    Some(version) => {
+                        let entry = counts.entry(version.clone()).or_default();
+                        entry.1 += 1;
+                        if !seen.contains(&version) {
+                            seen.push(version);
+                            entry.0 += 1;
+                        }
+                    }
+                    None => unknown += 1,
+                }
+            }
+        }
+
+        let (target, target_source) = match target {
+            Some(target) => (Some(target.clone()), TargetSource::Configured),
+            None => (counts.keys().next_back().cloned(), TargetSource::Newest),
+        };
+
+        let versions = counts
+            .into_iter()
+            .rev()
+            .map(|(version, (runners, managers))| VersionCount {
+                lag: target
+                    .as_ref()
+                    .map_or(Lag::Current, |t| Lag::between(&version, t)),
+                version,
+                runners,
+                managers,
+            })
+            .collect();
+
+        let mut outdated: Vec<u64> = match &target {
+            Some(target) => runners
+                .iter()
+                .filter(|r| runner_versions(r).iter().flatten().any(|v| v < target))
+                .map(|r| r.id)
+                .collect(),
+            None => Vec::new(),
+        };
+        outdated.sort_unstable();
+
+        Self {
+            target,
+            target_source,
+            versions,
+            unknown,
+            outdated,
+        }
+    }
+
+    /// Keep only the runners that must be upgraded
+    pub fn outdated_runners(&self, runners: Vec<Runner>) -> Vec<Runner> {
+        runners
+            .into_iter()
+            .filter(|r| self.outdated.binary_search(&r.id).is_ok())
+            .collect()
+    }
+
+    /// One-line description of the target, e.g. "17.5.0 (newest in fleet)"
+    pub fn target_label(&self) -> String {
+        match (&self.target, self.target_source) {
+            (Some(target), TargetSource::Configured) => format!("{} (configured)", target),
+            (Some(target), TargetSource::Newest) => format!("{} (newest in fleet)", target),
+            (None, _) => "none".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::manager::RunnerManager;
+    use crate::models::test_support;
+
+    fn manager(id: u64, version: &str) -> RunnerManager {
+        RunnerManager {
+            version: Some(version.to_string()),
+            ..test_support::manager(id, &format!("host-{}", id))
+        }
+    }
+
+    fn runner(id: u64, version: Option<&str>, managers: Vec<RunnerManager>) -> Runner {
+        Runner {
+            description: None,
+            version: version.map(String::from),
+            managers,
+            ..test_support::runner(id)
+        }
+    }
+
+    #[test]
+    fn test_lag_uses_most_significant_component() {
+        let target = Version::new(17, 5, 2);
+        let lag = |v: &str| Lag::between(&parse_version(v).unwrap(), &target);
+        assert_eq!(lag("17.5.2"), Lag::Current);
+        assert_eq!(lag("17.6.0"), Lag::Ahead);
+        assert_eq!(lag("17.5.0"), Lag::Patch(2));
+        assert_eq!(lag("17.3.9"), Lag::Minor(2));
+        assert_eq!(lag("16.11.0"), Lag::Major(1));
+        assert_eq!(Lag::Minor(2).to_string(), "2 minor behind");
+    }
+
+    #[test]
+    fn test_report_targets_newest_version_by_default() {
+        let runners = vec![
+            runner(1, None, vec![manager(10, "17.5.0"), manager(11, "17.4.1")]),
+            runner(2, None, vec![manager(20, "17.5.0")]),
+            runner(3, Some("16.11.0"), vec![]),
+            runner(4, None, vec![manager(40, "garbage")]),
+        ];
+        let report = DriftReport::from_runners(&runners, None);
+
+        assert_eq!(report.target, Some(Version::new(17, 5, 0)));
+        assert_eq!(report.target_source, TargetSource::Newest);
+        let summary: Vec<(String, usize, usize, Lag)> = report
+            .versions
+            .iter()
+            .map(|v| (v.version.to_string(), v.runners, v.managers, v.lag))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("17.5.0".to_string(), 2, 2, Lag::Current),
+                ("17.4.1".to_string(), 1, 1, Lag::Minor(1)),
+                ("16.11.0".to_string(), 1, 1, Lag::Major(1)),
+            ]
+        );
+        assert_eq!(report.unknown, 1);
+        assert_eq!(report.outdated, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_configured_target_and_outdated_runners() {
+        let runners = vec![
+            runner(1, None, vec![manager(10, "17.5.0")]),
+            runner(2, None, vec![manager(20, "17.6.0")]),
+        ];
+        let target = Version::new(17, 6, 0);
+        let report = DriftReport::from_runners(&runners, Some(&target));
+
+        assert_eq!(report.target_label(), "17.6.0 (configured)");
+        let outdated = report.outdated_runners(runners);
+        assert_eq!(outdated.len(), 1);
+        assert_eq!(outdated[0].id, 1);
+    }
+
+    #[test]
+    fn test_empty_fleet_has_no_target() {
+        let report = DriftReport::from_runners(&[], None);
+        assert_eq!(report.target, None);
+        assert!(report.versions.is_empty());
+        assert_eq!(report.target_label(), "none");
+    }
+}
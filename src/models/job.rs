@@ -0,0 +1,80 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A CI job picked up by a runner, from `GET /runners/:id/jobs`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Job {
+    pub id: u64,
+    pub name: String,
+    #[serde(default)]
+    pub stage: Option<String>,
+    pub status: String,
+    #[serde(rename = "ref", default)]
+    pub git_ref: Option<String>,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Run time in seconds, once the job has started
+    #[serde(default)]
+    pub duration: Option<f64>,
+    #[serde(default)]
+    pub web_url: Option<String>,
+    #[serde(default)]
+    pub project: Option<JobProject>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct JobProject {
+    pub id: u64,
+    pub path_with_namespace: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_deserialization() {
+        let json = r#"{
+            "id": 9001,
+            "name": "rspec 1/4",
+            "stage": "test",
+            "status": "running",
+            "ref": "main",
+            "created_at": "2024-06-09T11:12:02.507Z",
+            "started_at": "2024-06-09T11:12:30.000Z",
+            "finished_at": null,
+            "duration": 42.5,
+            "web_url": "https://gitlab.example.com/group/app/-/jobs/9001",
+            "project": {
+                "id": 7,
+                "name": "app",
+                "path_with_namespace": "group/app"
+            }
+        }"#;
+
+        let job: Job = serde_json::from_str(json).expect("Failed to deserialize job");
+
+        assert_eq!(job.id, 9001);
+        assert_eq!(job.git_ref, Some("main".to_string()));
+        assert_eq!(job.duration, Some(42.5));
+        assert!(job.finished_at.is_none());
+        assert_eq!(job.project.unwrap().path_with_namespace, "group/app");
+    }
+
+    #[test]
+    fn test_job_minimal_fields() {
+        let json = r#"{
+            "id": 1,
+            "name": "build",
+            "status": "pending",
+            "created_at": "2024-06-09T11:12:02Z"
+        }"#;
+
+        let job: Job = serde_json::from_str(json).expect("Failed to deserialize job");
+        assert!(job.stage.is_none());
+        assert!(job.project.is_none());
+    }
+}
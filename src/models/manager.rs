@@ -1,13 +1,15 @@
+use super::status::RunnerStatus;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct RunnerManager {
     pub id: u64,
     pub system_id: String,
-    pub created_at: String, // Keeping as String for now, will parse to DateTime later or verify serde_json handles it if we use chrono
-    pub contacted_at: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub contacted_at: Option<DateTime<Utc>>,
     pub ip_address: Option<String>,
-    pub status: String,
+    pub status: RunnerStatus,
     pub version: Option<String>,
     pub revision: Option<String>,
     #[serde(default)]
@@ -38,7 +40,11 @@ mod tests {
 
         assert_eq!(manager.id, 67890);
         assert_eq!(manager.system_id, "runner-host-01");
-        assert_eq!(manager.status, "online");
+        assert_eq!(manager.status, RunnerStatus::Online);
+        assert_eq!(
+            manager.contacted_at.unwrap().to_rfc3339(),
+            "2024-01-20T14:22:00+00:00"
+        );
         assert_eq!(manager.version, Some("17.5.0".to_string()));
         assert_eq!(manager.ip_address, Some("10.0.1.50".to_string()));
     }
@@ -60,7 +66,7 @@ mod tests {
             serde_json::from_str(json).expect("Failed to deserialize manager");
 
         assert_eq!(manager.id, 12345);
-        assert_eq!(manager.status, "never_contacted");
+        assert_eq!(manager.status, RunnerStatus::NeverContacted);
         assert!(manager.contacted_at.is_none());
         assert!(manager.ip_address.is_none());
         assert!(manager.version.is_none());
@@ -71,10 +77,10 @@ mod tests {
         let manager = RunnerManager {
             id: 1,
             system_id: "test".to_string(),
-            created_at: "2024-01-01T00:00:00Z".to_string(),
-            contacted_at: Some("2024-01-02T00:00:00Z".to_string()),
+            created_at: "2024-01-01T00:00:00Z".parse().unwrap(),
+            contacted_at: Some("2024-01-02T00:00:00Z".parse().unwrap()),
             ip_address: Some("192.168.1.1".to_string()),
-            status: "online".to_string(),
+            status: RunnerStatus::Online,
             version: Some("17.0.0".to_string()),
             revision: Some("abc123".to_string()),
             platform: Some("linux".to_string()),
@@ -126,4 +132,20 @@ mod tests {
         assert!(manager.platform.is_none());
         assert!(manager.architecture.is_none());
     }
+
+    #[test]
+    fn test_manager_with_invalid_timestamp_fails() {
+        let json = r#"{
+            "id": 300,
+            "system_id": "runner-bad",
+            "created_at": "yesterday",
+            "contacted_at": null,
+            "ip_address": null,
+            "status": "offline",
+            "version": null,
+            "revision": null
+        }"#;
+
+        assert!(serde_json::from_str::<RunnerManager>(json).is_err());
+    }
 }
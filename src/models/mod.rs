@@ -1,2 +1,11 @@
+pub mod coverage;
+pub mod drift;
+pub mod job;
 pub mod manager;
 pub mod runner;
+pub mod server;
+pub mod status;
+#[cfg(test)]
+pub mod test_support;
+pub mod timeline;
+pub mod version;
@@ -1,31 +1,105 @@
 use super::manager::RunnerManager;
+use super::status::RunnerStatus;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Scope a runner is registered at
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum RunnerType {
+    InstanceType,
+    GroupType,
+    ProjectType,
+    /// A runner type this version doesn't know about, kept verbatim
+    Unknown(String),
+}
+
+impl RunnerType {
+    /// Types accepted by the GitLab `type` filter
+    pub const KNOWN: &[RunnerType] = &[
+        RunnerType::InstanceType,
+        RunnerType::GroupType,
+        RunnerType::ProjectType,
+    ];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            RunnerType::InstanceType => "instance_type",
+            RunnerType::GroupType => "group_type",
+            RunnerType::ProjectType => "project_type",
+            RunnerType::Unknown(raw) => raw,
+        }
+    }
+}
+
+impl From<&str> for RunnerType {
+    fn from(raw: &str) -> Self {
+        match raw {
+            "instance_type" => RunnerType::InstanceType,
+            "group_type" => RunnerType::GroupType,
+            "project_type" => RunnerType::ProjectType,
+            other => RunnerType::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl From<String> for RunnerType {
+    fn from(raw: String) -> Self {
+        RunnerType::from(raw.as_str())
+    }
+}
+
+impl From<RunnerType> for String {
+    fn from(runner_type: RunnerType) -> Self {
+        runner_type.as_str().to_string()
+    }
+}
+
+impl fmt::Display for RunnerType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Runner {
     pub id: u64,
-    pub runner_type: String,
+    pub runner_type: RunnerType,
     pub active: bool,
     pub paused: bool,
     pub description: Option<String>,
-    pub created_at: Option<String>,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
     pub ip_address: Option<String>,
     pub is_shared: bool,
-    pub status: String,
+    pub status: RunnerStatus,
     pub version: Option<String>,
     pub revision: Option<String>,
     #[serde(default)]
     pub tag_list: Vec<String>,
+    /// Whether the runner picks up untagged jobs; only the detail endpoint
+    /// returns it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_untagged: Option<bool>,
     #[serde(default)]
     pub managers: Vec<RunnerManager>,
 }
 
+impl Runner {
+    /// Whether untagged jobs can go to this runner. Without detail data,
+    /// assume GitLab's registration default: only untagged runners take them.
+    pub fn accepts_untagged(&self) -> bool {
+        self.run_untagged.unwrap_or(self.tag_list.is_empty())
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq)]
 pub struct RunnerFilters {
     pub tag_list: Option<Vec<String>>,
-    pub status: Option<String>,
+    pub status: Option<RunnerStatus>,
     pub version_prefix: Option<String>,
-    pub runner_type: Option<String>,
+    pub runner_type: Option<RunnerType>,
     pub paused: Option<bool>,
 }
 
@@ -33,6 +107,7 @@ pub struct RunnerFilters {
 mod tests {
     use super::*;
     use crate::models::manager::RunnerManager;
+    use crate::models::test_support;
 
     #[test]
     fn test_runner_deserialization() {
@@ -55,9 +130,13 @@ mod tests {
         let runner: Runner = serde_json::from_str(json).expect("Failed to deserialize runner");
 
         assert_eq!(runner.id, 12345);
-        assert_eq!(runner.runner_type, "group_type");
+        assert_eq!(runner.runner_type, RunnerType::GroupType);
         assert!(runner.active);
-        assert_eq!(runner.status, "online");
+        assert_eq!(runner.status, RunnerStatus::Online);
+        assert_eq!(
+            runner.created_at.unwrap().to_rfc3339(),
+            "2024-01-15T10:30:00+00:00"
+        );
         assert_eq!(runner.tag_list.len(), 3);
     }
 
@@ -91,7 +170,7 @@ mod tests {
         let runner: Runner = serde_json::from_str(json).expect("Failed to deserialize runner");
 
         assert_eq!(runner.id, 12345);
-        assert_eq!(runner.runner_type, "instance_type");
+        assert_eq!(runner.runner_type, RunnerType::InstanceType);
         assert!(runner.is_shared);
         assert_eq!(runner.managers.len(), 1);
         assert_eq!(runner.managers[0].system_id, "host-1");
@@ -120,7 +199,8 @@ mod tests {
             );
 
             let runner: Runner = serde_json::from_str(&json).expect("Failed to deserialize");
-            assert_eq!(runner.status, *status);
+            assert_eq!(runner.status, RunnerStatus::from(*status));
+            assert!(!matches!(runner.status, RunnerStatus::Unknown(_)));
         }
     }
 
@@ -147,7 +227,8 @@ mod tests {
             );
 
             let runner: Runner = serde_json::from_str(&json).expect("Failed to deserialize");
-            assert_eq!(runner.runner_type, *runner_type);
+            assert_eq!(runner.runner_type, RunnerType::from(*runner_type));
+            assert!(!matches!(runner.runner_type, RunnerType::Unknown(_)));
         }
     }
 
@@ -176,37 +257,26 @@ mod tests {
         assert!(tags.contains(&"alm".to_string()));
     }
 
-    fn create_test_runner(id: u64, status: &str, manager_status: Option<&str>) -> Runner {
-        let managers = match manager_status {
-            Some(s) => vec![RunnerManager {
-                id: id * 10,
-                system_id: format!("host-{}", id),
-                created_at: "2024-01-15T10:30:00.000Z".to_string(),
-                contacted_at: Some("2024-01-20T14:22:00.000Z".to_string()),
-                ip_address: Some("10.0.1.1".to_string()),
-                status: s.to_string(),
-                version: Some("17.5.0".to_string()),
-                revision: None,
-                platform: None,
-                architecture: None,
-            }],
-            None => vec![],
-        };
+    #[test]
+    fn test_runner_unknown_type_is_preserved() {
+        let runner_type: RunnerType = serde_json::from_str(r#""fleet_type""#).unwrap();
+        assert_eq!(runner_type, RunnerType::Unknown("fleet_type".to_string()));
+        assert_eq!(runner_type.to_string(), "fleet_type");
+    }
 
+    fn create_test_runner(id: u64, status: &str, manager_status: Option<&str>) -> Runner {
+        let managers = manager_status
+            .map(|s| RunnerManager {
+                status: RunnerStatus::from(s),
+                ..test_support::manager(id * 10, &format!("host-{}", id))
+            })
+            .into_iter()
+            .collect();
         Runner {
-            id,
-            runner_type: "group_type".to_string(),
-            active: true,
-            paused: false,
-            description: None,
-            created_at: Some("2024-01-15T10:30:00.000Z".to_string()),
-            ip_address: None,
-            is_shared: false,
-            status: status.to_string(),
-            version: Some("17.5.0".to_string()),
-            revision: None,
-            tag_list: vec!["alm".to_string()],
+            status: RunnerStatus::from(status),
+            tag_list: test_support::tags(&["alm"]),
             managers,
+            ..test_support::runner(id)
         }
     }
 
@@ -224,7 +294,7 @@ mod tests {
             .filter(|r| {
                 r.managers
                     .first()
-                    .map(|m| m.status == "online")
+                    .map(|m| m.status.is_online())
                     .unwrap_or(false)
             })
             .collect();
@@ -0,0 +1,112 @@
+use super::version::parse_version;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Response of `GET /version`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServerVersion {
+    pub version: String,
+    #[serde(default)]
+    pub revision: String,
+}
+
+impl ServerVersion {
+    pub fn parsed(&self) -> Option<Version> {
+        parse_version(&self.version)
+    }
+}
+
+/// How a runner version relates to the GitLab server's major.minor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compatibility {
+    /// Same major.minor as the server
+    Compatible,
+    /// Older minor, within the allowed lag
+    Behind(u64),
+    /// Older minor, beyond the allowed lag
+    TooOld(u64),
+    /// Newer than the server, which may not support it
+    Ahead,
+    /// Different major version
+    MajorMismatch,
+    /// Missing or unparseable runner version
+    Unknown,
+}
+
+impl Compatibility {
+    /// Whether the version should be upgraded (or the server checked)
+    pub fn is_flagged(&self) -> bool {
+        matches!(
+            self,
+            Compatibility::TooOld(_) | Compatibility::Ahead | Compatibility::MajorMismatch
+        )
+    }
+}
+
+impl fmt::Display for Compatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Compatibility::Compatible => write!(f, "ok"),
+            Compatibility::Behind(n) => write!(f, "{} behind", n),
+            Compatibility::TooOld(n) => write!(f, "too old ({} behind)", n),
+            Compatibility::Ahead => write!(f, "ahead of server"),
+            Compatibility::MajorMismatch => write!(f, "major mismatch"),
+            Compatibility::Unknown => write!(f, "-"),
+        }
+    }
+}
+
+/// Server version plus how many minor releases runners may trail it
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompatPolicy {
+    pub server: Version,
+    pub max_minors_behind: u64,
+}
+
+impl CompatPolicy {
+    pub fn check(&self, version: Option<&str>) -> Compatibility {
+        let Some(version) = version.and_then(parse_version) else {
+            return Compatibility::Unknown;
+        };
+        if version.major != self.server.major {
+            return Compatibility::MajorMismatch;
+        }
+        match self.server.minor.checked_sub(version.minor) {
+            None => Compatibility::Ahead,
+            Some(0) => Compatibility::Compatible,
+            Some(n) if n <= self.max_minors_behind => Compatibility::Behind(n),
+            Some(n) => Compatibility::TooOld(n),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_server_version_deserialization() {
+        let json = r#"{"version": "17.5.1-ee", "revision": "a1b2c3", "kas": {"enabled": true}}"#;
+        let server: ServerVersion = serde_json::from_str(json).unwrap();
+        assert_eq!(server.parsed(), Some(Version::new(17, 5, 1)));
+    }
+
+    #[test]
+    fn test_compatibility_against_server() {
+        let policy = CompatPolicy {
+            server: Version::new(17, 5, 1),
+            max_minors_behind: 2,
+        };
+        assert_eq!(policy.check(Some("17.5.0")), Compatibility::Compatible);
+        assert_eq!(policy.check(Some("17.3.2")), Compatibility::Behind(2));
+        assert_eq!(policy.check(Some("17.1.0")), Compatibility::TooOld(4));
+        assert_eq!(policy.check(Some("17.6.0")), Compatibility::Ahead);
+        assert_eq!(policy.check(Some("16.11.0")), Compatibility::MajorMismatch);
+        assert_eq!(policy.check(None), Compatibility::Unknown);
+
+        assert!(!Compatibility::Behind(2).is_flagged());
+        assert!(Compatibility::TooOld(4).is_flagged());
+        assert!(Compatibility::MajorMismatch.is_flagged());
+    }
+}
@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Connection status reported by GitLab for a runner or runner manager
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum RunnerStatus {
+    Online,
+    Offline,
+    Stale,
+    NeverContacted,
+    /// A status this version doesn't know about, kept verbatim
+    Unknown(String),
+}
+
+impl RunnerStatus {
+    /// Statuses accepted by the GitLab `status` filter
+    pub const KNOWN: &[RunnerStatus] = &[
+        RunnerStatus::Online,
+        RunnerStatus::Offline,
+        RunnerStatus::Stale,
+        RunnerStatus::NeverContacted,
+    ];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            RunnerStatus::Online => "online",
+            RunnerStatus::Offline => "offline",
+            RunnerStatus::Stale => "stale",
+            RunnerStatus::NeverContacted => "never_contacted",
+            RunnerStatus::Unknown(raw) => raw,
+        }
+    }
+
+    pub fn is_online(&self) -> bool {
+        *self == RunnerStatus::Online
+    }
+}
+
+impl From<&str> for RunnerStatus {
+    fn from(raw: &str) -> Self {
+        match raw {
+            "online" => RunnerStatus::Online,
+            "offline" => RunnerStatus::Offline,
+            "stale" => RunnerStatus::Stale,
+            "never_contacted" => RunnerStatus::NeverContacted,
+            other => RunnerStatus::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl From<String> for RunnerStatus {
+    fn from(raw: String) -> Self {
+        RunnerStatus::from(raw.as_str())
+    }
+}
+
+impl From<RunnerStatus> for String {
+    fn from(status: RunnerStatus) -> Self {
+        status.as_str().to_string()
+    }
+}
+
+impl fmt::Display for RunnerStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_statuses_round_trip() {
+        for status in RunnerStatus::KNOWN {
+            assert_eq!(RunnerStatus::from(status.as_str()), *status);
+        }
+    }
+
+    #[test]
+    fn test_unknown_status_is_preserved() {
+        let status: RunnerStatus = serde_json::from_str(r#""paused""#).unwrap();
+        assert_eq!(status, RunnerStatus::Unknown("paused".to_string()));
+        assert_eq!(serde_json::to_string(&status).unwrap(), r#""paused""#);
+    }
+
+    #[test]
+    fn test_deserialize_known_status() {
+        let status: RunnerStatus = serde_json::from_str(r#""never_contacted""#).unwrap();
+        assert_eq!(status, RunnerStatus::NeverContacted);
+        assert!(!status.is_online());
+    }
+}
@@ -0,0 +1,56 @@
+//! Runners and managers for unit tests. Tests override the fields they care
+//! about with struct update syntax, so a new field is only added here.
+
+use super::manager::RunnerManager;
+use super::runner::{Runner, RunnerType};
+use super::status::RunnerStatus;
+
+/// An online, active group runner described as "runner-{id}", with no tags
+/// and no managers
+pub fn runner(id: u64) -> Runner {
+    Runner {
+        id,
+        runner_type: RunnerType::GroupType,
+        active: true,
+        paused: false,
+        description: Some(format!("runner-{}", id)),
+        created_at: None,
+        ip_address: None,
+        is_shared: false,
+        status: RunnerStatus::Online,
+        version: None,
+        revision: None,
+        tag_list: Vec::new(),
+        run_untagged: None,
+        managers: Vec::new(),
+    }
+}
+
+/// An online manager created at 2024-01-01 that has never been contacted
+pub fn manager(id: u64, system_id: &str) -> RunnerManager {
+    RunnerManager {
+        id,
+        system_id: system_id.to_string(),
+        created_at: "2024-01-01T00:00:00Z".parse().unwrap(),
+        contacted_at: None,
+        ip_address: None,
+        status: RunnerStatus::Online,
+        version: None,
+        revision: None,
+        platform: None,
+        architecture: None,
+    }
+}
+
+pub fn tags(tags: &[&str]) -> Vec<String> {
+    tags.iter().map(|t| t.to_string()).collect()
+}
+
+/// Online or offline
+pub fn status(online: bool) -> RunnerStatus {
+    if online {
+        RunnerStatus::Online
+    } else {
+        RunnerStatus::Offline
+    }
+}
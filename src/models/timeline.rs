@@ -0,0 +1,343 @@
+use super::runner::Runner;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+/// Events kept per runner; older ones are dropped first
+const MAX_EVENTS_PER_RUNNER: usize = 100;
+
+/// Runners missing from every poll for this long (deleted, or filtered out
+/// all day) are forgotten
+const RUNNER_RETENTION_HOURS: i64 = 24;
+
+/// What happened to a manager between two polls
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManagerEventKind {
+    /// First seen, with whether it was online
+    Appeared {
+        online: bool,
+    },
+    WentOnline,
+    WentOffline,
+    Disappeared,
+    /// Seen again after it had disappeared
+    Returned,
+}
+
+impl ManagerEventKind {
+    /// Status changes and returns count as flaps; a manager appearing once
+    /// and later disappearing is an ordinary rotation
+    pub fn is_flap(&self) -> bool {
+        matches!(
+            self,
+            ManagerEventKind::WentOnline
+                | ManagerEventKind::WentOffline
+                | ManagerEventKind::Returned
+        )
+    }
+}
+
+impl fmt::Display for ManagerEventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManagerEventKind::Appeared { online: true } => write!(f, "appeared online"),
+            ManagerEventKind::Appeared { online: false } => write!(f, "appeared offline"),
+            ManagerEventKind::WentOnline => write!(f, "went online"),
+            ManagerEventKind::WentOffline => write!(f, "went offline"),
+            ManagerEventKind::Disappeared => write!(f, "disappeared"),
+            ManagerEventKind::Returned => write!(f, "returned"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManagerEvent {
+    pub at: DateTime<Utc>,
+    pub system_id: String,
+    pub kind: ManagerEventKind,
+}
+
+/// How many flaps within a window make a runner "flapping"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlapPolicy {
+    pub max_flaps: usize,
+    pub window: Duration,
+}
+
+#[derive(Debug, Default)]
+struct RunnerHistory {
+    /// Managers in the last poll, by system_id, and whether they were online
+    present: HashMap<String, bool>,
+    /// Managers that disappeared within the flap window, and when.
+    /// Autoscaled managers get a new system_id per instance, so older ones
+    /// are dropped; one that comes back after that appears anew.
+    gone: HashMap<String, DateTime<Utc>>,
+    events: VecDeque<ManagerEvent>,
+    last_seen: DateTime<Utc>,
+}
+
+impl RunnerHistory {
+    fn push(&mut self, at: DateTime<Utc>, system_id: &str, kind: ManagerEventKind) {
+        if self.events.len() == MAX_EVENTS_PER_RUNNER {
+            self.events.pop_front();
+        }
+        self.events.push_back(ManagerEvent {
+            at,
+            system_id: system_id.to_string(),
+            kind,
+        });
+    }
+}
+
+/// Manager history per runner, built by diffing successive polls
+#[derive(Debug)]
+pub struct ManagerTracker {
+    policy: FlapPolicy,
+    runners: HashMap<u64, RunnerHistory>,
+}
+
+impl ManagerTracker {
+    pub fn new(policy: FlapPolicy) -> Self {
+        Self {
+            policy,
+            runners: HashMap::new(),
+        }
+    }
+
+    /// Record the managers of every polled runner. Runners missing from the
+    /// poll (filtered out, or deleted) keep their history untouched for
+    /// `RUNNER_RETENTION_HOURS`.
+    pub fn observe(&mut self, runners: &[Runner], now: DateTime<Utc>) {
+        let gone_since = now - self.policy.window;
+        for runner in runners {
+            let history = self.runners.entry(runner.id).or_default();
+            history.last_seen = now;
+            history.gone.retain(|_, at| *at >= gone_since);
+            let mut current: HashMap<String, bool> = HashMap::new();
+
+            for manager in &runner.managers {
+                let online = manager.status.is_online();
+                current.insert(manager.system_id.clone(), online);
+                let kind = match history.present.get(&manager.system_id) {
+                    Some(&was) if was == online => continue,
+                    Some(_) if online => ManagerEventKind::WentOnline,
+                    Some(_) => ManagerEventKind::WentOffline,
+                    None if history.gone.remove(&manager.system_id).is_some() => {
+                        ManagerEventKind::Returned
+                    }
+                    None => ManagerEventKind::Appeared { online },
+                };
+                history.push(now, &manager.system_id, kind);
+            }
+
+            let mut vanished: Vec<String> = history
+                .present
+                .keys()
+                .filter(|id| !current.contains_key(*id))
+                .cloned()
+                .collect();
+            vanished.sort();
+            for system_id in vanished {
+                history.push(now, &system_id, ManagerEventKind::Disappeared);
+                history.gone.insert(system_id, now);
+            }
+            history.present = current;
+        }
+
+        let seen_since = now - Duration::hours(RUNNER_RETENTION_HOURS);
+        self.runners
+            .retain(|_, history| history.last_seen >= seen_since);
+    }
+
+    /// Events for one runner, oldest first
+    pub fn timeline(&self, runner_id: u64) -> Vec<&ManagerEvent> {
+        self.runners
+            .get(&runner_id)
+            .map(|h| h.events.iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// Flaps within the policy window ending at `now`
+    pub fn flaps(&self, runner_id: u64, now: DateTime<Utc>) -> usize {
+        let since = now - self.policy.window;
+        self.timeline(runner_id)
+            .into_iter()
+            .filter(|e| e.at >= since && e.kind.is_flap())
+            .count()
+    }
+
+    pub fn is_flapping(&self, runner_id: u64, now: DateTime<Utc>) -> bool {
+        self.flaps(runner_id, now) > self.policy.max_flaps
+    }
+
+    /// Judge flapping by new limits from now on, keeping the history
+    pub fn set_policy(&mut self, policy: FlapPolicy) {
+        self.policy = policy;
+    }
+
+    pub fn policy(&self) -> FlapPolicy {
+        self.policy
+    }
+
+    /// Runners worth watching: those mid-rotation (several managers) and
+    /// those flapping
+    pub fn rotating(&self, runners: Vec<Runner>, now: DateTime<Utc>) -> Vec<Runner> {
+        runners
+            .into_iter()
+            .filter(|r| r.managers.len() > 1 || self.is_flapping(r.id, now))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::manager::RunnerManager;
+    use crate::models::test_support;
+
+    fn manager(system_id: &str, online: bool) -> RunnerManager {
+        RunnerManager {
+            status: test_support::status(online),
+            ..test_support::manager(1, system_id)
+        }
+    }
+
+    fn runner(managers: Vec<RunnerManager>) -> Runner {
+        Runner {
+            description: None,
+            managers,
+            ..test_support::runner(7)
+        }
+    }
+
+    fn tracker() -> ManagerTracker {
+        ManagerTracker::new(FlapPolicy {
+            max_flaps: 2,
+            window: Duration::minutes(30),
+        })
+    }
+
+    fn at(minute: i64) -> DateTime<Utc> {
+        "2024-06-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap() + Duration::minutes(minute)
+    }
+
+    #[test]
+    fn test_healthy_rotation_is_not_flapping() {
+        let mut tracker = tracker();
+        tracker.observe(&[runner(vec![manager("old", true)])], at(0));
+        tracker.observe(
+            &[runner(vec![manager("old", true), manager("new", true)])],
+            at(1),
+        );
+        tracker.observe(&[runner(vec![manager("new", true)])], at(2));
+
+        let kinds: Vec<(&str, ManagerEventKind)> = tracker
+            .timeline(7)
+            .into_iter()
+            .map(|e| (e.system_id.as_str(), e.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("old", ManagerEventKind::Appeared { online: true }),
+                ("new", ManagerEventKind::Appeared { online: true }),
+                ("old", ManagerEventKind::Disappeared),
+            ]
+        );
+        assert_eq!(tracker.flaps(7, at(2)), 0);
+        assert!(tracker
+            .rotating(vec![runner(vec![manager("new", true)])], at(2))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_flapping_manager_is_flagged_within_window() {
+        let mut tracker = tracker();
+        for minute in 0..4 {
+            tracker.observe(&[runner(vec![manager("m", minute % 2 == 0)])], at(minute));
+        }
+        tracker.observe(&[runner(vec![])], at(4));
+        tracker.observe(&[runner(vec![manager("m", true)])], at(5));
+
+        // Offline, online, offline, then a return
+        assert_eq!(tracker.flaps(7, at(5)), 4);
+        assert!(tracker.is_flapping(7, at(5)));
+        assert_eq!(
+            tracker
+                .rotating(vec![runner(vec![manager("m", true)])], at(5))
+                .len(),
+            1
+        );
+
+        // The flaps age out of the window
+        assert!(!tracker.is_flapping(7, at(40)));
+    }
+
+    #[test]
+    fn test_rotating_needs_several_managers_without_history() {
+        let tracker = tracker();
+        let fleet = vec![
+            Runner {
+                id: 1,
+                ..runner(vec![manager("a", true), manager("b", false)])
+            },
+            Runner {
+                id: 2,
+                ..runner(vec![manager("c", true)])
+            },
+            Runner {
+                id: 3,
+                ..runner(vec![])
+            },
+            Runner {
+                id: 4,
+                ..runner(vec![
+                    manager("d", false),
+                    manager("e", false),
+                    manager("f", true),
+                ])
+            },
+        ];
+        let ids: Vec<u64> = tracker
+            .rotating(fleet, at(0))
+            .iter()
+            .map(|r| r.id)
+            .collect();
+        assert_eq!(ids, vec![1, 4]);
+    }
+
+    #[test]
+    fn test_absent_runners_keep_history() {
+        let mut tracker = tracker();
+        tracker.observe(&[runner(vec![manager("m", true)])], at(0));
+        tracker.observe(&[], at(1));
+        tracker.observe(&[runner(vec![manager("m", true)])], at(2));
+        assert_eq!(tracker.timeline(7).len(), 1);
+
+        // Until they have been missing for a day
+        tracker.observe(&[], at(24 * 60 + 3));
+        assert!(tracker.timeline(7).is_empty());
+    }
+
+    #[test]
+    fn test_managers_gone_past_the_window_are_forgotten() {
+        let mut tracker = tracker();
+        tracker.observe(&[runner(vec![manager("a", true)])], at(0));
+        tracker.observe(&[runner(vec![manager("b", true)])], at(1));
+        assert_eq!(tracker.runners[&7].gone.len(), 1);
+
+        // Autoscaling replaced "a" for good: it ages out of the window
+        tracker.observe(&[runner(vec![manager("c", true)])], at(40));
+        let gone: Vec<&String> = tracker.runners[&7].gone.keys().collect();
+        assert_eq!(gone, vec!["b"]);
+
+        // Back after the window is a new manager, not a flap
+        tracker.observe(&[runner(vec![manager("a", true)])], at(80));
+        let back = tracker
+            .timeline(7)
+            .into_iter()
+            .rfind(|e| e.system_id == "a")
+            .unwrap();
+        assert_eq!(back.kind, ManagerEventKind::Appeared { online: true });
+    }
+}
@@ -0,0 +1,59 @@
+use semver::Version;
+
+/// Leniently parse a runner version such as `17.5.0`, `v16.11`, or
+/// `17.0.0~pre.88.g761ae5dd` into its `major.minor.patch` core.
+///
+/// Missing minor/patch components default to zero and anything after the
+/// numeric core (pre-release or build suffixes) is ignored.
+pub fn parse_version(raw: &str) -> Option<Version> {
+    let raw = raw.trim().trim_start_matches(['v', 'V']);
+    let core = raw
+        .split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .next()
+        .unwrap_or("");
+
+    let mut parts = core.split('.').filter(|p| !p.is_empty());
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().map(str::parse).transpose().ok()?.unwrap_or(0);
+    let patch = parts.next().map(str::parse).transpose().ok()?.unwrap_or(0);
+
+    Some(Version::new(major, minor, patch))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_full_version() {
+        assert_eq!(parse_version("17.5.0"), Some(Version::new(17, 5, 0)));
+    }
+
+    #[test]
+    fn test_parse_partial_versions_pad_with_zero() {
+        assert_eq!(parse_version("17"), Some(Version::new(17, 0, 0)));
+        assert_eq!(parse_version("16.11"), Some(Version::new(16, 11, 0)));
+    }
+
+    #[test]
+    fn test_parse_ignores_prefix_and_suffix() {
+        assert_eq!(parse_version("v16.11.1"), Some(Version::new(16, 11, 1)));
+        assert_eq!(
+            parse_version("17.0.0~pre.88.g761ae5dd"),
+            Some(Version::new(17, 0, 0))
+        );
+        assert_eq!(parse_version("15.2.0-rc1"), Some(Version::new(15, 2, 0)));
+    }
+
+    #[test]
+    fn test_parse_rejects_garbage() {
+        assert_eq!(parse_version(""), None);
+        assert_eq!(parse_version("-"), None);
+        assert_eq!(parse_version("latest"), None);
+    }
+
+    #[test]
+    fn test_parsed_versions_order_numerically() {
+        assert!(parse_version("17.10.0") > parse_version("17.9.3"));
+    }
+}
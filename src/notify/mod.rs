@@ -0,0 +1,424 @@
+pub mod sink;
+
+use crate::models::runner::{Runner, RunnerFilters};
+use crate::tui::app::HealthSummary;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sink::{Dispatcher, SinkConfig};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// `[notify]` table of `config.toml`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotifyConfig {
+    /// Percentage of online runners below which the fleet counts as unhealthy
+    pub health_threshold: f64,
+    pub sinks: Vec<SinkConfig>,
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        Self {
+            health_threshold: 90.0,
+            sinks: Vec::new(),
+        }
+    }
+}
+
+impl NotifyConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !(0.0..=100.0).contains(&self.health_threshold) {
+            anyhow::bail!(
+                "notify.health_threshold: {} is not a percentage",
+                self.health_threshold
+            );
+        }
+        for (i, sink) in self.sinks.iter().enumerate() {
+            sink.validate()
+                .map_err(|e| anyhow::anyhow!("notify.sinks[{}]: {}", i, e))?;
+        }
+        Ok(())
+    }
+}
+
+/// A change between two polls worth telling someone about
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum FleetEvent {
+    RunnerOffline {
+        runner_id: u64,
+        description: Option<String>,
+    },
+    RunnerOnline {
+        runner_id: u64,
+        description: Option<String>,
+    },
+    ManagerDisappeared {
+        runner_id: u64,
+        system_id: String,
+    },
+    HealthDropped {
+        online: usize,
+        total: usize,
+        threshold: f64,
+    },
+    HealthRecovered {
+        online: usize,
+        total: usize,
+        threshold: f64,
+    },
+}
+
+fn runner_label(runner_id: u64, description: &Option<String>) -> String {
+    match description {
+        Some(description) => format!("Runner {} ({})", runner_id, description),
+        None => format!("Runner {}", runner_id),
+    }
+}
+
+fn percentage(online: usize, total: usize) -> f64 {
+    HealthSummary {
+        online_count: online,
+        total_count: total,
+    }
+    .percentage()
+}
+
+impl fmt::Display for FleetEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FleetEvent::RunnerOffline {
+                runner_id,
+                description,
+            } => write!(f, "{} went offline", runner_label(*runner_id, description)),
+            FleetEvent::RunnerOnline {
+                runner_id,
+                description,
+            } => write!(
+                f,
+                "{} is back online",
+                runner_label(*runner_id, description)
+            ),
+            FleetEvent::ManagerDisappeared {
+                runner_id,
+                system_id,
+            } => write!(
+                f,
+                "Manager {} disappeared from runner {}",
+                system_id, runner_id
+            ),
+            FleetEvent::HealthDropped {
+                online,
+                total,
+                threshold,
+            } => write!(
+                f,
+                "Fleet health dropped to {:.1}% ({} of {} online), below {}%",
+                percentage(*online, *total),
+                online,
+                total,
+                threshold
+            ),
+            FleetEvent::HealthRecovered {
+                online,
+                total,
+                threshold,
+            } => write!(
+                f,
+                "Fleet health recovered to {:.1}% ({} of {} online), at or above {}%",
+                percentage(*online, *total),
+                online,
+                total,
+                threshold
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct RunnerState {
+    description: Option<String>,
+    online: bool,
+    managers: BTreeSet<String>,
+}
+
+/// What one poll saw, reduced to what transitions are detected from
+#[derive(Debug, Clone)]
+pub struct FleetSnapshot {
+    runners: BTreeMap<u64, RunnerState>,
+    health: HealthSummary,
+}
+
+impl FleetSnapshot {
+    pub fn from_runners(runners: &[Runner]) -> Self {
+        Self {
+            runners: runners
+                .iter()
+                .map(|r| {
+                    let state = RunnerState {
+                        description: r.description.clone(),
+                        online: r.managers.iter().any(|m| m.status.is_online()),
+                        managers: r.managers.iter().map(|m| m.system_id.clone()).collect(),
+                    };
+                    (r.id, state)
+                })
+                .collect(),
+            health: HealthSummary::from_runners(runners),
+        }
+    }
+
+    /// Transitions from `self` to `next`. Runners missing from either poll
+    /// (filtered out, registered or deleted) are not compared.
+    pub fn events(&self, next: &FleetSnapshot, threshold: f64) -> Vec<FleetEvent> {
+        let mut events = Vec::new();
+        for (&runner_id, after) in &next.runners {
+            let Some(before) = self.runners.get(&runner_id) else {
+                continue;
+            };
+            let description = after.description.clone();
+            match (before.online, after.online) {
+                (true, false) => events.push(FleetEvent::RunnerOffline {
+                    runner_id,
+                    description,
+                }),
+                (false, true) => events.push(FleetEvent::RunnerOnline {
+                    runner_id,
+                    description,
+                }),
+                _ => {}
+            }
+            for system_id in before.managers.difference(&after.managers) {
+                events.push(FleetEvent::ManagerDisappeared {
+                    runner_id,
+                    system_id: system_id.clone(),
+                });
+            }
+        }
+
+        let was_healthy = self.health.percentage() >= threshold;
+        let is_healthy = next.health.percentage() >= threshold;
+        let (online, total) = (next.health.online_count, next.health.total_count);
+        if was_healthy && !is_healthy {
+            events.push(FleetEvent::HealthDropped {
+                online,
+                total,
+                threshold,
+            });
+        } else if !was_healthy && is_healthy {
+            events.push(FleetEvent::HealthRecovered {
+                online,
+                total,
+                threshold,
+            });
+        }
+        events
+    }
+}
+
+/// Detects transitions between polls and sends them to the configured sinks
+pub struct Notifier {
+    threshold: f64,
+    previous: Option<FleetSnapshot>,
+    /// Filters the baseline was fetched with
+    scope: RunnerFilters,
+    dispatcher: Dispatcher,
+}
+
+impl Notifier {
+    pub fn new(config: &NotifyConfig) -> Result<Self> {
+        Ok(Self {
+            threshold: config.health_threshold,
+            previous: None,
+            scope: RunnerFilters::default(),
+            dispatcher: Dispatcher::new(config.sinks.clone())?,
+        })
+    }
+
+    /// Drop the baseline, e.g. after switching to another GitLab instance
+    pub fn reset(&mut self) {
+        self.previous = None;
+    }
+
+    /// Switch to new settings, keeping the previous poll as the baseline
+    pub fn reconfigure(&mut self, config: &NotifyConfig) -> Result<()> {
+        self.dispatcher = Dispatcher::new(config.sinks.clone())?;
+        self.threshold = config.health_threshold;
+        Ok(())
+    }
+
+    /// Set the filters the next poll is fetched with. A different set of
+    /// runners can't be compared with the baseline, so the next poll starts
+    /// a new one.
+    pub fn scope(&mut self, filters: &RunnerFilters) {
+        if self.scope != *filters {
+            self.scope = filters.clone();
+            self.previous = None;
+        }
+    }
+
+    /// Compare a poll with the previous one. The first poll is the baseline
+    /// and yields no events.
+    pub fn observe(&mut self, runners: &[Runner]) -> Vec<FleetEvent> {
+        let next = FleetSnapshot::from_runners(runners);
+        let events = match &self.previous {
+            Some(previous) => previous.events(&next, self.threshold),
+            None => Vec::new(),
+        };
+        self.previous = Some(next);
+        events
+    }
+
+    /// Sender for the events, cheap to clone into a background task
+    pub fn dispatcher(&self) -> Dispatcher {
+        self.dispatcher.clone()
+    }
+}
+
+/// Timestamped events for one poll, as sent to webhooks and command hooks
+#[derive(Debug, Serialize)]
+pub struct Notification<'a> {
+    pub timestamp: DateTime<Utc>,
+    pub events: Vec<EventPayload<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EventPayload<'a> {
+    #[serde(flatten)]
+    pub event: &'a FleetEvent,
+    pub message: String,
+}
+
+impl<'a> Notification<'a> {
+    pub fn new(events: &'a [FleetEvent], timestamp: DateTime<Utc>) -> Self {
+        Self {
+            timestamp,
+            events: events
+                .iter()
+                .map(|event| EventPayload {
+                    event,
+                    message: event.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::manager::RunnerManager;
+    use crate::models::test_support;
+
+    fn manager(system_id: &str, online: bool) -> RunnerManager {
+        RunnerManager {
+            status: test_support::status(online),
+            ..test_support::manager(1, system_id)
+        }
+    }
+
+    fn runner(id: u64, managers: Vec<RunnerManager>) -> Runner {
+        Runner {
+            managers,
+            ..test_support::runner(id)
+        }
+    }
+
+    fn notifier() -> Notifier {
+        Notifier::new(&NotifyConfig {
+            health_threshold: 75.0,
+            sinks: Vec::new(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_first_poll_is_baseline() {
+        let mut notifier = notifier();
+        assert!(notifier.observe(&[runner(1, vec![])]).is_empty());
+    }
+
+    #[test]
+    fn test_detects_offline_runners_and_lost_managers() {
+        let mut notifier = notifier();
+        notifier.observe(&[
+            runner(1, vec![manager("a", true), manager("b", true)]),
+            runner(2, vec![manager("c", true)]),
+            runner(3, vec![manager("d", true)]),
+            runner(4, vec![manager("e", true)]),
+        ]);
+        let events = notifier.observe(&[
+            runner(1, vec![manager("b", true)]),
+            runner(2, vec![manager("c", false)]),
+            runner(3, vec![manager("d", true)]),
+            runner(4, vec![manager("e", true)]),
+        ]);
+        assert_eq!(
+            events,
+            vec![
+                FleetEvent::ManagerDisappeared {
+                    runner_id: 1,
+                    system_id: "a".to_string(),
+                },
+                FleetEvent::RunnerOffline {
+                    runner_id: 2,
+                    description: Some("runner-2".to_string()),
+                },
+            ]
+        );
+        assert_eq!(events[1].to_string(), "Runner 2 (runner-2) went offline");
+    }
+
+    #[test]
+    fn test_health_threshold_crossings() {
+        let mut notifier = notifier();
+        let fleet = |online: &[bool]| -> Vec<Runner> {
+            online
+                .iter()
+                .enumerate()
+                .map(|(i, &up)| runner(i as u64, vec![manager(&i.to_string(), up)]))
+                .collect()
+        };
+
+        notifier.observe(&fleet(&[true, true, true, true]));
+        let dropped = notifier.observe(&fleet(&[true, true, false, false]));
+        assert!(dropped.contains(&FleetEvent::HealthDropped {
+            online: 2,
+            total: 4,
+            threshold: 75.0,
+        }));
+        // Still unhealthy: no repeat
+        let still = notifier.observe(&fleet(&[true, false, false, false]));
+        assert!(!still
+            .iter()
+            .any(|e| matches!(e, FleetEvent::HealthDropped { .. })));
+        let recovered = notifier.observe(&fleet(&[true, true, true, false]));
+        assert!(recovered
+            .iter()
+            .any(|e| matches!(e, FleetEvent::HealthRecovered { online: 3, .. })));
+    }
+
+    #[test]
+    fn test_new_filters_start_a_new_baseline() {
+        let mut notifier = notifier();
+        notifier.observe(&[
+            runner(1, vec![manager("a", true)]),
+            runner(2, vec![manager("b", true)]),
+        ]);
+
+        // Narrowing to a different population isn't a change in the fleet
+        notifier.scope(&RunnerFilters {
+            tag_list: Some(vec!["windows".to_string()]),
+            ..RunnerFilters::default()
+        });
+        assert!(notifier
+            .observe(&[runner(3, vec![manager("c", false)])])
+            .is_empty());
+        let events = notifier.observe(&[runner(3, vec![manager("c", true)])]);
+        assert!(events.contains(&FleetEvent::RunnerOnline {
+            runner_id: 3,
+            description: Some("runner-3".to_string()),
+        }));
+    }
+}
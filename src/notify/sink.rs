@@ -0,0 +1,261 @@
+use super::{FleetEvent, Notification};
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+/// How long a sink may take before it is abandoned
+const SINK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// One `[[notify.sinks]]` entry
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum SinkConfig {
+    /// POST the notification JSON to `url`
+    Webhook { url: String },
+    /// POST a `{"text": …}` message, as Slack and Mattermost incoming
+    /// webhooks expect
+    Slack { url: String },
+    /// Run a program once per event: the message is appended as the last
+    /// argument and the event JSON is written to stdin
+    Command { command: Vec<String> },
+}
+
+impl SinkConfig {
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            SinkConfig::Webhook { url } | SinkConfig::Slack { url } => {
+                if !url.starts_with("http://") && !url.starts_with("https://") {
+                    bail!("'{}' is not an http(s) URL", url);
+                }
+            }
+            SinkConfig::Command { command } => {
+                if command.is_empty() {
+                    bail!("command needs at least a program");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            SinkConfig::Webhook { .. } => "webhook",
+            SinkConfig::Slack { .. } => "slack",
+            SinkConfig::Command { .. } => "command",
+        }
+    }
+}
+
+/// Sends events to every configured sink
+#[derive(Clone)]
+pub struct Dispatcher {
+    client: Client,
+    sinks: Arc<Vec<SinkConfig>>,
+}
+
+impl Dispatcher {
+    pub fn new(sinks: Vec<SinkConfig>) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(SINK_TIMEOUT)
+            .build()
+            .context("Failed to build reqwest client")?;
+        Ok(Self {
+            client,
+            sinks: Arc::new(sinks),
+        })
+    }
+
+    /// Deliver to every sink; a failing sink is logged and doesn't stop the
+    /// others
+    pub async fn send(&self, events: &[FleetEvent]) {
+        if events.is_empty() {
+            return;
+        }
+        for sink in self.sinks.iter() {
+            if let Err(e) = self.send_to(sink, events).await {
+                tracing::warn!(sink = sink.name(), error = %e, "Failed to send notification");
+            }
+        }
+    }
+
+    async fn send_to(&self, sink: &SinkConfig, events: &[FleetEvent]) -> Result<()> {
+        match sink {
+            SinkConfig::Webhook { url } => {
+                let body = Notification::new(events, Utc::now());
+                self.post(url, &serde_json::to_value(body)?).await
+            }
+            SinkConfig::Slack { url } => self.post(url, &slack_payload(events)).await,
+            SinkConfig::Command { command } => {
+                for event in events {
+                    run_hook(command, event).await?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    async fn post(&self, url: &str, body: &serde_json::Value) -> Result<()> {
+        let response = self
+            .client
+            .post(url)
+            .json(body)
+            .send()
+            .await
+            .with_context(|| format!("Failed to POST to {}", url))?;
+        if !response.status().is_success() {
+            bail!("{} returned {}", url, response.status());
+        }
+        Ok(())
+    }
+}
+
+/// One message per poll, an event per line
+fn slack_payload(events: &[FleetEvent]) -> serde_json::Value {
+    let lines: Vec<String> = events.iter().map(|e| format!("• {}", e)).collect();
+    json!({ "text": format!("GitLab runners:\n{}", lines.join("\n")) })
+}
+
+async fn run_hook(command: &[String], event: &FleetEvent) -> Result<()> {
+    let (program, args) = command
+        .split_first()
+        .context("command needs at least a program")?;
+    let json = serde_json::to_vec(&Notification::new(std::slice::from_ref(event), Utc::now()))?;
+
+    let mut child = tokio::process::Command::new(program)
+        .args(args)
+        .arg(event.to_string())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        // Inherited, it would print over the TUI; logged below instead
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to run {}", program))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook that ignores stdin may exit before reading it
+        let _ = stdin.write_all(&json).await;
+    }
+
+    let output = tokio::time::timeout(SINK_TIMEOUT, child.wait_with_output())
+        .await
+        .with_context(|| format!("{} timed out", program))??;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stderr = stderr.trim();
+    if !output.status.success() {
+        if stderr.is_empty() {
+            bail!("{} exited with {}", program, output.status);
+        }
+        bail!("{} exited with {}: {}", program, output.status, stderr);
+    }
+    if !stderr.is_empty() {
+        tracing::warn!(hook = %program, stderr, "Command hook wrote to stderr");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server};
+
+    fn offline() -> FleetEvent {
+        FleetEvent::RunnerOffline {
+            runner_id: 42,
+            description: Some("build-box".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_webhook_posts_event_json() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/hook")
+            .match_header("content-type", "application/json")
+            .match_body(Matcher::PartialJson(json!({
+                "events": [{
+                    "event": "runner_offline",
+                    "runner_id": 42,
+                    "description": "build-box",
+                    "message": "Runner 42 (build-box) went offline",
+                }]
+            })))
+            .with_status(204)
+            .create_async()
+            .await;
+
+        let url = format!("{}/hook", server.url());
+        let dispatcher = Dispatcher::new(vec![SinkConfig::Webhook { url: url.clone() }]).unwrap();
+        dispatcher
+            .send_to(&dispatcher.sinks[0], &[offline()])
+            .await
+            .unwrap();
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_slack_posts_text_and_reports_errors() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/slack")
+            .match_body(Matcher::Json(json!({
+                "text": "GitLab runners:\n• Runner 42 (build-box) went offline"
+            })))
+            .with_status(500)
+            .create_async()
+            .await;
+
+        let sink = SinkConfig::Slack {
+            url: format!("{}/slack", server.url()),
+        };
+        let dispatcher = Dispatcher::new(vec![sink.clone()]).unwrap();
+        let err = dispatcher.send_to(&sink, &[offline()]).await.unwrap_err();
+        assert!(err.to_string().contains("500"));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_command_hook_gets_message_and_json() {
+        let out = std::env::temp_dir().join(format!("notify-hook-{}.txt", std::process::id()));
+        let script = r#"printf '%s\n' "$2" > "$1"; cat >> "$1""#;
+        let command = vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            script.to_string(),
+            "sh".to_string(),
+            out.display().to_string(),
+        ];
+
+        run_hook(&command, &offline()).await.unwrap();
+        let written = std::fs::read_to_string(&out).unwrap();
+        std::fs::remove_file(&out).unwrap();
+
+        let (message, json) = written.split_once('\n').unwrap();
+        assert_eq!(message, "Runner 42 (build-box) went offline");
+        let body: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(body["events"][0]["event"], "runner_offline");
+
+        // stderr is captured rather than printed over the TUI
+        let failing = vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            "echo no route >&2; exit 3".to_string(),
+        ];
+        let err = run_hook(&failing, &offline()).await.unwrap_err();
+        assert!(err.to_string().ends_with(": no route"), "{}", err);
+    }
+
+    #[test]
+    fn test_sink_validation() {
+        let sink = SinkConfig::Webhook {
+            url: "example.com".to_string(),
+        };
+        assert!(sink.validate().is_err());
+        assert!(SinkConfig::Command { command: vec![] }.validate().is_err());
+    }
+}
@@ -0,0 +1,249 @@
+use crate::models::runner::{RunnerFilters, RunnerType};
+use crate::models::status::RunnerStatus;
+use crate::query::Query;
+use crate::tui::app::Command;
+use crate::tui::columns::ColumnSpec;
+use crate::tui::filter_form::{RUNNER_TYPE_OPTIONS, STATUS_OPTIONS};
+use crate::tui::table::Column;
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// One `[[presets]]` entry of `config.toml`: a command and its filters,
+/// expression, sort and columns, recalled by name
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Preset {
+    pub name: String,
+    pub command: Command,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub version_prefix: Option<String>,
+    #[serde(default, rename = "type")]
+    pub runner_type: Option<String>,
+    #[serde(default)]
+    pub paused: Option<bool>,
+    /// Client-side filter expression, as accepted by `--where`
+    #[serde(default, rename = "where")]
+    pub where_clause: Option<String>,
+    /// Column the results are sorted by
+    #[serde(default)]
+    pub sort: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub descending: bool,
+    /// Layout of the command's view, replacing the one from `[columns]`
+    #[serde(default)]
+    pub columns: Option<Vec<ColumnSpec>>,
+}
+
+impl Preset {
+    fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            bail!("a preset needs a name");
+        }
+        if let Some(status) = self.status.as_deref() {
+            if !STATUS_OPTIONS.contains(&status) {
+                bail!(
+                    "status: '{}' is not one of {}",
+                    status,
+                    STATUS_OPTIONS.join(", ")
+                );
+            }
+        }
+        if let Some(runner_type) = self.runner_type.as_deref() {
+            if !RUNNER_TYPE_OPTIONS.contains(&runner_type) {
+                bail!(
+                    "type: '{}' is not one of {}",
+                    runner_type,
+                    RUNNER_TYPE_OPTIONS.join(", ")
+                );
+            }
+        }
+        if let Some(expr) = self.where_clause.as_deref() {
+            Query::parse(expr).map_err(|e| anyhow!("where: {}", e))?;
+        }
+
+        let available = self.command.view().available_columns();
+        if let Some(name) = self.sort.as_deref() {
+            match Column::from_name(name) {
+                Some(column) if available.contains(&column) => {}
+                _ => bail!("sort: no column '{}' in the {} view", name, self.command),
+            }
+        }
+        if let Some(specs) = &self.columns {
+            if specs.is_empty() {
+                bail!("columns: at least one column is required");
+            }
+            if let Some(spec) = specs.iter().find(|s| !available.contains(&s.column)) {
+                bail!(
+                    "columns: column '{}' is not available in the {} view",
+                    spec.column.name(),
+                    self.command
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// The preset's filters, with any set in `overrides` (command-line flags)
+    /// taking precedence
+    pub fn filters(&self, overrides: RunnerFilters) -> RunnerFilters {
+        let tags = (!self.tags.is_empty()).then(|| self.tags.clone());
+        RunnerFilters {
+            tag_list: overrides.tag_list.or(tags),
+            status: overrides
+                .status
+                .or_else(|| self.status.as_deref().map(RunnerStatus::from)),
+            version_prefix: overrides
+                .version_prefix
+                .or_else(|| self.version_prefix.clone()),
+            runner_type: overrides
+                .runner_type
+                .or_else(|| self.runner_type.as_deref().map(RunnerType::from)),
+            paused: overrides.paused.or(self.paused),
+        }
+    }
+
+    /// Merge command-line filters and expression into the preset's; those
+    /// given on the command line win
+    pub fn merge(
+        &self,
+        filters: RunnerFilters,
+        query: Option<Query>,
+    ) -> (RunnerFilters, Option<Query>) {
+        (self.filters(filters), query.or_else(|| self.query()))
+    }
+
+    /// The parsed `where` expression; presets are validated on load
+    pub fn query(&self) -> Option<Query> {
+        self.where_clause
+            .as_deref()
+            .and_then(|expr| Query::parse(expr).ok())
+    }
+
+    pub fn sort_column(&self) -> Option<Column> {
+        self.sort.as_deref().and_then(Column::from_name)
+    }
+
+    /// The filters, expression and sort on one line, e.g.
+    /// `tags=production status=online`
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.tags.is_empty() {
+            parts.push(format!("tags={}", self.tags.join(",")));
+        }
+        for (name, value) in [
+            ("status", &self.status),
+            ("version", &self.version_prefix),
+            ("type", &self.runner_type),
+            ("where", &self.where_clause),
+            ("sort", &self.sort),
+        ] {
+            if let Some(value) = value {
+                parts.push(format!("{}={}", name, value));
+            }
+        }
+        if let Some(paused) = self.paused {
+            parts.push(format!("paused={}", paused));
+        }
+        if parts.is_empty() {
+            "no filters".to_string()
+        } else {
+            parts.join(" ")
+        }
+    }
+}
+
+/// Check every preset, and that names are unique
+pub fn validate(presets: &[Preset]) -> Result<()> {
+    let mut names = HashSet::new();
+    for (i, preset) in presets.iter().enumerate() {
+        preset
+            .validate()
+            .map_err(|e| anyhow!("presets[{}]: {}", i, e))?;
+        if !names.insert(preset.name.as_str()) {
+            bail!("presets[{}]: duplicate preset name '{}'", i, preset.name);
+        }
+    }
+    Ok(())
+}
+
+/// The preset called `name`
+pub fn find<'a>(presets: &'a [Preset], name: &str) -> Result<&'a Preset> {
+    presets.iter().find(|p| p.name == name).ok_or_else(|| {
+        let names: Vec<&str> = presets.iter().map(|p| p.name.as_str()).collect();
+        if names.is_empty() {
+            anyhow!("no preset named '{}' (none configured)", name)
+        } else {
+            anyhow!(
+                "no preset named '{}' (expected one of: {})",
+                name,
+                names.join(", ")
+            )
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preset(toml_str: &str) -> Result<Preset> {
+        let preset: Preset = toml::from_str(toml_str)?;
+        preset.validate()?;
+        Ok(preset)
+    }
+
+    #[test]
+    fn test_flags_override_preset_filters() {
+        let preset = preset(
+            r#"
+            name = "prod"
+            command = "lights"
+            tags = ["production", "linux"]
+            status = "online"
+            paused = false
+            where = "version < 17.0"
+            "#,
+        )
+        .unwrap();
+        let overrides = RunnerFilters {
+            status: Some(RunnerStatus::Offline),
+            ..RunnerFilters::default()
+        };
+        let filters = preset.filters(overrides);
+        assert_eq!(
+            filters.tag_list,
+            Some(vec!["production".to_string(), "linux".to_string()])
+        );
+        assert_eq!(filters.status, Some(RunnerStatus::Offline));
+        assert_eq!(filters.paused, Some(false));
+        assert!(preset.query().is_some());
+    }
+
+    #[test]
+    fn test_rejects_invalid_presets() {
+        let base = "name = \"x\"\ncommand = \"workers\"\n";
+        assert!(preset(&format!("{}sort = \"version\"", base)).is_ok());
+        assert!(preset(&format!("{}sort = \"nope\"", base)).is_err());
+        assert!(preset(&format!("{}status = \"sleeping\"", base)).is_err());
+        assert!(preset(&format!("{}where = \"status =\"", base)).is_err());
+        assert!(preset(&format!("{}columns = []", base)).is_err());
+        assert!(preset("name = \"x\"\ncommand = \"teleport\"").is_err());
+
+        let a = preset(base).unwrap();
+        let err = validate(&[a.clone(), a]).unwrap_err();
+        assert!(err.to_string().contains("duplicate preset name"));
+    }
+
+    #[test]
+    fn test_find_lists_names() {
+        let presets = vec![preset("name = \"a\"\ncommand = \"fetch\"").unwrap()];
+        assert!(find(&presets, "a").is_ok());
+        let err = find(&presets, "b").unwrap_err();
+        assert_eq!(err.to_string(), "no preset named 'b' (expected one of: a)");
+    }
+}
@@ -0,0 +1,408 @@
+use super::{Comparison, Expr, Field, Op, Query, Value};
+use crate::models::manager::RunnerManager;
+use crate::models::runner::Runner;
+use crate::models::version::parse_version;
+use chrono::{DateTime, Utc};
+use std::cmp::Ordering;
+
+/// The value of a field for one runner (and optionally one of its managers)
+enum FieldValue<'a> {
+    Number(f64),
+    Text(&'a str),
+    Bool(bool),
+    Version(Option<&'a str>),
+    List(&'a [String]),
+}
+
+impl Query {
+    pub fn matches_runner(&self, runner: &Runner) -> bool {
+        self.matches_runner_at(runner, Utc::now())
+    }
+
+    /// Evaluate with `uncontacted` measured up to `now`
+    pub fn matches_runner_at(&self, runner: &Runner, now: DateTime<Utc>) -> bool {
+        eval(&self.expr, runner, None, now)
+    }
+
+    /// Evaluate against a single manager, as shown in the workers view
+    pub fn matches_manager(&self, runner: &Runner, manager: &RunnerManager) -> bool {
+        eval(&self.expr, runner, Some(manager), Utc::now())
+    }
+
+    pub fn filter_runners(&self, runners: Vec<Runner>) -> Vec<Runner> {
+        runners
+            .into_iter()
+            .filter(|r| self.matches_runner(r))
+            .collect()
+    }
+
+    /// Keep only matching managers, dropping runners left with none
+    pub fn filter_managers(&self, runners: Vec<Runner>) -> Vec<Runner> {
+        runners
+            .into_iter()
+            .filter_map(|mut runner| {
+                let managers = std::mem::take(&mut runner.managers);
+                runner.managers = managers
+                    .into_iter()
+                    .filter(|m| self.matches_manager(&runner, m))
+                    .collect();
+                (!runner.managers.is_empty()).then_some(runner)
+            })
+            .collect()
+    }
+}
+
+fn eval(expr: &Expr, runner: &Runner, manager: Option<&RunnerManager>, now: DateTime<Utc>) -> bool {
+    match expr {
+        Expr::And(left, right) => {
+            eval(left, runner, manager, now) && eval(right, runner, manager, now)
+        }
+        Expr::Or(left, right) => {
+            eval(left, runner, manager, now) || eval(right, runner, manager, now)
+        }
+        Expr::Not(inner) => !eval(inner, runner, manager, now),
+        Expr::Compare(comparison) => match manager {
+            None if comparison.field.is_manager_field() => runner.managers.iter().any(|m| {
+                compare(
+                    comparison,
+                    field_value(comparison.field, runner, Some(m), now),
+                )
+            }),
+            _ => compare(
+                comparison,
+                field_value(comparison.field, runner, manager, now),
+            ),
+        },
+    }
+}
+
+fn field_value<'a>(
+    field: Field,
+    runner: &'a Runner,
+    manager: Option<&'a RunnerManager>,
+    now: DateTime<Utc>,
+) -> FieldValue<'a> {
+    let opt = |value: &'a Option<String>| value.as_deref().unwrap_or("");
+    match (field, manager) {
+        (Field::Id, _) => FieldValue::Number(runner.id as f64),
+        (Field::Description, _) => FieldValue::Text(opt(&runner.description)),
+        (Field::Status, Some(m)) | (Field::ManagerStatus, Some(m)) => {
+            FieldValue::Text(m.status.as_str())
+        }
+        (Field::Status, None) => FieldValue::Text(runner.status.as_str()),
+        (Field::RunnerType, _) => FieldValue::Text(runner.runner_type.as_str()),
+        (Field::Version, Some(m)) => {
+            FieldValue::Version(m.version.as_deref().or(runner.version.as_deref()))
+        }
+        (Field::Version, None) => FieldValue::Version(runner.version.as_deref()),
+        (Field::Paused, _) => FieldValue::Bool(runner.paused),
+        (Field::Active, _) => FieldValue::Bool(runner.active),
+        (Field::Shared, _) => FieldValue::Bool(runner.is_shared),
+        (Field::Ip, Some(m)) => FieldValue::Text(opt(&m.ip_address)),
+        (Field::Ip, None) => FieldValue::Text(opt(&runner.ip_address)),
+        (Field::Tags, _) => FieldValue::List(&runner.tag_list),
+        (Field::Managers, _) => FieldValue::Number(runner.managers.len() as f64),
+        (Field::SystemId, Some(m)) => FieldValue::Text(&m.system_id),
+        (Field::Platform, Some(m)) => FieldValue::Text(opt(&m.platform)),
+        (Field::Architecture, Some(m)) => FieldValue::Text(opt(&m.architecture)),
+        (Field::ManagerVersion, Some(m)) => FieldValue::Version(m.version.as_deref()),
+        // Manager fields are always evaluated per manager (see `eval`)
+        (Field::SystemId, None)
+        | (Field::Platform, None)
+        | (Field::Architecture, None)
+        | (Field::ManagerStatus, None) => FieldValue::Text(""),
+        (Field::ManagerVersion, None) => FieldValue::Version(None),
+        (Field::Uncontacted, Some(m)) => FieldValue::Number(seconds_since(m.contacted_at, now)),
+        (Field::Uncontacted, None) => FieldValue::Number(seconds_since(
+            runner.managers.iter().filter_map(|m| m.contacted_at).max(),
+            now,
+        )),
+    }
+}
+
+/// Never contacted (or no managers at all) is infinitely long ago
+fn seconds_since(contacted_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> f64 {
+    contacted_at
+        .map(|t| (now - t).num_seconds() as f64)
+        .unwrap_or(f64::INFINITY)
+}
+
+fn compare(comparison: &Comparison, actual: FieldValue) -> bool {
+    let op = comparison.op;
+    match (&comparison.value, actual) {
+        (Value::Regex(re), FieldValue::Text(text)) => (op == Op::Matches) == re.is_match(text),
+        (Value::Regex(re), FieldValue::Version(version)) => {
+            (op == Op::Matches) == version.is_some_and(|v| re.is_match(v))
+        }
+        (Value::Regex(re), FieldValue::List(items)) => {
+            (op == Op::Matches) == items.iter().any(|i| re.is_match(i))
+        }
+        (Value::VersionReq(req), FieldValue::Version(version)) => version
+            .and_then(parse_version)
+            .is_some_and(|v| req.matches(&v)),
+        (Value::Version(expected), FieldValue::Version(version)) => {
+            match version.and_then(parse_version) {
+                Some(actual) => ordering_matches(op, actual.cmp(expected)),
+                // Unknown versions only satisfy "not equal"
+                None => op == Op::Ne,
+            }
+        }
+        (Value::Number(expected), FieldValue::Number(actual)) => actual
+            .partial_cmp(expected)
+            .is_some_and(|ord| ordering_matches(op, ord)),
+        (Value::Bool(expected), FieldValue::Bool(actual)) => {
+            ordering_matches(op, actual.cmp(expected))
+        }
+        (Value::Text(expected), FieldValue::Text(actual)) => match op {
+            Op::Contains => actual
+                .to_ascii_lowercase()
+                .contains(&expected.to_ascii_lowercase()),
+            _ => ordering_matches(op, text_ordering(actual, expected)),
+        },
+        (Value::Text(expected), FieldValue::List(items)) => {
+            let found = items.iter().any(|i| i.eq_ignore_ascii_case(expected));
+            if op == Op::Ne {
+                !found
+            } else {
+                found
+            }
+        }
+        // The parser only builds value/field combinations handled above
+        _ => false,
+    }
+}
+
+/// Text compares case-insensitively for equality and ordering alike
+fn text_ordering(actual: &str, expected: &str) -> Ordering {
+    actual
+        .to_ascii_lowercase()
+        .cmp(&expected.to_ascii_lowercase())
+}
+
+fn ordering_matches(op: Op, ordering: Ordering) -> bool {
+    match op {
+        Op::Eq => ordering == Ordering::Equal,
+        Op::Ne => ordering != Ordering::Equal,
+        Op::Lt => ordering == Ordering::Less,
+        Op::Le => ordering != Ordering::Greater,
+        Op::Gt => ordering == Ordering::Greater,
+        Op::Ge => ordering != Ordering::Less,
+        Op::Matches | Op::NotMatches | Op::Contains | Op::Satisfies => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::status::RunnerStatus;
+    use crate::models::test_support;
+
+    fn manager(system_id: &str, status: &str, version: &str, platform: &str) -> RunnerManager {
+        RunnerManager {
+            contacted_at: Some("2024-01-20T14:22:00.000Z".parse().unwrap()),
+            ip_address: Some("10.0.1.1".to_string()),
+            status: RunnerStatus::from(status),
+            version: Some(version.to_string()),
+            platform: Some(platform.to_string()),
+            architecture: Some("amd64".to_string()),
+            ..test_support::manager(1, system_id)
+        }
+    }
+
+    fn runner(id: u64, description: &str, version: &str, managers: Vec<RunnerManager>) -> Runner {
+        Runner {
+            description: Some(description.to_string()),
+            version: Some(version.to_string()),
+            tag_list: test_support::tags(&["docker", "Linux"]),
+            managers,
+            ..test_support::runner(id)
+        }
+    }
+
+    fn matches(source: &str, runner: &Runner) -> bool {
+        Query::parse(source).unwrap().matches_runner(runner)
+    }
+
+    #[test]
+    fn test_version_comparison_is_semver_aware() {
+        let r = runner(1, "a", "16.11.1", vec![]);
+        assert!(matches("version < 17.0", &r));
+        assert!(matches("version > 16.9", &r));
+        assert!(!matches("version >= 17", &r));
+        assert!(matches("version = 16.11.1", &r));
+    }
+
+    #[test]
+    fn test_unknown_version_only_matches_not_equal() {
+        let mut r = runner(1, "a", "x", vec![]);
+        r.version = None;
+        assert!(!matches("version < 17.0", &r));
+        assert!(!matches("version >= 0", &r));
+        assert!(matches("version != 17.0", &r));
+    }
+
+    #[test]
+    fn test_version_satisfies_requirement() {
+        let r = runner(1, "a", "16.11.1", vec![]);
+        assert!(matches("version satisfies '>=16.0, <17'", &r));
+        assert!(!matches("version satisfies ^17", &r));
+    }
+
+    #[test]
+    fn test_online_but_old_version() {
+        let r = runner(1, "a", "16.5.0", vec![]);
+        assert!(matches("status = online and version < 17.0", &r));
+        let mut offline = r.clone();
+        offline.status = RunnerStatus::Offline;
+        assert!(!matches("status = online and version < 17.0", &offline));
+    }
+
+    #[test]
+    fn test_manager_fields_match_any_manager() {
+        let r = runner(
+            1,
+            "a",
+            "17.0.0",
+            vec![
+                manager("host-a", "online", "17.0.0", "linux"),
+                manager("host-b", "offline", "16.0.0", "windows"),
+            ],
+        );
+        assert!(matches("managers > 1 and platform = windows", &r));
+        assert!(matches("manager_version < 17", &r));
+        assert!(!matches("platform = darwin", &r));
+        assert!(!matches("managers > 2", &r));
+    }
+
+    #[test]
+    fn test_regex_matches_description() {
+        let r = runner(1, "prod-runner-01", "17.0.0", vec![]);
+        assert!(matches("description matches ^prod-", &r));
+        assert!(!matches("description !~ ^prod-", &r));
+        assert!(!matches("description ~ ^staging-", &r));
+    }
+
+    #[test]
+    fn test_text_equality_is_case_insensitive() {
+        let r = runner(1, "Prod", "17.0.0", vec![]);
+        assert!(matches("description = prod", &r));
+        assert!(matches("description contains RO", &r));
+        assert!(!matches("description != PROD", &r));
+    }
+
+    #[test]
+    fn test_text_ordering_is_case_insensitive() {
+        let r = runner(1, "Prod", "17.0.0", vec![]);
+        assert!(matches("description >= prod", &r));
+        assert!(matches("description <= PROD", &r));
+        assert!(matches("description > alpha", &r));
+        assert!(matches("description < ZULU", &r));
+    }
+
+    #[test]
+    fn test_tags_list_semantics() {
+        let r = runner(1, "a", "17.0.0", vec![]);
+        assert!(matches("tags = docker", &r));
+        assert!(matches("tags contains linux", &r));
+        assert!(matches("tags != gpu", &r));
+        assert!(!matches("tags != docker", &r));
+        assert!(!matches("tags ~ ^lin", &r));
+        assert!(matches("tags ~ '(?i)^lin'", &r));
+    }
+
+    #[test]
+    fn test_boolean_fields_and_not() {
+        let mut r = runner(1, "a", "17.0.0", vec![]);
+        assert!(matches("not paused and active", &r));
+        r.paused = true;
+        assert!(matches("paused = true", &r));
+        assert!(matches("!(active = false)", &r));
+    }
+
+    #[test]
+    fn test_missing_optional_text_is_empty() {
+        let mut r = runner(1, "a", "17.0.0", vec![]);
+        r.description = None;
+        assert!(matches("description = ''", &r));
+        assert!(matches("ip != 10.0.0.1", &r));
+    }
+
+    #[test]
+    fn test_uncontacted_uses_most_recent_manager() {
+        let mut recent = manager("recent", "online", "17.0.0", "linux");
+        recent.contacted_at = Some(Utc::now() - chrono::Duration::minutes(5));
+        let stale = manager("stale", "offline", "17.0.0", "linux");
+        let r = runner(1, "a", "17.0.0", vec![stale.clone(), recent.clone()]);
+
+        assert!(matches("uncontacted < 10m", &r));
+        assert!(!matches("uncontacted > 3600", &r));
+        let query = Query::parse("uncontacted > 1d").unwrap();
+        assert!(query.matches_manager(&r, &stale));
+        assert!(!query.matches_manager(&r, &recent));
+        // No managers: never contacted
+        assert!(matches(
+            "uncontacted > 1d",
+            &runner(2, "b", "17.0.0", vec![])
+        ));
+    }
+
+    #[test]
+    fn test_contextual_fields_use_manager_in_manager_context() {
+        let r = runner(
+            1,
+            "a",
+            "17.0.0",
+            vec![
+                manager("host-a", "online", "17.0.0", "linux"),
+                manager("host-b", "offline", "16.0.0", "windows"),
+            ],
+        );
+        let query = Query::parse("status = offline and version < 17").unwrap();
+        assert!(!query.matches_runner(&r));
+        assert!(!query.matches_manager(&r, &r.managers[0]));
+        assert!(query.matches_manager(&r, &r.managers[1]));
+    }
+
+    #[test]
+    fn test_filter_managers_drops_empty_runners() {
+        let runners = vec![
+            runner(
+                1,
+                "a",
+                "17.0.0",
+                vec![manager("a", "online", "17.0.0", "linux")],
+            ),
+            runner(
+                2,
+                "b",
+                "17.0.0",
+                vec![
+                    manager("b1", "online", "17.0.0", "linux"),
+                    manager("b2", "offline", "17.0.0", "windows"),
+                ],
+            ),
+        ];
+
+        let filtered = Query::parse("platform = windows")
+            .unwrap()
+            .filter_managers(runners);
+
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].id, 2);
+        assert_eq!(filtered[0].managers.len(), 1);
+        assert_eq!(filtered[0].managers[0].system_id, "b2");
+    }
+
+    #[test]
+    fn test_filter_runners() {
+        let runners = vec![
+            runner(1, "prod-a", "17.0.0", vec![]),
+            runner(2, "dev-b", "17.0.0", vec![]),
+        ];
+        let filtered = Query::parse("description ~ ^prod")
+            .unwrap()
+            .filter_runners(runners);
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].id, 1);
+    }
+}
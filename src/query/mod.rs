@@ -0,0 +1,891 @@
+//! Client-side filter expressions evaluated over fetched runners.
+//!
+//! Expressions compare runner (or manager) fields against values and combine
+//! comparisons with `and`, `or`, `not` and parentheses:
+//!
+//! ```text
+//! status = online and version < 17.0
+//! managers > 1 and platform = windows
+//! description matches ^prod- or tags contains gpu
+//! version satisfies ">=16.0, <17" and not paused
+//! uncontacted > 1h and managers > 0
+//! ```
+//!
+//! Everything is validated when the expression is parsed, so evaluation
+//! itself can't fail.
+
+mod eval;
+
+use crate::models::version::parse_version;
+use regex::Regex;
+use semver::{Version, VersionReq};
+use std::fmt;
+use std::str::FromStr;
+
+/// A parsed filter expression together with its source text
+#[derive(Debug, Clone)]
+pub struct Query {
+    source: String,
+    expr: Expr,
+}
+
+impl Query {
+    pub fn parse(source: &str) -> Result<Self, ParseError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            end: source.len(),
+        };
+        let expr = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            return Err(ParseError::new(
+                format!("unexpected '{}'", token.text),
+                token.offset,
+            ));
+        }
+        Ok(Self {
+            source: source.trim().to_string(),
+            expr,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+}
+
+impl PartialEq for Query {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl FromStr for Query {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Query::parse(s)
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+    /// Byte offset into the source where the error was detected
+    pub offset: usize,
+}
+
+impl ParseError {
+    fn new(message: impl Into<String>, offset: usize) -> Self {
+        Self {
+            message: message.into(),
+            offset,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.offset + 1)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Comparison),
+}
+
+#[derive(Debug, Clone)]
+struct Comparison {
+    field: Field,
+    op: Op,
+    value: Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldKind {
+    Number,
+    Text,
+    Bool,
+    Version,
+    List,
+    /// Seconds, written as a number or with an `s`/`m`/`h`/`d` suffix
+    Duration,
+}
+
+/// Fields available in expressions.
+///
+/// `status`, `version` and `ip` refer to the manager when evaluated against a
+/// single manager (the workers view) and to the runner otherwise. Manager-only
+/// fields evaluated against a runner match if any of its managers match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Id,
+    Description,
+    Status,
+    RunnerType,
+    Version,
+    Paused,
+    Active,
+    Shared,
+    Ip,
+    Tags,
+    Managers,
+    SystemId,
+    Platform,
+    Architecture,
+    ManagerStatus,
+    ManagerVersion,
+    /// Time since the last contact: the manager's, or the most recent of a
+    /// runner's managers
+    Uncontacted,
+}
+
+impl Field {
+    const NAMES: &'static [(&'static str, Field)] = &[
+        ("id", Field::Id),
+        ("description", Field::Description),
+        ("status", Field::Status),
+        ("type", Field::RunnerType),
+        ("runner_type", Field::RunnerType),
+        ("version", Field::Version),
+        ("paused", Field::Paused),
+        ("active", Field::Active),
+        ("shared", Field::Shared),
+        ("is_shared", Field::Shared),
+        ("ip", Field::Ip),
+        ("ip_address", Field::Ip),
+        ("tags", Field::Tags),
+        ("tag", Field::Tags),
+        ("managers", Field::Managers),
+        ("system_id", Field::SystemId),
+        ("platform", Field::Platform),
+        ("arch", Field::Architecture),
+        ("architecture", Field::Architecture),
+        ("manager_status", Field::ManagerStatus),
+        ("manager_version", Field::ManagerVersion),
+        ("uncontacted", Field::Uncontacted),
+    ];
+
+    fn from_name(name: &str) -> Option<Field> {
+        Self::NAMES
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, f)| *f)
+    }
+
+    fn kind(&self) -> FieldKind {
+        match self {
+            Field::Id | Field::Managers => FieldKind::Number,
+            Field::Paused | Field::Active | Field::Shared => FieldKind::Bool,
+            Field::Version | Field::ManagerVersion => FieldKind::Version,
+            Field::Tags => FieldKind::List,
+            Field::Uncontacted => FieldKind::Duration,
+            Field::Description
+            | Field::Status
+            | Field::RunnerType
+            | Field::Ip
+            | Field::SystemId
+            | Field::Platform
+            | Field::Architecture
+            | Field::ManagerStatus => FieldKind::Text,
+        }
+    }
+
+    fn is_manager_field(&self) -> bool {
+        matches!(
+            self,
+            Field::SystemId
+                | Field::Platform
+                | Field::Architecture
+                | Field::ManagerStatus
+                | Field::ManagerVersion
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Matches,
+    NotMatches,
+    Contains,
+    Satisfies,
+}
+
+impl Op {
+    fn from_token(text: &str) -> Option<Op> {
+        let op = match text.to_ascii_lowercase().as_str() {
+            "=" | "==" => Op::Eq,
+            "!=" => Op::Ne,
+            "<" => Op::Lt,
+            "<=" => Op::Le,
+            ">" => Op::Gt,
+            ">=" => Op::Ge,
+            "~" | "=~" | "matches" => Op::Matches,
+            "!~" => Op::NotMatches,
+            "contains" => Op::Contains,
+            "satisfies" => Op::Satisfies,
+            _ => return None,
+        };
+        Some(op)
+    }
+
+    fn is_ordering(&self) -> bool {
+        matches!(self, Op::Lt | Op::Le | Op::Gt | Op::Ge)
+    }
+
+    fn allowed_for(&self, kind: FieldKind) -> bool {
+        match self {
+            Op::Eq | Op::Ne => true,
+            Op::Lt | Op::Le | Op::Gt | Op::Ge => matches!(
+                kind,
+                FieldKind::Number | FieldKind::Text | FieldKind::Version | FieldKind::Duration
+            ),
+            Op::Matches | Op::NotMatches => {
+                matches!(kind, FieldKind::Text | FieldKind::Version | FieldKind::List)
+            }
+            Op::Contains => matches!(kind, FieldKind::Text | FieldKind::List),
+            Op::Satisfies => kind == FieldKind::Version,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Value {
+    Number(f64),
+    Text(String),
+    Bool(bool),
+    Version(Version),
+    Regex(Regex),
+    VersionReq(VersionReq),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Word,
+    Quoted,
+    Symbol,
+    LParen,
+    RParen,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    text: String,
+    offset: usize,
+}
+
+impl Token {
+    fn is_keyword(&self, keyword: &str) -> bool {
+        self.kind == TokenKind::Word && self.text.eq_ignore_ascii_case(keyword)
+    }
+
+    fn is_symbol(&self, symbol: &str) -> bool {
+        self.kind == TokenKind::Symbol && self.text == symbol
+    }
+}
+
+const SYMBOL_CHARS: &[char] = &['=', '!', '<', '>', '~', '&', '|'];
+const SYMBOLS: &[&str] = &[
+    "==", "!=", "<=", ">=", "=~", "!~", "&&", "||", "=", "<", ">", "~", "!",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+
+    while let Some(&(offset, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '(' || c == ')' {
+            chars.next();
+            tokens.push(Token {
+                kind: if c == '(' {
+                    TokenKind::LParen
+                } else {
+                    TokenKind::RParen
+                },
+                text: c.to_string(),
+                offset,
+            });
+        } else if c == '"' || c == '\'' {
+            chars.next();
+            let mut text = String::new();
+            let mut closed = false;
+            while let Some((_, ch)) = chars.next() {
+                match ch {
+                    '\\' => match chars.next() {
+                        Some((_, escaped)) if escaped == c || escaped == '\\' => text.push(escaped),
+                        Some((_, other)) => {
+                            text.push('\\');
+                            text.push(other);
+                        }
+                        None => break,
+                    },
+                    ch if ch == c => {
+                        closed = true;
+                        break;
+                    }
+                    ch => text.push(ch),
+                }
+            }
+            if !closed {
+                return Err(ParseError::new("unterminated string", offset));
+            }
+            tokens.push(Token {
+                kind: TokenKind::Quoted,
+                text,
+                offset,
+            });
+        } else if SYMBOL_CHARS.contains(&c) {
+            let rest = &source[offset..];
+            let symbol = SYMBOLS
+                .iter()
+                .find(|s| rest.starts_with(**s))
+                .ok_or_else(|| ParseError::new(format!("unexpected '{}'", c), offset))?;
+            for _ in 0..symbol.chars().count() {
+                chars.next();
+            }
+            tokens.push(Token {
+                kind: TokenKind::Symbol,
+                text: symbol.to_string(),
+                offset,
+            });
+        } else {
+            let mut text = String::new();
+            while let Some(&(_, ch)) = chars.peek() {
+                if ch.is_whitespace()
+                    || ch == '('
+                    || ch == ')'
+                    || ch == '"'
+                    || ch == '\''
+                    || SYMBOL_CHARS.contains(&ch)
+                {
+                    break;
+                }
+                text.push(ch);
+                chars.next();
+            }
+            tokens.push(Token {
+                kind: TokenKind::Word,
+                text,
+                offset,
+            });
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Offset reported for errors at end of input
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        if token.is_some() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn expect_token(&mut self, what: &str) -> Result<Token, ParseError> {
+        self.advance()
+            .ok_or_else(|| ParseError::new(format!("expected {}", what), self.end))
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, ParseError> {
+        let mut left = self.parse_and()?;
+        while self
+            .peek()
+            .is_some_and(|t| t.is_keyword("or") || t.is_symbol("||"))
+        {
+            self.advance();
+            let right = self.parse_and()?;
+            left = Expr::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, ParseError> {
+        let mut left = self.parse_not()?;
+        while self
+            .peek()
+            .is_some_and(|t| t.is_keyword("and") || t.is_symbol("&&"))
+        {
+            self.advance();
+            let right = self.parse_not()?;
+            left = Expr::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, ParseError> {
+        if self
+            .peek()
+            .is_some_and(|t| t.is_keyword("not") || t.is_symbol("!"))
+        {
+            self.advance();
+            let inner = self.parse_not()?;
+            return Ok(Expr::Not(Box::new(inner)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, ParseError> {
+        let token = self.expect_token("a field name or '('")?;
+        match token.kind {
+            TokenKind::LParen => {
+                let expr = self.parse_or()?;
+                match self.advance() {
+                    Some(t) if t.kind == TokenKind::RParen => Ok(expr),
+                    Some(t) => Err(ParseError::new(
+                        format!("expected ')' but found '{}'", t.text),
+                        t.offset,
+                    )),
+                    None => Err(ParseError::new("expected ')'", self.end)),
+                }
+            }
+            TokenKind::Word => self.parse_comparison(token),
+            _ => Err(ParseError::new(
+                format!("expected a field name but found '{}'", token.text),
+                token.offset,
+            )),
+        }
+    }
+
+    fn parse_comparison(&mut self, field_token: Token) -> Result<Expr, ParseError> {
+        let field = Field::from_name(&field_token.text).ok_or_else(|| {
+            ParseError::new(
+                format!("unknown field '{}'", field_token.text),
+                field_token.offset,
+            )
+        })?;
+
+        let op = match self.peek().and_then(op_for) {
+            Some(op) => {
+                self.advance();
+                op
+            }
+            // A bare boolean field means `field = true`
+            None if field.kind() == FieldKind::Bool => {
+                return Ok(Expr::Compare(Comparison {
+                    field,
+                    op: Op::Eq,
+                    value: Value::Bool(true),
+                }));
+            }
+            None => {
+                let (found, offset) = match self.peek() {
+                    Some(t) => (format!(" but found '{}'", t.text), t.offset),
+                    None => (String::new(), self.end),
+                };
+                return Err(ParseError::new(
+                    format!("expected an operator after '{}'{}", field_token.text, found),
+                    offset,
+                ));
+            }
+        };
+
+        if !op.allowed_for(field.kind()) {
+            return Err(ParseError::new(
+                format!(
+                    "operator '{}' is not supported for field '{}'",
+                    self.tokens[self.pos - 1].text,
+                    field_token.text
+                ),
+                self.tokens[self.pos - 1].offset,
+            ));
+        }
+
+        let value_token = self.expect_token("a value")?;
+        if !matches!(value_token.kind, TokenKind::Word | TokenKind::Quoted) {
+            return Err(ParseError::new(
+                format!("expected a value but found '{}'", value_token.text),
+                value_token.offset,
+            ));
+        }
+
+        let value = build_value(field, op, &value_token)?;
+        Ok(Expr::Compare(Comparison { field, op, value }))
+    }
+}
+
+fn op_for(token: &Token) -> Option<Op> {
+    match token.kind {
+        TokenKind::Symbol => Op::from_token(&token.text),
+        TokenKind::Word => match token.text.to_ascii_lowercase().as_str() {
+            "matches" | "contains" | "satisfies" => Op::from_token(&token.text),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn build_value(field: Field, op: Op, token: &Token) -> Result<Value, ParseError> {
+    let text = token.text.as_str();
+    let invalid = |what: &str, detail: String| {
+        ParseError::new(
+            format!("invalid {} '{}': {}", what, text, detail),
+            token.offset,
+        )
+    };
+
+    match op {
+        Op::Matches | Op::NotMatches => Regex::new(text)
+            .map(Value::Regex)
+            .map_err(|e| invalid("regex", e.to_string())),
+        Op::Satisfies => VersionReq::parse(text)
+            .map(Value::VersionReq)
+            .map_err(|e| invalid("version requirement", e.to_string())),
+        _ => match field.kind() {
+            FieldKind::Number => text
+                .parse::<f64>()
+                .map(Value::Number)
+                .map_err(|_| invalid("number", "expected a numeric value".to_string())),
+            FieldKind::Duration => parse_duration_secs(text)
+                .map(Value::Number)
+                .ok_or_else(|| invalid("duration", "expected e.g. 3600, 90m or 2h".to_string())),
+            FieldKind::Bool => match text.to_ascii_lowercase().as_str() {
+                "true" | "yes" => Ok(Value::Bool(true)),
+                "false" | "no" => Ok(Value::Bool(false)),
+                _ => Err(invalid("boolean", "expected true or false".to_string())),
+            },
+            FieldKind::Version if op.is_ordering() || op == Op::Eq || op == Op::Ne => {
+                parse_version(text)
+                    .map(Value::Version)
+                    .ok_or_else(|| invalid("version", "expected e.g. 17.5.0".to_string()))
+            }
+            FieldKind::Text | FieldKind::List | FieldKind::Version => {
+                Ok(Value::Text(text.to_string()))
+            }
+        },
+    }
+}
+
+/// `3600`, `90m`, `2h`, `1d` as seconds
+fn parse_duration_secs(text: &str) -> Option<f64> {
+    let (number, unit) = match text.char_indices().last()? {
+        (i, 's') => (&text[..i], 1.0),
+        (i, 'm') => (&text[..i], 60.0),
+        (i, 'h') => (&text[..i], 3600.0),
+        (i, 'd') => (&text[..i], 86400.0),
+        _ => (text, 1.0),
+    };
+    let value = number.parse::<f64>().ok()?;
+    (value >= 0.0).then_some(value * unit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_err(source: &str) -> ParseError {
+        Query::parse(source).expect_err("expected parse error")
+    }
+
+    fn compare(expr: &Expr) -> &Comparison {
+        match expr {
+            Expr::Compare(c) => c,
+            other => panic!("expected comparison, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_simple_comparison() {
+        let query = Query::parse("status = online").unwrap();
+        let c = compare(&query.expr);
+        assert_eq!(c.field, Field::Status);
+        assert_eq!(c.op, Op::Eq);
+        assert!(matches!(&c.value, Value::Text(t) if t == "online"));
+    }
+
+    #[test]
+    fn test_parse_all_comparison_operators() {
+        for (source, op) in [
+            ("id = 1", Op::Eq),
+            ("id == 1", Op::Eq),
+            ("id != 1", Op::Ne),
+            ("id < 1", Op::Lt),
+            ("id <= 1", Op::Le),
+            ("id > 1", Op::Gt),
+            ("id >= 1", Op::Ge),
+            ("description ~ x", Op::Matches),
+            ("description =~ x", Op::Matches),
+            ("description matches x", Op::Matches),
+            ("description !~ x", Op::NotMatches),
+            ("tags contains x", Op::Contains),
+            ("version satisfies ^17", Op::Satisfies),
+        ] {
+            let query = Query::parse(source).unwrap();
+            assert_eq!(compare(&query.expr).op, op, "source: {}", source);
+        }
+    }
+
+    #[test]
+    fn test_parse_operators_without_whitespace() {
+        let query = Query::parse("managers>1").unwrap();
+        let c = compare(&query.expr);
+        assert_eq!(c.field, Field::Managers);
+        assert_eq!(c.op, Op::Gt);
+        assert!(matches!(c.value, Value::Number(n) if n == 1.0));
+    }
+
+    #[test]
+    fn test_parse_version_values_are_semver() {
+        let query = Query::parse("version < 17.0").unwrap();
+        assert!(
+            matches!(&compare(&query.expr).value, Value::Version(v) if *v == Version::new(17, 0, 0))
+        );
+    }
+
+    #[test]
+    fn test_parse_and_binds_tighter_than_or() {
+        let query = Query::parse("status = online or id = 1 and paused").unwrap();
+        match &query.expr {
+            Expr::Or(left, right) => {
+                assert_eq!(compare(left).field, Field::Status);
+                assert!(matches!(**right, Expr::And(_, _)));
+            }
+            other => panic!("expected or at the root, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_parentheses_override_precedence() {
+        let query = Query::parse("(status = online or id = 1) and paused").unwrap();
+        match &query.expr {
+            Expr::And(left, _) => assert!(matches!(**left, Expr::Or(_, _))),
+            other => panic!("expected and at the root, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_not_and_symbolic_connectives() {
+        let query = Query::parse("!paused && (shared || not active)").unwrap();
+        match &query.expr {
+            Expr::And(left, right) => {
+                assert!(matches!(**left, Expr::Not(_)));
+                assert!(matches!(**right, Expr::Or(_, _)));
+            }
+            other => panic!("expected and at the root, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_keywords_are_case_insensitive() {
+        assert!(Query::parse("STATUS = online AND NOT Paused").is_ok());
+    }
+
+    #[test]
+    fn test_parse_bare_bool_field() {
+        let query = Query::parse("paused").unwrap();
+        let c = compare(&query.expr);
+        assert_eq!(c.field, Field::Paused);
+        assert!(matches!(c.value, Value::Bool(true)));
+    }
+
+    #[test]
+    fn test_parse_quoted_values() {
+        let query = Query::parse(r#"description = "prod runner \"a\"""#).unwrap();
+        assert!(matches!(&compare(&query.expr).value, Value::Text(t) if t == r#"prod runner "a""#));
+
+        let query = Query::parse("description ~ 'a|b'").unwrap();
+        assert!(matches!(&compare(&query.expr).value, Value::Regex(r) if r.as_str() == "a|b"));
+    }
+
+    #[test]
+    fn test_parse_bare_regex() {
+        let query = Query::parse("description matches ^prod-").unwrap();
+        assert!(matches!(&compare(&query.expr).value, Value::Regex(r) if r.as_str() == "^prod-"));
+    }
+
+    #[test]
+    fn test_parse_field_aliases() {
+        for (alias, field) in [
+            ("type", Field::RunnerType),
+            ("runner_type", Field::RunnerType),
+            ("ip_address", Field::Ip),
+            ("tag", Field::Tags),
+            ("arch", Field::Architecture),
+            ("is_shared", Field::Shared),
+        ] {
+            let query = Query::parse(&format!("{} = x", alias)).or_else(|_| {
+                // Boolean aliases need a boolean value
+                Query::parse(&format!("{} = true", alias))
+            });
+            assert_eq!(compare(&query.unwrap().expr).field, field, "{}", alias);
+        }
+    }
+
+    #[test]
+    fn test_source_is_trimmed_and_displayed() {
+        let query = Query::parse("  status = online ").unwrap();
+        assert_eq!(query.source(), "status = online");
+        assert_eq!(query.to_string(), "status = online");
+    }
+
+    #[test]
+    fn test_parse_duration_values() {
+        for (source, secs) in [
+            ("uncontacted > 3600", 3600.0),
+            ("uncontacted > 90m", 5400.0),
+            ("uncontacted >= 2h", 7200.0),
+            ("uncontacted < 1d", 86400.0),
+        ] {
+            let query = Query::parse(source).unwrap();
+            assert!(matches!(compare(&query.expr).value, Value::Number(n) if n == secs));
+        }
+        assert!(parse_err("uncontacted > soon")
+            .message
+            .contains("invalid duration"));
+        assert!(parse_err("uncontacted contains 1h")
+            .message
+            .contains("not supported"));
+    }
+
+    #[test]
+    fn test_error_unknown_field() {
+        let err = parse_err("colour = red");
+        assert_eq!(err.message, "unknown field 'colour'");
+        assert_eq!(err.offset, 0);
+    }
+
+    #[test]
+    fn test_error_missing_operator() {
+        let err = parse_err("status online");
+        assert!(err.message.contains("expected an operator"));
+        assert_eq!(err.offset, 7);
+    }
+
+    #[test]
+    fn test_error_missing_value() {
+        let err = parse_err("status =");
+        assert_eq!(err.message, "expected a value");
+        assert_eq!(err.offset, 8);
+    }
+
+    #[test]
+    fn test_error_unbalanced_parentheses() {
+        assert_eq!(parse_err("(paused").message, "expected ')'");
+        assert_eq!(parse_err("paused)").message, "unexpected ')'");
+    }
+
+    #[test]
+    fn test_error_dangling_connective() {
+        let err = parse_err("paused and");
+        assert!(err.message.contains("expected a field name"));
+        assert_eq!(err.offset, 10);
+    }
+
+    #[test]
+    fn test_error_empty_expression() {
+        assert!(parse_err("").message.contains("expected a field name"));
+    }
+
+    #[test]
+    fn test_error_unterminated_string() {
+        let err = parse_err("description = \"oops");
+        assert_eq!(err.message, "unterminated string");
+        assert_eq!(err.offset, 14);
+    }
+
+    #[test]
+    fn test_error_invalid_regex() {
+        let err = parse_err("description ~ '('");
+        assert!(err.message.starts_with("invalid regex"));
+        assert_eq!(err.offset, 14);
+    }
+
+    #[test]
+    fn test_error_invalid_number() {
+        assert!(parse_err("managers > many")
+            .message
+            .starts_with("invalid number"));
+    }
+
+    #[test]
+    fn test_error_invalid_version() {
+        assert!(parse_err("version < latest")
+            .message
+            .starts_with("invalid version"));
+        assert!(parse_err("version satisfies '>>1'")
+            .message
+            .starts_with("invalid version requirement"));
+    }
+
+    #[test]
+    fn test_error_invalid_bool() {
+        assert!(parse_err("paused = maybe")
+            .message
+            .starts_with("invalid boolean"));
+    }
+
+    #[test]
+    fn test_error_operator_not_supported_for_field() {
+        let err = parse_err("paused > true");
+        assert_eq!(
+            err.message,
+            "operator '>' is not supported for field 'paused'"
+        );
+        assert_eq!(err.offset, 7);
+
+        assert!(parse_err("tags < a").message.contains("not supported"));
+        assert!(parse_err("id ~ 1").message.contains("not supported"));
+        assert!(parse_err("status satisfies ^1")
+            .message
+            .contains("not supported"));
+    }
+
+    #[test]
+    fn test_error_unexpected_symbol() {
+        let err = parse_err("id = 1 & paused");
+        assert_eq!(err.message, "unexpected '&'");
+        assert_eq!(err.offset, 7);
+    }
+
+    #[test]
+    fn test_error_display_is_one_based() {
+        let err = parse_err("colour = red");
+        assert_eq!(err.to_string(), "unknown field 'colour' at position 1");
+    }
+
+    #[test]
+    fn test_query_equality_uses_source() {
+        assert_eq!(
+            Query::parse("paused").unwrap(),
+            Query::parse(" paused ").unwrap()
+        );
+        assert_ne!(
+            Query::parse("paused").unwrap(),
+            Query::parse("active").unwrap()
+        );
+    }
+}
@@ -0,0 +1,473 @@
+use super::fake::FakeSource;
+use super::RunnerSource;
+use crate::models::job::{Job, JobProject};
+use crate::models::manager::RunnerManager;
+use crate::models::runner::{Runner, RunnerFilters, RunnerType};
+use crate::models::server::ServerVersion;
+use crate::models::status::RunnerStatus;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use std::sync::{Arc, Mutex};
+
+/// Seed used by `--demo` when `--demo-seed` is not given
+pub const DEFAULT_DEMO_SEED: u64 = 42;
+
+/// GitLab version the demo fleet reports for `GET /version`
+const DEMO_SERVER_VERSION: &str = "17.5.2-ee";
+
+/// Number of runners in the generated fleet
+pub const DEMO_FLEET_SIZE: u64 = 320;
+
+const ENVIRONMENTS: &[&str] = &["prod", "staging", "dev"];
+const TEAMS: &[&str] = &["alm", "payments", "search", "infra", "mobile"];
+const CAPABILITIES: &[&str] = &["docker", "shell", "gpu", "kubernetes"];
+const PLATFORMS: &[(&str, &str)] = &[
+    ("linux", "amd64"),
+    ("linux", "amd64"),
+    ("linux", "arm64"),
+    ("windows", "amd64"),
+    ("darwin", "arm64"),
+];
+const VERSIONS: &[&str] = &[
+    "16.10.0", "16.11.1", "17.0.2", "17.2.1", "17.3.0", "17.4.2", "17.5.0", "17.5.1",
+];
+const JOB_NAMES: &[&str] = &["build", "rspec", "lint", "deploy", "e2e", "package"];
+
+/// Small deterministic PRNG (SplitMix64) so a seed always yields the same fleet
+#[derive(Debug, Clone)]
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..n`
+    fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n.max(1)
+    }
+
+    /// True with probability `percent`/100
+    fn chance(&mut self, percent: u64) -> bool {
+        self.below(100) < percent
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+}
+
+/// Runner status as GitLab derives it from the runner's managers
+fn derived_status(managers: &[RunnerManager]) -> RunnerStatus {
+    let has = |status: RunnerStatus| managers.iter().any(|m| m.status == status);
+    if has(RunnerStatus::Online) {
+        RunnerStatus::Online
+    } else if has(RunnerStatus::Offline) {
+        RunnerStatus::Offline
+    } else if has(RunnerStatus::Stale) {
+        RunnerStatus::Stale
+    } else {
+        RunnerStatus::NeverContacted
+    }
+}
+
+/// Last-contact time consistent with a manager status
+fn contacted_for(
+    rng: &mut Rng,
+    status: &RunnerStatus,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let ago = match status {
+        RunnerStatus::Online => Duration::seconds(rng.below(90) as i64),
+        RunnerStatus::Offline => Duration::minutes(5 + rng.below(600) as i64),
+        RunnerStatus::Stale => Duration::days(90 + rng.below(120) as i64),
+        RunnerStatus::NeverContacted | RunnerStatus::Unknown(_) => return None,
+    };
+    Some(now - ago)
+}
+
+fn random_status(rng: &mut Rng) -> RunnerStatus {
+    match rng.below(100) {
+        0..=79 => RunnerStatus::Online,
+        80..=89 => RunnerStatus::Offline,
+        90..=94 => RunnerStatus::Stale,
+        _ => RunnerStatus::NeverContacted,
+    }
+}
+
+struct Generator {
+    rng: Rng,
+    next_manager_id: u64,
+    now: DateTime<Utc>,
+}
+
+impl Generator {
+    fn manager(&mut self, platform: (&str, &str), version: &str, age_days: i64) -> RunnerManager {
+        let id = self.next_manager_id;
+        self.next_manager_id += 1;
+        let status = random_status(&mut self.rng);
+        RunnerManager {
+            id,
+            system_id: format!("r_{:012x}", self.rng.next_u64() & 0xFFFF_FFFF_FFFF),
+            created_at: self.now
+                - Duration::days(age_days)
+                - Duration::minutes(self.rng.below(1440) as i64),
+            contacted_at: contacted_for(&mut self.rng, &status, self.now),
+            ip_address: Some(format!(
+                "10.{}.{}.{}",
+                self.rng.below(8),
+                self.rng.below(256),
+                1 + self.rng.below(254)
+            )),
+            status,
+            version: Some(version.to_string()),
+            revision: Some(format!("{:08x}", self.rng.next_u64() as u32)),
+            platform: Some(platform.0.to_string()),
+            architecture: Some(platform.1.to_string()),
+        }
+    }
+
+    fn runner(&mut self, id: u64) -> Runner {
+        let env = *self.rng.pick(ENVIRONMENTS);
+        let team = *self.rng.pick(TEAMS);
+        let capability = *self.rng.pick(CAPABILITIES);
+        let platform = *self.rng.pick(PLATFORMS);
+        let version = *self.rng.pick(VERSIONS);
+
+        let runner_type = match self.rng.below(100) {
+            0..=59 => RunnerType::GroupType,
+            60..=84 => RunnerType::ProjectType,
+            _ => RunnerType::InstanceType,
+        };
+
+        // Most runners have one manager; some are mid-rotation, a few have none
+        let manager_count = match self.rng.below(100) {
+            0..=4 => 0,
+            5..=79 => 1,
+            80..=94 => 2,
+            _ => 3,
+        };
+        let mut managers: Vec<RunnerManager> = (0..manager_count)
+            .map(|i| {
+                // Newer managers in a rotation run the newest version
+                let version = if i + 1 == manager_count && manager_count > 1 {
+                    VERSIONS[VERSIONS.len() - 1]
+                } else {
+                    version
+                };
+                let age = 30 * (manager_count - i) as i64;
+                self.manager(platform, version, age)
+            })
+            .collect();
+        if managers.len() > 1 {
+            // Rotations usually retire the old manager
+            managers[0].status = RunnerStatus::Offline;
+            managers[0].contacted_at =
+                contacted_for(&mut self.rng, &RunnerStatus::Offline, self.now);
+        }
+
+        let mut tag_list = vec![env.to_string(), team.to_string(), platform.0.to_string()];
+        tag_list.push(capability.to_string());
+        if platform.1 == "arm64" {
+            tag_list.push("arm64".to_string());
+        }
+
+        // Most shared runners also take untagged jobs; team runners don't
+        let run_untagged = runner_type == RunnerType::InstanceType && self.rng.chance(60);
+
+        Runner {
+            id,
+            is_shared: runner_type == RunnerType::InstanceType,
+            runner_type,
+            active: true,
+            paused: self.rng.chance(4),
+            description: Some(format!("{}-{}-{}-{:03}", team, env, platform.0, id)),
+            created_at: Some(self.now - Duration::days(60 + self.rng.below(700) as i64)),
+            ip_address: managers.last().and_then(|m| m.ip_address.clone()),
+            status: derived_status(&managers),
+            version: managers.last().and_then(|m| m.version.clone()),
+            revision: managers.last().and_then(|m| m.revision.clone()),
+            tag_list,
+            run_untagged: Some(run_untagged),
+            managers,
+        }
+    }
+
+    fn jobs(&mut self, runner: &Runner) -> Vec<Job> {
+        let count = if runner.status.is_online() {
+            1 + self.rng.below(5)
+        } else {
+            0
+        };
+        (0..count)
+            .map(|i| {
+                let created_at = self.now - Duration::minutes((count - i) as i64 * 7);
+                let running = i + 1 == count && self.rng.chance(50);
+                let duration = 30.0 + self.rng.below(600) as f64;
+                Job {
+                    id: runner.id * 1000 + i,
+                    name: self.rng.pick(JOB_NAMES).to_string(),
+                    stage: Some("test".to_string()),
+                    status: if running { "running" } else { "success" }.to_string(),
+                    git_ref: Some("main".to_string()),
+                    created_at,
+                    started_at: Some(created_at + Duration::seconds(5)),
+                    finished_at: (!running)
+                        .then(|| created_at + Duration::seconds(5 + duration as i64)),
+                    duration: Some(duration),
+                    web_url: None,
+                    project: Some(JobProject {
+                        id: 100 + runner.id % 17,
+                        path_with_namespace: format!("demo/project-{}", runner.id % 17),
+                    }),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Generate the demo fleet for `seed`; the same seed always yields the same
+/// runners, managers, tags and versions (timestamps are relative to `now`)
+pub fn generate_fleet(seed: u64, now: DateTime<Utc>) -> FakeSource {
+    let mut generator = Generator {
+        rng: Rng(seed),
+        next_manager_id: 50_000,
+        now,
+    };
+    let runners: Vec<Runner> = (1..=DEMO_FLEET_SIZE)
+        .map(|i| generator.runner(1000 + i))
+        .collect();
+    let mut fleet = FakeSource::new(Vec::new()).with_server_version(DEMO_SERVER_VERSION);
+    for runner in &runners {
+        let jobs = generator.jobs(runner);
+        fleet = fleet.with_jobs(runner.id, jobs);
+    }
+    *fleet.runners_mut() = runners;
+    fleet
+}
+
+struct DemoState {
+    fleet: Arc<FakeSource>,
+    rng: Rng,
+    next_manager_id: u64,
+    polls: u64,
+}
+
+/// Simulate one poll interval of fleet activity: managers drop off and come
+/// back, rotations start and finish, and online managers check in
+fn churn(runners: &mut [Runner], rng: &mut Rng, next_manager_id: &mut u64, now: DateTime<Utc>) {
+    for runner in runners.iter_mut() {
+        for manager in runner.managers.iter_mut() {
+            if rng.chance(3) {
+                manager.status = match manager.status {
+                    RunnerStatus::Online => RunnerStatus::Offline,
+                    _ => RunnerStatus::Online,
+                };
+            }
+            if manager.status.is_online() {
+                manager.contacted_at = Some(now - Duration::seconds(rng.below(30) as i64));
+            }
+        }
+
+        if runner.managers.len() == 1 && rng.chance(1) {
+            // Start a rotation: a new manager registers on the latest version
+            let old = &runner.managers[0];
+            let manager = RunnerManager {
+                id: *next_manager_id,
+                system_id: format!("r_{:012x}", rng.next_u64() & 0xFFFF_FFFF_FFFF),
+                created_at: now,
+                contacted_at: Some(now),
+                status: RunnerStatus::Online,
+                version: Some(VERSIONS[VERSIONS.len() - 1].to_string()),
+                ..old.clone()
+            };
+            *next_manager_id += 1;
+            runner.managers.push(manager);
+        } else if runner.managers.len() > 1 && rng.chance(10) {
+            // Finish a rotation: the oldest manager is removed
+            if let Some(oldest) = runner
+                .managers
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, m)| m.created_at)
+                .map(|(i, _)| i)
+            {
+                runner.managers.remove(oldest);
+            }
+        }
+
+        runner.status = derived_status(&runner.managers);
+        runner.version = runner
+            .managers
+            .iter()
+            .max_by_key(|m| m.created_at)
+            .and_then(|m| m.version.clone());
+    }
+}
+
+/// Offline runner source serving a generated fleet that changes between polls
+pub struct DemoSource {
+    state: Mutex<DemoState>,
+}
+
+impl DemoSource {
+    pub fn new(seed: u64) -> Self {
+        let fleet = generate_fleet(seed, Utc::now());
+        Self {
+            state: Mutex::new(DemoState {
+                fleet: Arc::new(fleet),
+                rng: Rng(seed ^ 0xD3E0),
+                next_manager_id: 90_000,
+                polls: 0,
+            }),
+        }
+    }
+
+    /// Current fleet snapshot, advancing the simulation when a new poll starts
+    fn snapshot(&self, new_poll: bool) -> Arc<FakeSource> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if new_poll {
+            state.polls += 1;
+            // The first poll sees the fleet exactly as generated
+            if state.polls > 1 {
+                let DemoState {
+                    fleet,
+                    rng,
+                    next_manager_id,
+                    ..
+                } = &mut *state;
+                let mut next = (**fleet).clone();
+                churn(next.runners_mut(), rng, next_manager_id, Utc::now());
+                *fleet = Arc::new(next);
+            }
+        }
+        Arc::clone(&state.fleet)
+    }
+}
+
+#[async_trait]
+impl RunnerSource for DemoSource {
+    async fn fetch_runners(
+        &self,
+        filters: &RunnerFilters,
+        page: u32,
+        per_page: u32,
+    ) -> Result<Vec<Runner>> {
+        // Every conductor query starts at page 1, so treat that as a new poll
+        let fleet = self.snapshot(page == 1);
+        fleet.fetch_runners(filters, page, per_page).await
+    }
+
+    async fn fetch_runner_detail(&self, runner_id: u64) -> Result<Runner> {
+        self.snapshot(false).fetch_runner_detail(runner_id).await
+    }
+
+    async fn fetch_runner_managers(&self, runner_id: u64) -> Result<Vec<RunnerManager>> {
+        self.snapshot(false).fetch_runner_managers(runner_id).await
+    }
+
+    async fn fetch_runner_jobs(&self, runner_id: u64, per_page: u32) -> Result<Vec<Job>> {
+        self.snapshot(false)
+            .fetch_runner_jobs(runner_id, per_page)
+            .await
+    }
+
+    async fn fetch_server_version(&self) -> Result<ServerVersion> {
+        self.snapshot(false).fetch_server_version().await
+    }
+
+    async fn set_runner_paused(&self, runner_id: u64, paused: bool) -> Result<Runner> {
+        // The snapshot is shared with the simulation, so later polls keep it
+        self.snapshot(false)
+            .set_runner_paused(runner_id, paused)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conductor::Conductor;
+
+    fn fixed_now() -> DateTime<Utc> {
+        "2024-06-01T12:00:00Z".parse().unwrap()
+    }
+
+    #[test]
+    fn test_same_seed_same_fleet() {
+        let mut a = generate_fleet(7, fixed_now());
+        let mut b = generate_fleet(7, fixed_now());
+        assert_eq!(a.runners_mut(), b.runners_mut());
+
+        let mut c = generate_fleet(8, fixed_now());
+        assert_ne!(a.runners_mut(), c.runners_mut());
+    }
+
+    #[test]
+    fn test_fleet_is_varied() {
+        let mut fleet = generate_fleet(42, fixed_now());
+        let runners = fleet.runners_mut();
+        assert_eq!(runners.len() as u64, DEMO_FLEET_SIZE);
+
+        let count = |f: &dyn Fn(&Runner) -> bool| runners.iter().filter(|r| f(r)).count();
+        assert!(count(&|r| r.managers.is_empty()) > 0);
+        assert!(count(&|r| r.managers.len() > 1) > 0);
+        assert!(count(&|r| r.status == RunnerStatus::Online) > runners.len() / 2);
+        assert!(count(&|r| r.status == RunnerStatus::Offline) > 0);
+        assert!(count(&|r| r.status == RunnerStatus::Stale) > 0);
+        assert!(count(&|r| r.runner_type == RunnerType::InstanceType) > 0);
+        assert!(count(&|r| r.tag_list.contains(&"windows".to_string())) > 0);
+        assert!(count(&|r| r.paused) > 0);
+    }
+
+    #[test]
+    fn test_runner_status_matches_managers() {
+        let mut fleet = generate_fleet(42, fixed_now());
+        for runner in fleet.runners_mut().iter() {
+            assert_eq!(runner.status, derived_status(&runner.managers));
+        }
+    }
+
+    #[test]
+    fn test_churn_changes_fleet() {
+        let mut fleet = generate_fleet(42, fixed_now());
+        let before = fleet.runners_mut().clone();
+        let mut rng = Rng(1);
+        let mut next_id = 1_000_000;
+        churn(fleet.runners_mut(), &mut rng, &mut next_id, fixed_now());
+        let after = fleet.runners_mut();
+
+        assert_ne!(&before, after);
+        assert_eq!(before.len(), after.len());
+        for runner in after.iter() {
+            assert_eq!(runner.status, derived_status(&runner.managers));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_demo_source_through_conductor() {
+        let conductor = Conductor::new(DemoSource::new(42));
+
+        let runners = conductor
+            .fetch_runners(RunnerFilters::default())
+            .await
+            .unwrap();
+        assert_eq!(runners.len() as u64, DEMO_FLEET_SIZE);
+        assert!(runners.iter().all(|r| !r.tag_list.is_empty()));
+
+        let filters = RunnerFilters {
+            tag_list: Some(vec!["prod".to_string()]),
+            ..Default::default()
+        };
+        let prod = conductor.fetch_runners(filters).await.unwrap();
+        assert!(!prod.is_empty());
+        assert!(prod.len() < runners.len());
+
+        assert!(runners.iter().any(|r| r.managers.len() > 1));
+    }
+}
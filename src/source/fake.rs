@@ -0,0 +1,280 @@
+use super::RunnerSource;
+use crate::models::job::Job;
+use crate::models::manager::RunnerManager;
+use crate::models::runner::{Runner, RunnerFilters};
+use crate::models::server::ServerVersion;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, MutexGuard};
+
+/// In-memory runner source that behaves like the REST API: server-side
+/// filters and pagination on the list call, tags only on the detail call and
+/// managers only on the managers call.
+#[derive(Debug, Default)]
+pub struct FakeSource {
+    /// Behind a lock so pausing a runner works through `&self`
+    runners: Mutex<Vec<Runner>>,
+    jobs: HashMap<u64, Vec<Job>>,
+    failing_details: HashSet<u64>,
+    server_version: Option<ServerVersion>,
+}
+
+impl FakeSource {
+    /// A fleet of fully-populated runners (tags and managers included)
+    pub fn new(runners: Vec<Runner>) -> Self {
+        Self {
+            runners: Mutex::new(runners),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_jobs(mut self, runner_id: u64, jobs: Vec<Job>) -> Self {
+        self.jobs.insert(runner_id, jobs);
+        self
+    }
+
+    /// Answer `GET /version`; without one the call fails like an old GitLab
+    pub fn with_server_version(mut self, version: &str) -> Self {
+        self.server_version = Some(ServerVersion {
+            version: version.to_string(),
+            revision: String::new(),
+        });
+        self
+    }
+
+    /// Make the detail call for `runner_id` fail, as a flaky API would
+    #[cfg(test)]
+    pub fn with_failing_detail(mut self, runner_id: u64) -> Self {
+        self.failing_details.insert(runner_id);
+        self
+    }
+
+    /// The full fleet, for sources that mutate it between polls
+    pub fn runners_mut(&mut self) -> &mut Vec<Runner> {
+        self.runners.get_mut().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn runners(&self) -> MutexGuard<'_, Vec<Runner>> {
+        self.runners.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn find(&self, runner_id: u64) -> Result<Runner> {
+        self.runners()
+            .iter()
+            .find(|r| r.id == runner_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("runner {} not found", runner_id))
+    }
+}
+
+impl Clone for FakeSource {
+    fn clone(&self) -> Self {
+        Self {
+            runners: Mutex::new(self.runners().clone()),
+            jobs: self.jobs.clone(),
+            failing_details: self.failing_details.clone(),
+            server_version: self.server_version.clone(),
+        }
+    }
+}
+
+/// Server-side filters as GitLab applies them to `GET /runners/all`
+fn matches_filters(runner: &Runner, filters: &RunnerFilters) -> bool {
+    if let Some(status) = &filters.status {
+        if runner.status != *status {
+            return false;
+        }
+    }
+    if let Some(runner_type) = &filters.runner_type {
+        if runner.runner_type != *runner_type {
+            return false;
+        }
+    }
+    if let Some(paused) = filters.paused {
+        if runner.paused != paused {
+            return false;
+        }
+    }
+    if let Some(tags) = &filters.tag_list {
+        if !tags.iter().all(|t| runner.tag_list.contains(t)) {
+            return false;
+        }
+    }
+    if let Some(prefix) = &filters.version_prefix {
+        let versions = std::iter::once(runner.version.as_deref())
+            .chain(runner.managers.iter().map(|m| m.version.as_deref()));
+        if !versions.flatten().any(|v| v.starts_with(prefix.as_str())) {
+            return false;
+        }
+    }
+    true
+}
+
+#[async_trait]
+impl RunnerSource for FakeSource {
+    async fn fetch_runners(
+        &self,
+        filters: &RunnerFilters,
+        page: u32,
+        per_page: u32,
+    ) -> Result<Vec<Runner>> {
+        // `page` comes from the client when served by the mock server
+        let start = (page.saturating_sub(1) as usize).saturating_mul(per_page as usize);
+        Ok(self
+            .runners()
+            .iter()
+            .filter(|r| matches_filters(r, filters))
+            .skip(start)
+            .take(per_page as usize)
+            .map(|r| Runner {
+                tag_list: Vec::new(),
+                run_untagged: None,
+                managers: Vec::new(),
+                ..r.clone()
+            })
+            .collect())
+    }
+
+    async fn fetch_runner_detail(&self, runner_id: u64) -> Result<Runner> {
+        if self.failing_details.contains(&runner_id) {
+            return Err(anyhow!(
+                "500 Internal Server Error for runner {}",
+                runner_id
+            ));
+        }
+        let runner = self.find(runner_id)?;
+        Ok(Runner {
+            managers: Vec::new(),
+            ..runner
+        })
+    }
+
+    async fn fetch_runner_managers(&self, runner_id: u64) -> Result<Vec<RunnerManager>> {
+        // Like the API's 404, an unknown runner has no managers
+        Ok(self.find(runner_id).map(|r| r.managers).unwrap_or_default())
+    }
+
+    async fn fetch_runner_jobs(&self, runner_id: u64, per_page: u32) -> Result<Vec<Job>> {
+        self.find(runner_id)?;
+        let mut jobs = self.jobs.get(&runner_id).cloned().unwrap_or_default();
+        jobs.sort_by_key(|j| std::cmp::Reverse(j.id));
+        jobs.truncate(per_page as usize);
+        Ok(jobs)
+    }
+
+    async fn fetch_server_version(&self) -> Result<ServerVersion> {
+        self.server_version
+            .clone()
+            .ok_or_else(|| anyhow!("404 Not Found for GitLab version"))
+    }
+
+    async fn set_runner_paused(&self, runner_id: u64, paused: bool) -> Result<Runner> {
+        let mut runners = self.runners();
+        let runner = runners
+            .iter_mut()
+            .find(|r| r.id == runner_id)
+            .ok_or_else(|| anyhow!("runner {} not found", runner_id))?;
+        runner.paused = paused;
+        Ok(Runner {
+            managers: Vec::new(),
+            ..runner.clone()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::status::RunnerStatus;
+    use crate::models::test_support;
+
+    fn runner(id: u64, status: RunnerStatus, tags: &[&str]) -> Runner {
+        Runner {
+            status,
+            version: Some("17.5.0".to_string()),
+            tag_list: test_support::tags(tags),
+            ..test_support::runner(id)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_applies_filters_and_strips_tags() {
+        let source = FakeSource::new(vec![
+            runner(1, RunnerStatus::Online, &["alm", "prod"]),
+            runner(2, RunnerStatus::Offline, &["alm"]),
+            runner(3, RunnerStatus::Online, &["dev"]),
+        ]);
+        let filters = RunnerFilters {
+            tag_list: Some(vec!["alm".to_string()]),
+            status: Some(RunnerStatus::Online),
+            ..Default::default()
+        };
+
+        let runners = source.fetch_runners(&filters, 1, 100).await.unwrap();
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].id, 1);
+        assert!(runners[0].tag_list.is_empty());
+
+        let detail = source.fetch_runner_detail(1).await.unwrap();
+        assert_eq!(detail.tag_list, vec!["alm", "prod"]);
+    }
+
+    #[tokio::test]
+    async fn test_paused_runner_drops_out_of_the_active_filter() {
+        let source = FakeSource::new(vec![
+            runner(1, RunnerStatus::Online, &[]),
+            runner(2, RunnerStatus::Online, &[]),
+        ]);
+        let updated = source.set_runner_paused(2, true).await.unwrap();
+        assert!(updated.paused);
+
+        let active = RunnerFilters {
+            paused: Some(false),
+            ..Default::default()
+        };
+        let runners = source.fetch_runners(&active, 1, 100).await.unwrap();
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].id, 1);
+        assert!(source.set_runner_paused(9, true).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_list_paginates() {
+        let source = FakeSource::new(
+            (1..=5)
+                .map(|id| runner(id, RunnerStatus::Online, &[]))
+                .collect(),
+        );
+        let filters = RunnerFilters::default();
+
+        let ids = |runners: Vec<Runner>| runners.iter().map(|r| r.id).collect::<Vec<_>>();
+        assert_eq!(
+            ids(source.fetch_runners(&filters, 1, 2).await.unwrap()),
+            vec![1, 2]
+        );
+        assert_eq!(
+            ids(source.fetch_runners(&filters, 3, 2).await.unwrap()),
+            vec![5]
+        );
+        assert!(source
+            .fetch_runners(&filters, 4, 2)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(source
+            .fetch_runners(&filters, 50_000_000, 100)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failing_detail_and_unknown_runner() {
+        let source =
+            FakeSource::new(vec![runner(1, RunnerStatus::Online, &[])]).with_failing_detail(1);
+        assert!(source.fetch_runner_detail(1).await.is_err());
+        assert!(source.fetch_runner_detail(99).await.is_err());
+        assert!(source.fetch_runner_managers(99).await.unwrap().is_empty());
+    }
+}
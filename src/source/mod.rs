@@ -0,0 +1,40 @@
+pub mod demo;
+pub mod fake;
+
+use crate::models::job::Job;
+use crate::models::manager::RunnerManager;
+use crate::models::runner::{Runner, RunnerFilters};
+use crate::models::server::ServerVersion;
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// Where runner data comes from: the GitLab REST API, an in-memory fake, ...
+///
+/// Mirrors the REST endpoints the conductor needs: the list endpoint returns
+/// summaries (no tags or managers), which the conductor enriches with the
+/// detail and managers calls.
+#[async_trait]
+pub trait RunnerSource: Send + Sync {
+    /// One page of runners matching `filters` (`GET /runners/all`)
+    async fn fetch_runners(
+        &self,
+        filters: &RunnerFilters,
+        page: u32,
+        per_page: u32,
+    ) -> Result<Vec<Runner>>;
+
+    /// Full runner record including tags (`GET /runners/:id`)
+    async fn fetch_runner_detail(&self, runner_id: u64) -> Result<Runner>;
+
+    /// Managers registered under a runner (`GET /runners/:id/managers`)
+    async fn fetch_runner_managers(&self, runner_id: u64) -> Result<Vec<RunnerManager>>;
+
+    /// Most recent jobs picked up by a runner (`GET /runners/:id/jobs`)
+    async fn fetch_runner_jobs(&self, runner_id: u64, per_page: u32) -> Result<Vec<Job>>;
+
+    /// Version of the GitLab server itself (`GET /version`)
+    async fn fetch_server_version(&self) -> Result<ServerVersion>;
+
+    /// Pause or resume a runner, returning the updated record (`PUT /runners/:id`)
+    async fn set_runner_paused(&self, runner_id: u64, paused: bool) -> Result<Runner>;
+}
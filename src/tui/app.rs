@@ -1,13 +1,37 @@
-use crate::conductor::Conductor;
-use crate::config::AppConfig;
+use crate::alerts::{AlertEngine, AlertState};
+use crate::ci::{job_coverage, pipeline_jobs, required_tag_sets, JobCoverage};
+use crate::conductor::{Conductor, DEFAULT_FLAMES_THRESHOLD_SECS};
+use crate::config::{self, AppConfig, ConfigWatcher, Profile};
+use crate::models::coverage::{tag_coverage, TagSetCoverage};
+use crate::models::drift::DriftReport;
 use crate::models::manager::RunnerManager;
-use crate::models::runner::Runner;
-use crossterm::event::{KeyCode, KeyEvent};
+use crate::models::runner::{Runner, RunnerFilters};
+use crate::models::server::{CompatPolicy, Compatibility, ServerVersion};
+use crate::models::timeline::ManagerTracker;
+use crate::notify::Notifier;
+use crate::presets::Preset;
+use crate::query::Query;
+use crate::source::RunnerSource;
+use crate::tui::columns::{fit_columns, ColumnPicker, ColumnSpec, ColumnsConfig};
+use crate::tui::export::{self, ExportFormat};
+use crate::tui::filter_form::FilterForm;
+use crate::tui::keymap::{Action, Keymap};
+use crate::tui::mouse::ClickAreas;
+use crate::tui::palette::{Palette, PaletteAction, PaletteEntry, PaletteOutcome, RecentActions};
+use crate::tui::search::SearchState;
+use crate::tui::sort::{sort_rows, SortState};
+use crate::tui::table::{Column, TableRow};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::widgets::TableState;
+use serde::{Deserialize, Serialize};
 use std::fmt;
-use std::time::Instant;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Command {
     Fetch,
     Lights,
@@ -16,6 +40,9 @@ pub enum Command {
     Flames,
     Empty,
     Rotate,
+    Drift,
+    Coverage,
+    Pipeline,
 }
 
 impl Command {
@@ -27,9 +54,60 @@ impl Command {
         Command::Flames,
         Command::Empty,
         Command::Rotate,
+        Command::Drift,
+        Command::Coverage,
+        Command::Pipeline,
     ];
 }
 
+impl Command {
+    /// One-line summary shown in the command palette
+    pub fn description(self) -> &'static str {
+        match self {
+            Command::Fetch => "Fetch GitLab Runner details",
+            Command::Lights => "Check if runners are online (health check)",
+            Command::Switch => "List runners with offline managers",
+            Command::Workers => "Show runner managers (flattened view)",
+            Command::Flames => "List runners not contacted recently",
+            Command::Empty => "List runners with no managers",
+            Command::Rotate => "Detect runners with multiple managers (rotation)",
+            Command::Drift => "Compare runner versions against a target version",
+            Command::Coverage => "Check required tag sets have an online runner",
+            Command::Pipeline => "List .gitlab-ci.yml jobs no online runner would pick up",
+        }
+    }
+
+    /// Whether the command fetches every runner matching the filters,
+    /// rather than a subset such as offline runners
+    pub fn fetches_fleet(self) -> bool {
+        !matches!(self, Command::Switch | Command::Flames | Command::Empty)
+    }
+
+    /// The results view the command fills
+    pub fn view(self) -> ResultsViewType {
+        match self {
+            Command::Workers => ResultsViewType::Workers,
+            Command::Lights => ResultsViewType::HealthCheck,
+            Command::Rotate => ResultsViewType::Rotation,
+            Command::Drift => ResultsViewType::Drift,
+            Command::Coverage => ResultsViewType::Coverage,
+            Command::Pipeline => ResultsViewType::Pipeline,
+            Command::Fetch | Command::Switch | Command::Flames | Command::Empty => {
+                ResultsViewType::Runners
+            }
+        }
+    }
+
+    /// Apply a client-side query; the workers view filters individual managers
+    pub fn apply_query(self, query: Option<&Query>, runners: Vec<Runner>) -> Vec<Runner> {
+        match (query, self) {
+            (Some(q), Command::Workers) => q.filter_managers(runners),
+            (Some(q), _) => q.filter_runners(runners),
+            (None, _) => runners,
+        }
+    }
+}
+
 impl fmt::Display for Command {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -40,6 +118,9 @@ impl fmt::Display for Command {
             Command::Flames => write!(f, "flames"),
             Command::Empty => write!(f, "empty"),
             Command::Rotate => write!(f, "rotate"),
+            Command::Drift => write!(f, "drift"),
+            Command::Coverage => write!(f, "coverage"),
+            Command::Pipeline => write!(f, "pipeline"),
         }
     }
 }
@@ -50,6 +131,15 @@ pub enum AppMode {
     CommandSelection,
     FilterInput,
     ResultsView,
+    ColumnPicker,
+    /// Manager timeline of the selected rotation row
+    Timeline,
+    /// Saved presets, opened from command selection
+    PresetPicker,
+    /// Naming the current query to save it as a preset
+    SavePreset,
+    /// Fuzzy search over commands, presets and actions
+    Palette,
     Help,
 }
 
@@ -60,6 +150,9 @@ pub enum ResultsViewType {
     Workers,
     HealthCheck,
     Rotation,
+    Drift,
+    Coverage,
+    Pipeline,
 }
 
 /// Flattened row for workers view: runner info + manager info
@@ -68,6 +161,54 @@ pub struct ManagerRow {
     pub runner_id: u64,
     pub runner_tags: Vec<String>,
     pub manager: RunnerManager,
+    /// Compared against the GitLab server, when its version is known
+    pub compatibility: Compatibility,
+}
+
+impl ManagerRow {
+    /// Flatten runners into one row per manager
+    pub fn from_runners(runners: &[Runner], policy: Option<&CompatPolicy>) -> Vec<ManagerRow> {
+        runners
+            .iter()
+            .flat_map(|r| {
+                r.managers.iter().map(move |m| ManagerRow {
+                    runner_id: r.id,
+                    runner_tags: r.tag_list.clone(),
+                    manager: m.clone(),
+                    compatibility: policy
+                        .map_or(Compatibility::Unknown, |p| p.check(m.version.as_deref())),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Row for the rotation view: a runner and its recent manager flaps
+#[derive(Debug, Clone)]
+pub struct RotationRow {
+    pub runner: Runner,
+    /// Flaps within the configured window
+    pub flaps: usize,
+    pub flapping: bool,
+}
+
+impl RotationRow {
+    /// Rows for runners mid-rotation or flapping
+    pub fn from_runners(
+        runners: Vec<Runner>,
+        tracker: &ManagerTracker,
+        now: DateTime<Utc>,
+    ) -> Vec<RotationRow> {
+        tracker
+            .rotating(runners, now)
+            .into_iter()
+            .map(|runner| RotationRow {
+                flaps: tracker.flaps(runner.id, now),
+                flapping: tracker.is_flapping(runner.id, now),
+                runner,
+            })
+            .collect()
+    }
 }
 
 /// Health check summary for lights command
@@ -78,6 +219,18 @@ pub struct HealthSummary {
 }
 
 impl HealthSummary {
+    /// A runner counts as online when at least one of its managers is online
+    pub fn from_runners(runners: &[Runner]) -> Self {
+        let online_count = runners
+            .iter()
+            .filter(|r| r.managers.iter().any(|m| m.status.is_online()))
+            .count();
+        Self {
+            online_count,
+            total_count: runners.len(),
+        }
+    }
+
     pub fn percentage(&self) -> f64 {
         if self.total_count == 0 {
             0.0
@@ -91,8 +244,31 @@ impl HealthSummary {
     }
 }
 
-pub struct App {
-    pub conductor: Conductor,
+/// How long a toast stays on screen
+const TOAST_DURATION: Duration = Duration::from_secs(8);
+
+/// Wait before asking for the GitLab version again after a failure
+const SERVER_VERSION_RETRY: Duration = Duration::from_secs(300);
+
+/// A short-lived message over the bottom of the screen
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub message: String,
+    pub is_error: bool,
+    pub shown_at: Instant,
+}
+
+/// Builds the source for a `[profiles]` entry
+pub type ProfileSwitcher<S> = Box<dyn Fn(&Profile) -> Result<S>>;
+
+pub struct App<S> {
+    pub conductor: Conductor<S>,
+    /// Unset where there is nothing to switch to, as with `--demo`
+    pub profile_switcher: Option<ProfileSwitcher<S>>,
+    /// `[profiles]` entry in use; `None` until the first switch
+    pub profile: Option<String>,
+    /// Where exports are written
+    pub export_dir: PathBuf,
     pub config: AppConfig,
     pub mode: AppMode,
     pub should_quit: bool,
@@ -100,12 +276,55 @@ pub struct App {
     pub manager_rows: Vec<ManagerRow>,
     pub results_view_type: ResultsViewType,
     pub health_summary: Option<HealthSummary>,
+    pub drift_report: Option<DriftReport>,
+    pub coverage_rows: Vec<TagSetCoverage>,
+    pub job_rows: Vec<JobCoverage>,
+    pub rotation_rows: Vec<RotationRow>,
+    /// Manager history across rotate polls
+    pub manager_tracker: ManagerTracker,
+    /// Runner whose manager timeline is open
+    pub timeline_runner: Option<u64>,
+    /// Sends fleet changes between polls to the configured sinks
+    pub notifier: Option<Notifier>,
+    /// Configured alert rules and their current alerts
+    pub alerts: AlertEngine,
+    /// Whether the alerts panel is shown below the main content
+    pub show_alerts: bool,
+    /// Re-reads config.toml when it changes
+    pub config_watcher: Option<ConfigWatcher>,
+    /// `--config`, where presets are saved; the default locations when unset
+    pub config_path: Option<PathBuf>,
+    pub toast: Option<Toast>,
+    /// GitLab server version, fetched with the first search
+    pub server_version: Option<ServerVersion>,
+    /// After a failed version fetch, when to try again
+    pub server_version_retry_at: Option<Instant>,
 
     pub commands: &'static [Command],
     pub selected_command_index: usize,
+    pub selected_preset: usize,
+    /// Name typed for the preset being saved
+    pub preset_name: String,
+    pub palette: Option<Palette>,
+    /// Palette entries run this session, for recently-used ordering
+    pub recent_actions: RecentActions,
 
-    pub input_buffer: String,
+    pub filter_form: FilterForm,
+    /// Client-side query applied to every search until the filter is edited
+    pub active_query: Option<Query>,
     pub table_state: TableState,
+    pub search: SearchState,
+    pub sort: SortState,
+    /// Column layouts, seeded from config and edited with the column picker
+    pub columns: ColumnsConfig,
+    /// Key bindings from `[keys]`
+    pub keymap: Keymap,
+    /// Set by the renderer every frame
+    pub click_areas: ClickAreas,
+    /// Inner width of the results table when last drawn, which decides the
+    /// columns shown; every column until then
+    pub results_width: Option<u16>,
+    pub column_picker: Option<ColumnPicker>,
 
     // Loading and error state
     pub is_loading: bool,
@@ -114,16 +333,28 @@ pub struct App {
 
     // Polling state
     pub polling_active: bool,
+    /// Whether the terminal window has focus, as reported by the terminal
+    pub focused: bool,
     pub poll_started_at: Option<Instant>,
+    /// When polling was last held for lost focus; the timeout clock stops
+    /// until it resumes
+    pub poll_paused_at: Option<Instant>,
     pub last_poll_at: Option<Instant>,
 }
 
 const SPINNER_FRAMES: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
 
-impl App {
-    pub fn new(conductor: Conductor, config: AppConfig) -> Self {
+impl<S: RunnerSource> App<S> {
+    pub fn new(conductor: Conductor<S>, config: AppConfig) -> Self {
+        let columns = config.columns.clone();
+        // Checked when the config was loaded
+        let keymap = config.keys.keymap().unwrap_or_default();
+        let manager_tracker = ManagerTracker::new(config.flap_policy());
         Self {
             conductor,
+            profile_switcher: None,
+            profile: None,
+            export_dir: PathBuf::from("."),
             config,
             mode: AppMode::default(),
             should_quit: false,
@@ -131,19 +362,136 @@ impl App {
             manager_rows: Vec::new(),
             results_view_type: ResultsViewType::default(),
             health_summary: None,
+            drift_report: None,
+            coverage_rows: Vec::new(),
+            job_rows: Vec::new(),
+            rotation_rows: Vec::new(),
+            manager_tracker,
+            timeline_runner: None,
+            notifier: None,
+            alerts: AlertEngine::default(),
+            show_alerts: false,
+            config_watcher: None,
+            config_path: None,
+            toast: None,
+            server_version: None,
+            server_version_retry_at: None,
             commands: Command::ALL,
             selected_command_index: 0,
-            input_buffer: String::new(),
+            selected_preset: 0,
+            preset_name: String::new(),
+            palette: None,
+            recent_actions: RecentActions::default(),
+            filter_form: FilterForm::default(),
+            active_query: None,
             table_state: TableState::default(),
+            search: SearchState::default(),
+            sort: SortState::default(),
+            columns,
+            keymap,
+            click_areas: ClickAreas::default(),
+            results_width: None,
+            column_picker: None,
             is_loading: false,
             error_message: None,
             spinner_frame: 0,
             polling_active: false,
+            focused: true,
             poll_started_at: None,
+            poll_paused_at: None,
             last_poll_at: None,
         }
     }
 
+    pub fn with_notifier(mut self, notifier: Notifier) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    pub fn with_alerts(mut self, alerts: AlertEngine) -> Self {
+        self.alerts = alerts;
+        self
+    }
+
+    pub fn with_config_watcher(mut self, watcher: ConfigWatcher) -> Self {
+        self.config_watcher = Some(watcher);
+        self
+    }
+
+    pub fn with_config_path(mut self, path: Option<PathBuf>) -> Self {
+        self.config_path = path;
+        self
+    }
+
+    pub fn with_profile_switcher(mut self, switcher: ProfileSwitcher<S>) -> Self {
+        self.profile_switcher = Some(switcher);
+        self
+    }
+
+    pub fn show_toast(&mut self, message: String, is_error: bool) {
+        self.toast = Some(Toast {
+            message,
+            is_error,
+            shown_at: Instant::now(),
+        });
+    }
+
+    /// Switch to a new config without restarting: polling picks up the new
+    /// interval and timeout, the notifier, alert rules, flap limits and key
+    /// bindings are swapped in place, and a changed `[columns]` table
+    /// replaces the current layouts. Returns what changed.
+    pub fn apply_config(&mut self, config: AppConfig) -> anyhow::Result<Vec<String>> {
+        let changes = self.config.changes(&config);
+        if changes.is_empty() {
+            return Ok(changes);
+        }
+        let keymap = config.keys.keymap()?;
+        self.alerts.reload(&config.alerts)?;
+        if let Some(notifier) = self.notifier.as_mut() {
+            notifier.reconfigure(&config.notify)?;
+        }
+        self.manager_tracker.set_policy(config.flap_policy());
+        self.keymap = keymap;
+        let columns_changed = config.columns != self.config.columns;
+        self.config = config;
+        if columns_changed {
+            self.columns = self.config.columns.clone();
+            self.sort = SortState::default();
+            self.resort();
+        }
+        Ok(changes)
+    }
+
+    /// Apply an edited config file and say what happened; results on screen
+    /// are refreshed so thresholds and targets take effect straight away
+    async fn reload_config(&mut self, path: &Path, config: anyhow::Result<AppConfig>) {
+        let name = path.display();
+        let applied = config.and_then(|config| {
+            let restart = config.gitlab_host != self.config.gitlab_host
+                || config.gitlab_token != self.config.gitlab_token;
+            self.apply_config(config).map(|changes| (changes, restart))
+        });
+        match applied {
+            Ok((changes, _)) if changes.is_empty() => {}
+            Ok((changes, restart)) => {
+                let mut message = format!("Reloaded {}: {}", name, changes.join(", "));
+                if restart {
+                    message.push_str(" (restart to connect with the new host or token)");
+                }
+                tracing::info!(%message, "Config reloaded");
+                self.show_toast(message, false);
+                if self.mode == AppMode::ResultsView && !self.is_loading {
+                    self.execute_search().await;
+                }
+            }
+            Err(e) => {
+                let message = format!("Kept the running config: {:#}", e);
+                tracing::warn!(%message, "Config rejected");
+                self.show_toast(message, true);
+            }
+        }
+    }
+
     pub fn spinner_char(&self) -> char {
         SPINNER_FRAMES[self.spinner_frame % SPINNER_FRAMES.len()]
     }
@@ -176,7 +524,395 @@ impl App {
 
     pub fn select_command(&mut self) {
         self.mode = AppMode::FilterInput;
-        self.input_buffer.clear();
+        self.filter_form.clear();
+        self.active_query = None;
+        self.search.clear();
+        self.sort = SortState::default();
+    }
+
+    pub fn open_preset_picker(&mut self) {
+        if self.config.presets.is_empty() {
+            self.show_toast(
+                format!(
+                    "No presets yet: run a query and press {} in the results to save one",
+                    self.keymap.hint(Action::SavePreset)
+                ),
+                false,
+            );
+            return;
+        }
+        self.selected_preset = self.selected_preset.min(self.config.presets.len() - 1);
+        self.mode = AppMode::PresetPicker;
+    }
+
+    async fn handle_preset_picker_key(&mut self, key: KeyEvent) {
+        let count = self.config.presets.len();
+        match key.code {
+            KeyCode::Enter => {
+                let preset = self.config.presets[self.selected_preset].clone();
+                self.run_preset(&preset).await;
+            }
+            KeyCode::Esc => self.mode = AppMode::CommandSelection,
+            _ if self.keymap.is(Action::Presets, &key) => {
+                self.mode = AppMode::CommandSelection;
+            }
+            _ if self.keymap.is(Action::Up, &key) => {
+                self.selected_preset = (self.selected_preset + count - 1) % count;
+            }
+            _ if self.keymap.is(Action::Down, &key) => {
+                self.selected_preset = (self.selected_preset + 1) % count;
+            }
+            _ => {}
+        }
+    }
+
+    /// Fill the filter form, sort and columns from `preset` and run it
+    pub async fn run_preset(&mut self, preset: &Preset) {
+        let Some(index) = self.commands.iter().position(|c| *c == preset.command) else {
+            return;
+        };
+        self.selected_command_index = index;
+        self.filter_form = FilterForm::from_preset(preset);
+        self.active_query = preset.query();
+        self.search.clear();
+
+        let view = preset.command.view();
+        if let Some(specs) = &preset.columns {
+            self.columns.set(view, specs.clone());
+        }
+        self.sort = SortState::default();
+        let columns = self.columns.for_view(view);
+        if let Some(index) = preset
+            .sort_column()
+            .and_then(|column| columns.iter().position(|spec| spec.column == column))
+        {
+            self.sort = SortState {
+                column: Some(index),
+                descending: preset.descending,
+                cursor: index,
+            };
+        }
+        self.execute_search().await;
+    }
+
+    pub fn start_save_preset(&mut self) {
+        self.preset_name.clear();
+        self.mode = AppMode::SavePreset;
+    }
+
+    fn handle_save_preset_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Enter => {
+                let name = self.preset_name.trim().to_string();
+                if !name.is_empty() {
+                    self.save_preset(&name);
+                    self.mode = AppMode::ResultsView;
+                }
+            }
+            KeyCode::Esc => self.mode = AppMode::ResultsView,
+            KeyCode::Backspace => {
+                self.preset_name.pop();
+            }
+            KeyCode::Char(c) => self.preset_name.push(c),
+            _ => {}
+        }
+    }
+
+    /// The current command, filters, sort and (when changed from the
+    /// config) columns as a preset
+    pub fn current_preset(&self, name: &str) -> Preset {
+        let command = self.commands[self.selected_command_index];
+        let mut preset = self.filter_form.to_preset(name, command);
+        let columns = self.columns();
+        if let Some(spec) = self.sort.column.and_then(|index| columns.get(index)) {
+            preset.sort = Some(spec.column.name().to_string());
+            preset.descending = self.sort.descending;
+        }
+        if columns != self.config.columns.for_view(self.results_view_type) {
+            preset.columns = Some(columns);
+        }
+        preset
+    }
+
+    /// Everything the palette can run from the current mode; result
+    /// actions are only offered over results
+    fn palette_entries(&self) -> Vec<PaletteEntry> {
+        let mut entries: Vec<PaletteEntry> = self
+            .commands
+            .iter()
+            .map(|command| {
+                PaletteEntry::new(
+                    PaletteAction::Command(*command),
+                    command.to_string(),
+                    command.description(),
+                )
+            })
+            .collect();
+        entries.extend(self.config.presets.iter().map(|preset| {
+            PaletteEntry::new(
+                PaletteAction::Preset(preset.name.clone()),
+                format!("Preset: {}", preset.name),
+                format!("{} {}", preset.command, preset.summary()),
+            )
+        }));
+        if self.mode == AppMode::ResultsView {
+            let polling = if self.polling_active {
+                "Stop auto-refresh polling"
+            } else {
+                "Start auto-refresh polling"
+            };
+            entries.push(PaletteEntry::new(
+                PaletteAction::TogglePolling,
+                "Toggle polling",
+                polling,
+            ));
+            entries.push(PaletteEntry::new(
+                PaletteAction::Search,
+                "Search results",
+                "Fuzzy search the visible columns",
+            ));
+            entries.push(PaletteEntry::new(
+                PaletteAction::Columns,
+                "Choose columns",
+                "Show, hide, reorder and resize columns",
+            ));
+            if self.results_view_type == ResultsViewType::Rotation {
+                entries.push(PaletteEntry::new(
+                    PaletteAction::Timeline,
+                    "Manager timeline",
+                    "Manager history of the selected runner",
+                ));
+            }
+            entries.push(PaletteEntry::new(
+                PaletteAction::SavePreset,
+                "Save preset",
+                "Save the command, filters, sort and columns to config.toml",
+            ));
+            if let Some(runner) = self.selected_runner() {
+                let (title, verb) = if runner.paused {
+                    ("Resume runner", "Resume")
+                } else {
+                    ("Pause runner", "Pause")
+                };
+                entries.push(PaletteEntry::new(
+                    PaletteAction::PauseRunner,
+                    title,
+                    match runner.description.as_deref() {
+                        Some(description) => {
+                            format!("{} runner {} ({})", verb, runner.id, description)
+                        }
+                        None => format!("{} runner {}", verb, runner.id),
+                    },
+                ));
+            }
+            if self.result_len() > 0 {
+                for format in [ExportFormat::Csv, ExportFormat::Json] {
+                    entries.push(PaletteEntry::new(
+                        PaletteAction::Export(format),
+                        format!("Export {}", format),
+                        format!(
+                            "Write the results to a .{} file in {}",
+                            format.extension(),
+                            self.export_dir.display()
+                        ),
+                    ));
+                }
+            }
+        }
+        if self.profile_switcher.is_some() {
+            entries.extend(self.config.profiles.iter().map(|(name, profile)| {
+                let current = if self.profile.as_deref() == Some(name.as_str()) {
+                    " (current)"
+                } else {
+                    ""
+                };
+                PaletteEntry::new(
+                    PaletteAction::Profile(name.clone()),
+                    format!("Profile: {}", name),
+                    format!("Switch to {}{}", profile.gitlab_host, current),
+                )
+            }));
+        }
+        if !self.config.presets.is_empty() {
+            entries.push(PaletteEntry::new(
+                PaletteAction::Presets,
+                "Presets",
+                "Browse saved presets",
+            ));
+        }
+        entries.push(PaletteEntry::new(
+            PaletteAction::ToggleAlerts,
+            "Toggle alerts panel",
+            "Show or hide firing [[alerts]] rules",
+        ));
+        entries.push(PaletteEntry::new(
+            PaletteAction::Help,
+            "Help",
+            "List every key binding",
+        ));
+        entries.push(PaletteEntry::new(
+            PaletteAction::Quit,
+            "Quit",
+            "Exit the application",
+        ));
+        entries
+    }
+
+    pub fn open_palette(&mut self) {
+        let entries = self.palette_entries();
+        self.palette = Some(Palette::new(
+            entries,
+            self.recent_actions.clone(),
+            self.mode,
+        ));
+        self.mode = AppMode::Palette;
+    }
+
+    async fn handle_palette_key(&mut self, key: KeyEvent) {
+        let Some(palette) = self.palette.as_mut() else {
+            self.mode = AppMode::CommandSelection;
+            return;
+        };
+        let previous = palette.previous;
+        match palette.handle_key(key) {
+            PaletteOutcome::Open => {}
+            PaletteOutcome::Cancel => {
+                self.palette = None;
+                self.mode = previous;
+            }
+            PaletteOutcome::Run(action) => {
+                self.palette = None;
+                self.mode = previous;
+                self.recent_actions.record(&action);
+                self.run_palette_action(action).await;
+            }
+        }
+    }
+
+    async fn run_palette_action(&mut self, action: PaletteAction) {
+        match action {
+            PaletteAction::Command(command) => {
+                if let Some(index) = self.commands.iter().position(|c| *c == command) {
+                    self.selected_command_index = index;
+                    self.error_message = None;
+                    self.select_command();
+                }
+            }
+            PaletteAction::Preset(name) => {
+                let preset = self.config.presets.iter().find(|p| p.name == name).cloned();
+                if let Some(preset) = preset {
+                    self.run_preset(&preset).await;
+                }
+            }
+            PaletteAction::Profile(name) => self.switch_profile(&name).await,
+            PaletteAction::PauseRunner => self.toggle_runner_paused().await,
+            PaletteAction::Export(format) => self.export(format),
+            PaletteAction::TogglePolling => self.toggle_polling(),
+            PaletteAction::Search => self.start_search(),
+            PaletteAction::Columns => self.open_column_picker(),
+            PaletteAction::Timeline => self.open_timeline(),
+            PaletteAction::SavePreset => self.start_save_preset(),
+            PaletteAction::Presets => {
+                self.mode = AppMode::CommandSelection;
+                self.open_preset_picker();
+            }
+            PaletteAction::ToggleAlerts => self.toggle_alerts(),
+            PaletteAction::Help => self.mode = AppMode::Help,
+            PaletteAction::Quit => self.should_quit = true,
+        }
+    }
+
+    /// Connect to a `[profiles]` entry, starting the manager history and
+    /// notification baseline afresh, and rerun the results against it
+    async fn switch_profile(&mut self, name: &str) {
+        let Some(profile) = self.config.profiles.get(name).cloned() else {
+            return;
+        };
+        let Some(switcher) = self.profile_switcher.as_ref() else {
+            return;
+        };
+        match switcher(&profile) {
+            Ok(source) => {
+                self.conductor = Conductor::new(source);
+                self.profile = Some(name.to_string());
+                self.server_version = None;
+                self.server_version_retry_at = None;
+                self.manager_tracker = ManagerTracker::new(self.config.flap_policy());
+                if let Some(notifier) = self.notifier.as_mut() {
+                    notifier.reset();
+                }
+                self.show_toast(
+                    format!("Switched to profile '{}' ({})", name, profile.gitlab_host),
+                    false,
+                );
+                if self.mode == AppMode::ResultsView {
+                    self.execute_search().await;
+                }
+            }
+            Err(e) => self.show_toast(
+                format!("Failed to switch to profile '{}': {:#}", name, e),
+                true,
+            ),
+        }
+    }
+
+    /// Pause the selected runner through the source, or resume it if
+    /// paused; the row updates in place until the next poll
+    async fn toggle_runner_paused(&mut self) {
+        let Some(runner) = self.selected_runner() else {
+            return;
+        };
+        let (id, paused) = (runner.id, !runner.paused);
+        match self.conductor.set_runner_paused(id, paused).await {
+            Ok(_) => {
+                let rotating = self.rotation_rows.iter_mut().map(|row| &mut row.runner);
+                for runner in self.runners.iter_mut().chain(rotating) {
+                    if runner.id == id {
+                        runner.paused = paused;
+                    }
+                }
+                self.refresh_search();
+                let verb = if paused { "Paused" } else { "Resumed" };
+                self.show_toast(format!("{} runner {}", verb, id), false);
+            }
+            Err(e) => self.show_toast(format!("Failed to update runner {}: {:#}", id, e), true),
+        }
+    }
+
+    /// Write every column of the current results, in their current order,
+    /// to a file named after the command
+    fn export(&mut self, format: ExportFormat) {
+        let columns: Vec<Column> = self.columns().iter().map(|spec| spec.column).collect();
+        let rows = self.rows(&columns);
+        let command = self.commands[self.selected_command_index];
+        let stem = format!("{}-{}", command, Utc::now().format("%Y%m%d-%H%M%S"));
+        match export::write(&self.export_dir, &stem, format, &columns, &rows) {
+            Ok(path) => self.show_toast(
+                format!("Exported {} rows to {}", rows.len(), path.display()),
+                false,
+            ),
+            Err(e) => self.show_toast(format!("Failed to export: {:#}", e), true),
+        }
+    }
+
+    /// Write the current query to the config file as preset `name`
+    fn save_preset(&mut self, name: &str) {
+        let preset = self.current_preset(name);
+        match config::save_preset(self.config_path.as_deref(), &preset) {
+            Ok(path) => {
+                // Matching the file, so the config watcher has nothing to report
+                let presets = &mut self.config.presets;
+                match presets.iter_mut().find(|p| p.name == preset.name) {
+                    Some(existing) => *existing = preset,
+                    None => presets.push(preset),
+                }
+                self.show_toast(
+                    format!("Saved preset '{}' to {}", name, path.display()),
+                    false,
+                );
+            }
+            Err(e) => self.show_toast(format!("Failed to save preset: {:#}", e), true),
+        }
     }
 
     pub async fn execute_search(&mut self) {
@@ -184,75 +920,132 @@ impl App {
         self.error_message = None;
 
         let command = self.commands[self.selected_command_index];
-        let mut filters = crate::models::runner::RunnerFilters::default();
-
-        if !self.input_buffer.is_empty() {
-            filters.tag_list = Some(
-                self.input_buffer
-                    .split(',')
-                    .map(|s| s.trim().to_string())
-                    .collect(),
-            );
+        let filters = self.filter_form.to_filters();
+
+        // Coverage and pipeline read their tag sets or CI jobs before
+        // fetching runners
+        let ci_file = self.config.ci_file.as_deref();
+        let inputs = match command {
+            Command::Coverage => required_tag_sets(&self.config.required_tag_sets, ci_file)
+                .map(|sets| (sets, Vec::new())),
+            Command::Pipeline => pipeline_jobs(ci_file).map(|jobs| (Vec::new(), jobs)),
+            _ => Ok((Vec::new(), Vec::new())),
+        };
+        let (tag_sets, jobs) = match inputs {
+            Ok(inputs) => inputs,
+            Err(e) => {
+                self.is_loading = false;
+                self.error_message = Some(format!("{:#}", e));
+                self.mode = AppMode::ResultsView;
+                return;
+            }
+        };
+
+        let retry_due = self
+            .server_version_retry_at
+            .is_none_or(|at| Instant::now() >= at);
+        if self.server_version.is_none() && retry_due {
+            match self.conductor.server_version().await {
+                Ok(version) => {
+                    self.server_version = Some(version);
+                    self.server_version_retry_at = None;
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, "Failed to fetch GitLab version");
+                    self.server_version_retry_at = Some(Instant::now() + SERVER_VERSION_RETRY);
+                }
+            }
         }
 
         let result = match command {
-            Command::Fetch | Command::Lights | Command::Workers => {
-                self.conductor.fetch_runners(filters).await
+            Command::Fetch
+            | Command::Lights
+            | Command::Workers
+            | Command::Drift
+            | Command::Coverage
+            | Command::Pipeline => self.conductor.fetch_runners(filters.clone()).await,
+            Command::Switch => self.conductor.list_offline_runners(filters.clone()).await,
+            Command::Flames => {
+                self.conductor
+                    .list_uncontacted_runners(filters.clone(), DEFAULT_FLAMES_THRESHOLD_SECS)
+                    .await
+            }
+            Command::Empty => {
+                self.conductor
+                    .list_runners_without_managers(filters.clone())
+                    .await
             }
-            Command::Switch => self.conductor.list_offline_runners(filters).await,
-            Command::Flames => self.conductor.list_uncontacted_runners(filters, 3600).await,
-            Command::Empty => self.conductor.list_runners_without_managers(filters).await,
-            Command::Rotate => self.conductor.detect_rotating_runners(filters).await,
+            // Rotation diffs every runner's managers, not just those
+            // rotating right now, so a manager that drops out is seen
+            Command::Rotate => self.conductor.fetch_runners(filters.clone()).await,
         };
 
         self.is_loading = false;
 
         match result {
             Ok(runners) => {
+                if command.fetches_fleet() {
+                    self.notify(&filters, &runners);
+                    self.evaluate_alerts(&runners);
+                }
+                let runners = command.apply_query(self.active_query.as_ref(), runners);
+                let previous_view = self.results_view_type;
+                let previous_id = self.selected_row_id();
+
                 // Clear all previous results before populating new ones
                 self.runners.clear();
                 self.manager_rows.clear();
                 self.health_summary = None;
+                self.drift_report = None;
+                self.coverage_rows.clear();
+                self.job_rows.clear();
+                self.rotation_rows.clear();
 
+                self.results_view_type = command.view();
                 match command {
                     Command::Workers => {
-                        self.manager_rows = runners
-                            .iter()
-                            .flat_map(|r| {
-                                r.managers.iter().map(move |m| ManagerRow {
-                                    runner_id: r.id,
-                                    runner_tags: r.tag_list.clone(),
-                                    manager: m.clone(),
-                                })
-                            })
-                            .collect();
-                        self.results_view_type = ResultsViewType::Workers;
+                        let policy = self.compat_policy();
+                        self.manager_rows = ManagerRow::from_runners(&runners, policy.as_ref());
                     }
                     Command::Lights => {
-                        let online_count = runners
-                            .iter()
-                            .filter(|r| r.managers.iter().any(|m| m.status == "online"))
-                            .count();
-                        self.health_summary = Some(HealthSummary {
-                            online_count,
-                            total_count: runners.len(),
-                        });
+                        self.health_summary = Some(HealthSummary::from_runners(&runners));
                         self.runners = runners;
-                        self.results_view_type = ResultsViewType::HealthCheck;
                     }
                     Command::Rotate => {
-                        self.runners = runners;
-                        self.results_view_type = ResultsViewType::Rotation;
+                        let now = Utc::now();
+                        self.manager_tracker.observe(&runners, now);
+                        self.rotation_rows =
+                            RotationRow::from_runners(runners, &self.manager_tracker, now);
+                    }
+                    Command::Drift => {
+                        let target = self.config.target_version();
+                        let report = DriftReport::from_runners(&runners, target.as_ref());
+                        self.runners = report.outdated_runners(runners);
+                        self.drift_report = Some(report);
+                    }
+                    Command::Coverage => {
+                        self.coverage_rows = tag_coverage(&runners, &tag_sets);
+                    }
+                    Command::Pipeline => {
+                        self.job_rows = job_coverage(&runners, &jobs);
                     }
                     _ => {
                         self.runners = runners;
-                        self.results_view_type = ResultsViewType::Runners;
                     }
                 }
                 self.mode = AppMode::ResultsView;
-                if !self.runners.is_empty() || !self.manager_rows.is_empty() {
-                    self.table_state.select(Some(0));
+                self.sort_results();
+
+                // Keep the cursor on the same runner/manager when polling refreshes
+                let position = previous_id
+                    .filter(|_| previous_view == self.results_view_type)
+                    .and_then(|id| self.row_position(id));
+                if self.result_len() == 0 {
+                    self.table_state.select(None);
+                } else {
+                    self.table_state.select(Some(position.unwrap_or(0)));
                 }
+                self.refresh_search();
             }
             Err(e) => {
                 self.error_message = Some(format!("{:#}", e));
@@ -261,13 +1054,286 @@ impl App {
         }
     }
 
-    pub fn next_result(&mut self) {
-        let len = match self.results_view_type {
-            ResultsViewType::Runners | ResultsViewType::HealthCheck | ResultsViewType::Rotation => {
+    /// Runner compatibility rules, once the server version is known
+    pub fn compat_policy(&self) -> Option<CompatPolicy> {
+        Some(CompatPolicy {
+            server: self.server_version.as_ref()?.parsed()?,
+            max_minors_behind: self.config.max_minors_behind,
+        })
+    }
+
+    /// Number of rows in the current results table
+    pub fn result_len(&self) -> usize {
+        match self.results_view_type {
+            ResultsViewType::Runners | ResultsViewType::HealthCheck | ResultsViewType::Drift => {
                 self.runners.len()
             }
+            ResultsViewType::Rotation => self.rotation_rows.len(),
             ResultsViewType::Workers => self.manager_rows.len(),
+            ResultsViewType::Coverage => self.coverage_rows.len(),
+            ResultsViewType::Pipeline => self.job_rows.len(),
+        }
+    }
+
+    /// Column layout of the current results table
+    pub fn columns(&self) -> Vec<ColumnSpec> {
+        self.columns.for_view(self.results_view_type)
+    }
+
+    pub fn open_column_picker(&mut self) {
+        self.column_picker = Some(ColumnPicker::new(self.results_view_type, &self.columns()));
+        self.mode = AppMode::ColumnPicker;
+    }
+
+    fn close_column_picker(&mut self) {
+        self.column_picker = None;
+        self.mode = AppMode::ResultsView;
+    }
+
+    /// Apply picker edits to the live layout; the sort resets because
+    /// column indices may have shifted
+    fn handle_column_picker_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc | KeyCode::Enter => self.close_column_picker(),
+            _ if self.keymap.is(Action::Columns, &key) => self.close_column_picker(),
+            code => {
+                let Some(picker) = self.column_picker.as_mut() else {
+                    return;
+                };
+                if picker.handle_key(code) {
+                    let specs = picker.specs();
+                    self.columns.set(self.results_view_type, specs);
+                    self.sort = SortState::default();
+                    self.resort();
+                }
+            }
+        }
+    }
+
+    /// Send changes since the previous poll without holding up the UI
+    fn notify(&mut self, filters: &RunnerFilters, runners: &[Runner]) {
+        let Some(notifier) = self.notifier.as_mut() else {
+            return;
+        };
+        notifier.scope(filters);
+        let events = notifier.observe(runners);
+        if events.is_empty() {
+            return;
+        }
+        for event in &events {
+            tracing::info!(%event, "Fleet change");
+        }
+        let dispatcher = notifier.dispatcher();
+        tokio::spawn(async move { dispatcher.send(&events).await });
+    }
+
+    fn evaluate_alerts(&mut self, runners: &[Runner]) {
+        for alert in self.alerts.evaluate(runners, Utc::now()) {
+            match alert.state {
+                AlertState::Resolved => tracing::info!(%alert, "Alert resolved"),
+                _ => tracing::warn!(%alert, "Alert firing"),
+            }
+        }
+    }
+
+    pub fn toggle_alerts(&mut self) {
+        self.show_alerts = !self.show_alerts;
+    }
+
+    /// Open the manager timeline of the selected rotation row
+    pub fn open_timeline(&mut self) {
+        if self.results_view_type != ResultsViewType::Rotation {
+            return;
+        }
+        if let Some(id) = self.selected_row_id() {
+            self.timeline_runner = Some(id);
+            self.mode = AppMode::Timeline;
+        }
+    }
+
+    fn close_timeline(&mut self) {
+        self.timeline_runner = None;
+        self.mode = AppMode::ResultsView;
+    }
+
+    fn row_ids(&self) -> Vec<u64> {
+        match self.results_view_type {
+            ResultsViewType::Runners | ResultsViewType::HealthCheck | ResultsViewType::Drift => {
+                self.runners.iter().map(TableRow::row_id).collect()
+            }
+            ResultsViewType::Rotation => self.rotation_rows.iter().map(TableRow::row_id).collect(),
+            ResultsViewType::Workers => self.manager_rows.iter().map(TableRow::row_id).collect(),
+            ResultsViewType::Coverage => self.coverage_rows.iter().map(TableRow::row_id).collect(),
+            ResultsViewType::Pipeline => self.job_rows.iter().map(TableRow::row_id).collect(),
+        }
+    }
+
+    fn selected_row_id(&self) -> Option<u64> {
+        let selected = self.table_state.selected()?;
+        self.row_ids().get(selected).copied()
+    }
+
+    /// The runner under the cursor, in views whose rows are runners
+    pub fn selected_runner(&self) -> Option<&Runner> {
+        let selected = self.table_state.selected()?;
+        match self.results_view_type {
+            ResultsViewType::Runners | ResultsViewType::HealthCheck | ResultsViewType::Drift => {
+                self.runners.get(selected)
+            }
+            ResultsViewType::Rotation => self.rotation_rows.get(selected).map(|row| &row.runner),
+            _ => None,
+        }
+    }
+
+    fn row_position(&self, id: u64) -> Option<usize> {
+        self.row_ids().iter().position(|&row_id| row_id == id)
+    }
+
+    /// Re-order the current rows by the active sort column
+    fn sort_results(&mut self) {
+        let columns = self.columns();
+        let Some(column) = self
+            .sort
+            .column
+            .and_then(|index| columns.get(index))
+            .map(|spec| spec.column)
+        else {
+            return;
         };
+        let descending = self.sort.descending;
+        match self.results_view_type {
+            ResultsViewType::Runners | ResultsViewType::HealthCheck | ResultsViewType::Drift => {
+                sort_rows(&mut self.runners, column, descending)
+            }
+            ResultsViewType::Rotation => sort_rows(&mut self.rotation_rows, column, descending),
+            ResultsViewType::Workers => sort_rows(&mut self.manager_rows, column, descending),
+            ResultsViewType::Coverage => sort_rows(&mut self.coverage_rows, column, descending),
+            ResultsViewType::Pipeline => sort_rows(&mut self.job_rows, column, descending),
+        }
+    }
+
+    /// Sort by the column at `index` (toggling direction if already sorted
+    /// by it), keeping the selected row under the cursor
+    pub fn sort_by_column(&mut self, index: usize) {
+        if index >= self.columns().len() {
+            return;
+        }
+        self.sort.sort_by(index);
+        self.resort();
+    }
+
+    /// Re-sort the current rows, keeping the selected row under the cursor
+    fn resort(&mut self) {
+        let selected_id = self.selected_row_id();
+        self.sort_results();
+        if let Some(position) = selected_id.and_then(|id| self.row_position(id)) {
+            self.table_state.select(Some(position));
+        }
+        self.refresh_search();
+    }
+
+    /// Indices into `columns()` of the columns that fit the table
+    pub fn shown_columns(&self) -> Vec<usize> {
+        let columns = self.columns();
+        match self.results_width {
+            Some(width) => fit_columns(&columns, width),
+            None => (0..columns.len()).collect(),
+        }
+    }
+
+    /// Record the table width from the renderer. Search only matches shown
+    /// columns, so matches are refreshed when the width shows others.
+    pub fn set_results_width(&mut self, width: u16) {
+        let shown = self.shown_columns();
+        self.results_width = Some(width);
+        if self.shown_columns() != shown {
+            self.refresh_search();
+        }
+    }
+
+    /// Cell text of every row in the current results table, one cell per
+    /// shown column
+    pub fn visible_rows(&self) -> Vec<Vec<String>> {
+        let columns = self.columns();
+        let shown: Vec<Column> = self
+            .shown_columns()
+            .into_iter()
+            .map(|i| columns[i].column)
+            .collect();
+        self.rows(&shown)
+    }
+
+    /// Cell text of every row in the current results table for `columns`
+    fn rows(&self, columns: &[Column]) -> Vec<Vec<String>> {
+        let cells = |row: &dyn TableRow| columns.iter().map(|&c| row.cell(c)).collect();
+        match self.results_view_type {
+            ResultsViewType::Runners | ResultsViewType::HealthCheck | ResultsViewType::Drift => {
+                self.runners.iter().map(|r| cells(r)).collect()
+            }
+            ResultsViewType::Rotation => self.rotation_rows.iter().map(|r| cells(r)).collect(),
+            ResultsViewType::Workers => self.manager_rows.iter().map(|r| cells(r)).collect(),
+            ResultsViewType::Coverage => self.coverage_rows.iter().map(|r| cells(r)).collect(),
+            ResultsViewType::Pipeline => self.job_rows.iter().map(|r| cells(r)).collect(),
+        }
+    }
+
+    fn refresh_search(&mut self) {
+        if self.search.is_active() {
+            let rows = self.visible_rows();
+            self.search.update_matches(&rows);
+        }
+    }
+
+    pub fn start_search(&mut self) {
+        self.search.clear();
+        self.search.editing = true;
+    }
+
+    /// Recompute matches after the query changed and jump to the nearest one
+    fn search_query_changed(&mut self) {
+        let rows = self.visible_rows();
+        self.search.update_matches(&rows);
+        let from = self.table_state.selected().unwrap_or(0);
+        if let Some(i) = self.search.match_from(from) {
+            self.table_state.select(Some(i));
+        }
+    }
+
+    pub fn next_match(&mut self) {
+        if let Some(i) = self.search.next_match(self.table_state.selected()) {
+            self.table_state.select(Some(i));
+        }
+    }
+
+    pub fn previous_match(&mut self) {
+        if let Some(i) = self.search.previous_match(self.table_state.selected()) {
+            self.table_state.select(Some(i));
+        }
+    }
+
+    fn handle_search_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Enter => {
+                self.search.editing = false;
+                if self.search.query.is_empty() {
+                    self.search.clear();
+                }
+            }
+            KeyCode::Esc => self.search.clear(),
+            KeyCode::Backspace => {
+                self.search.query.pop();
+                self.search_query_changed();
+            }
+            KeyCode::Char(c) => {
+                self.search.query.push(c);
+                self.search_query_changed();
+            }
+            _ => {}
+        }
+    }
+
+    pub fn next_result(&mut self) {
+        let len = self.result_len();
         if len == 0 {
             return;
         }
@@ -285,12 +1351,7 @@ impl App {
     }
 
     pub fn previous_result(&mut self) {
-        let len = match self.results_view_type {
-            ResultsViewType::Runners | ResultsViewType::HealthCheck | ResultsViewType::Rotation => {
-                self.runners.len()
-            }
-            ResultsViewType::Workers => self.manager_rows.len(),
-        };
+        let len = self.result_len();
         if len == 0 {
             return;
         }
@@ -311,6 +1372,7 @@ impl App {
         if self.polling_active {
             self.polling_active = false;
             self.poll_started_at = None;
+            self.poll_paused_at = None;
             self.last_poll_at = None;
         } else {
             self.polling_active = true;
@@ -320,8 +1382,9 @@ impl App {
     }
 
     pub fn poll_elapsed_secs(&self) -> u64 {
+        let until = self.poll_paused_at.unwrap_or_else(Instant::now);
         self.poll_started_at
-            .map(|t| t.elapsed().as_secs())
+            .map(|t| until.saturating_duration_since(t).as_secs())
             .unwrap_or(0)
     }
 
@@ -329,8 +1392,33 @@ impl App {
         self.poll_elapsed_secs() >= self.config.poll_timeout_secs
     }
 
+    /// Polling is on but held because the window lost focus
+    pub fn polling_paused(&self) -> bool {
+        self.polling_active && !self.focused && self.config.pause_when_unfocused
+    }
+
+    pub fn set_focus(&mut self, focused: bool) {
+        self.focused = focused;
+        self.sync_poll_pause();
+    }
+
+    /// Start or stop the timeout clock as polling is held and released, so
+    /// time spent unfocused doesn't count towards `poll_timeout_secs`
+    fn sync_poll_pause(&mut self) {
+        match (self.polling_paused(), self.poll_paused_at) {
+            (true, None) => self.poll_paused_at = Some(Instant::now()),
+            (false, Some(paused_at)) => {
+                self.poll_paused_at = None;
+                if let Some(started) = self.poll_started_at.as_mut() {
+                    *started += paused_at.elapsed();
+                }
+            }
+            _ => {}
+        }
+    }
+
     fn should_poll_now(&self) -> bool {
-        if !self.polling_active || self.is_loading {
+        if !self.polling_active || self.is_loading || self.polling_paused() {
             return false;
         }
         if self.mode != AppMode::ResultsView {
@@ -349,6 +1437,18 @@ impl App {
             self.advance_spinner();
         }
 
+        if let Some((path, config)) = self.config_watcher.as_mut().and_then(ConfigWatcher::check) {
+            self.reload_config(&path, config).await;
+        }
+        if self
+            .toast
+            .as_ref()
+            .is_some_and(|t| t.shown_at.elapsed() >= TOAST_DURATION)
+        {
+            self.toast = None;
+        }
+
+        self.sync_poll_pause();
         if self.should_poll_now() {
             self.last_poll_at = Some(Instant::now());
             self.execute_search().await;
@@ -359,22 +1459,139 @@ impl App {
         }
     }
 
+    /// Clicks and the wheel on the command list and results table: a click
+    /// runs a command, selects a row or sorts by a header
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) {
+        let (column, row) = (mouse.column, mouse.row);
+        match (self.mode, mouse.kind) {
+            (AppMode::CommandSelection, MouseEventKind::ScrollDown) => {
+                self.selected_command_index =
+                    (self.selected_command_index + 1).min(self.commands.len() - 1);
+            }
+            (AppMode::CommandSelection, MouseEventKind::ScrollUp) => {
+                self.selected_command_index = self.selected_command_index.saturating_sub(1);
+            }
+            (AppMode::ResultsView, MouseEventKind::ScrollDown) => self.scroll_results(true),
+            (AppMode::ResultsView, MouseEventKind::ScrollUp) => self.scroll_results(false),
+            (AppMode::CommandSelection, MouseEventKind::Down(MouseButton::Left)) => {
+                let clicked = self
+                    .click_areas
+                    .commands
+                    .and_then(|list| list.item_at(column, row))
+                    .filter(|&index| index < self.commands.len());
+                if let Some(index) = clicked {
+                    self.selected_command_index = index;
+                    self.select_command();
+                }
+            }
+            (AppMode::ResultsView, MouseEventKind::Down(MouseButton::Left)) => {
+                let Some(table) = &self.click_areas.table else {
+                    return;
+                };
+                if let Some(index) = table.header_at(column, row) {
+                    self.sort_by_column(index);
+                } else if let Some(index) = table
+                    .body
+                    .item_at(column, row)
+                    .filter(|&index| index < self.result_len())
+                {
+                    self.table_state.select(Some(index));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Bracketed paste goes to the open text input in one piece
+    pub fn handle_paste(&mut self, text: &str) {
+        let line = text.replace(['\r', '\n'], " ");
+        match self.mode {
+            AppMode::FilterInput => {
+                self.filter_form.paste(text);
+            }
+            AppMode::ResultsView if self.search.editing => {
+                self.search.query.push_str(&line);
+                self.search_query_changed();
+            }
+            AppMode::SavePreset => self.preset_name.push_str(&line),
+            AppMode::Palette => {
+                if let Some(palette) = self.palette.as_mut() {
+                    palette.paste(&line);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Move the selection one row without wrapping around, for the wheel
+    fn scroll_results(&mut self, down: bool) {
+        let len = self.result_len();
+        if len == 0 {
+            return;
+        }
+        let selected = self.table_state.selected().unwrap_or(0);
+        let selected = if down {
+            (selected + 1).min(len - 1)
+        } else {
+            selected.saturating_sub(1)
+        };
+        self.table_state.select(Some(selected));
+    }
+
     pub async fn handle_key(&mut self, key: KeyEvent) {
-        // FilterInput mode: route all chars/backspace to input buffer first
+        // FilterInput mode: route all other keys to the filter form first
         if self.mode == AppMode::FilterInput {
             match key.code {
-                KeyCode::Enter => self.execute_search().await,
+                KeyCode::Enter => match self.filter_form.query() {
+                    Ok(query) => {
+                        self.filter_form.error = None;
+                        self.active_query = query;
+                        self.execute_search().await;
+                    }
+                    Err(e) => self.filter_form.error = Some(e.to_string()),
+                },
                 KeyCode::Esc => {
                     self.error_message = None;
                     self.mode = AppMode::CommandSelection;
                 }
-                KeyCode::Backspace => {
-                    self.input_buffer.pop();
+                code => {
+                    self.filter_form.handle_key(code);
                 }
-                KeyCode::Char(c) => {
-                    self.input_buffer.push(c);
-                }
-                _ => {}
+            }
+            return;
+        }
+
+        // Search input in results view: route keys to the search query
+        if self.mode == AppMode::ResultsView && self.search.editing {
+            self.handle_search_key(key.code);
+            return;
+        }
+
+        if self.mode == AppMode::ColumnPicker {
+            self.handle_column_picker_key(key);
+            return;
+        }
+
+        if self.mode == AppMode::PresetPicker {
+            self.handle_preset_picker_key(key).await;
+            return;
+        }
+
+        if self.mode == AppMode::SavePreset {
+            self.handle_save_preset_key(key.code);
+            return;
+        }
+
+        if self.mode == AppMode::Palette {
+            self.handle_palette_key(key).await;
+            return;
+        }
+
+        if self.mode == AppMode::Timeline {
+            if matches!(key.code, KeyCode::Esc | KeyCode::Enter)
+                || self.keymap.is(Action::Timeline, &key)
+            {
+                self.close_timeline();
             }
             return;
         }
@@ -385,41 +1602,46 @@ impl App {
             return;
         }
 
-        // CommandSelection and ResultsView modes
-        match key.code {
-            KeyCode::Char('?') => {
-                self.mode = AppMode::Help;
-            }
-            KeyCode::Char('q') => {
-                self.should_quit = true;
-            }
-            KeyCode::Char('p') if self.mode == AppMode::ResultsView => {
-                self.toggle_polling();
-            }
-            KeyCode::Up | KeyCode::Char('k') => match self.mode {
+        // CommandSelection and ResultsView modes, through the keymap
+        let Some(action) = self.keymap.action(self.mode, &key) else {
+            return;
+        };
+        match action {
+            Action::Help => self.mode = AppMode::Help,
+            Action::Quit => self.should_quit = true,
+            Action::Palette => self.open_palette(),
+            Action::ToggleAlerts => self.toggle_alerts(),
+            Action::Presets => self.open_preset_picker(),
+            Action::TogglePolling => self.toggle_polling(),
+            Action::Search => self.start_search(),
+            Action::NextMatch => self.next_match(),
+            Action::PreviousMatch => self.previous_match(),
+            Action::ColumnLeft => self.sort.previous_column(self.columns().len()),
+            Action::ColumnRight => self.sort.next_column(self.columns().len()),
+            Action::Sort => self.sort_by_column(self.sort.cursor),
+            Action::SavePreset => self.start_save_preset(),
+            Action::Columns => self.open_column_picker(),
+            Action::Timeline => self.open_timeline(),
+            Action::Up => match self.mode {
                 AppMode::CommandSelection => self.previous_command(),
                 AppMode::ResultsView => self.previous_result(),
                 _ => {}
             },
-            KeyCode::Down | KeyCode::Char('j') => match self.mode {
+            Action::Down => match self.mode {
                 AppMode::CommandSelection => self.next_command(),
                 AppMode::ResultsView => self.next_result(),
                 _ => {}
             },
-            KeyCode::Enter => {
-                if self.mode == AppMode::CommandSelection {
-                    self.select_command();
-                }
-            }
-            KeyCode::Esc => match self.mode {
+            Action::Select => self.select_command(),
+            Action::Back => match self.mode {
                 AppMode::CommandSelection => self.should_quit = true,
+                AppMode::ResultsView if self.search.is_active() => self.search.clear(),
                 AppMode::ResultsView => {
                     self.error_message = None;
                     self.mode = AppMode::CommandSelection;
                 }
                 _ => self.mode = AppMode::CommandSelection,
             },
-            _ => {}
         }
     }
 }
@@ -427,6 +1649,8 @@ impl App {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::test_support;
+    use crate::source::fake::FakeSource;
 
     #[test]
     fn test_health_summary_percentage_all_online() {
@@ -490,26 +1714,284 @@ mod tests {
     #[test]
     fn test_manager_row_creation() {
         let manager = RunnerManager {
-            id: 1,
-            system_id: "test-host".to_string(),
-            created_at: "2024-01-15T10:30:00.000Z".to_string(),
-            contacted_at: Some("2024-01-20T14:22:00.000Z".to_string()),
-            ip_address: Some("10.0.1.1".to_string()),
-            status: "online".to_string(),
             version: Some("17.5.0".to_string()),
-            revision: None,
-            platform: None,
-            architecture: None,
+            ..test_support::manager(1, "test-host")
         };
 
         let row = ManagerRow {
             runner_id: 12345,
             runner_tags: vec!["alm".to_string(), "prod".to_string()],
             manager: manager.clone(),
+            compatibility: Compatibility::Unknown,
         };
 
         assert_eq!(row.runner_id, 12345);
         assert_eq!(row.runner_tags.len(), 2);
         assert_eq!(row.manager.system_id, "test-host");
     }
+
+    fn fleet() -> Vec<Runner> {
+        let manager = |id, online| RunnerManager {
+            status: test_support::status(online),
+            ..test_support::manager(id, &format!("host-{}", id))
+        };
+        vec![
+            Runner {
+                tag_list: test_support::tags(&["prod"]),
+                managers: vec![manager(10, true)],
+                ..test_support::runner(1)
+            },
+            Runner {
+                tag_list: test_support::tags(&["dev"]),
+                managers: vec![manager(20, false)],
+                ..test_support::runner(2)
+            },
+            test_support::runner(3),
+        ]
+    }
+
+    fn app(config: AppConfig) -> App<FakeSource> {
+        App::new(Conductor::new(FakeSource::new(fleet())), config)
+    }
+
+    async fn run(app: &mut App<FakeSource>, command: Command) {
+        app.selected_command_index = app.commands.iter().position(|&c| c == command).unwrap();
+        app.execute_search().await;
+    }
+
+    #[tokio::test]
+    async fn test_execute_search_fills_each_command_view() {
+        let mut app = app(AppConfig {
+            required_tag_sets: vec![vec!["prod".to_string()]],
+            ..AppConfig::default()
+        });
+        let expected = [
+            (Command::Fetch, 3),
+            (Command::Lights, 3),
+            (Command::Switch, 1),
+            (Command::Workers, 2),
+            (Command::Flames, 2),
+            (Command::Empty, 1),
+            (Command::Rotate, 0),
+            (Command::Drift, 0),
+            (Command::Coverage, 1),
+        ];
+        for (command, rows) in expected {
+            run(&mut app, command).await;
+            assert_eq!(app.error_message, None, "{}", command);
+            assert_eq!(app.mode, AppMode::ResultsView);
+            assert_eq!(app.results_view_type, command.view(), "{}", command);
+            assert_eq!(app.result_len(), rows, "{}", command);
+        }
+
+        run(&mut app, Command::Lights).await;
+        let health = app.health_summary.as_ref().unwrap();
+        assert_eq!((health.online_count, health.total_count), (1, 3));
+    }
+
+    #[tokio::test]
+    async fn test_execute_search_shows_errors() {
+        let mut app = app(AppConfig::default());
+        run(&mut app, Command::Pipeline).await;
+        assert_eq!(app.mode, AppMode::ResultsView);
+        assert!(!app.is_loading);
+        assert_eq!(
+            app.error_message.as_deref(),
+            Some("no CI file to check: pass --ci-file or set ci_file in config.toml")
+        );
+
+        // The next successful search clears it
+        run(&mut app, Command::Fetch).await;
+        assert_eq!(app.error_message, None);
+    }
+
+    #[tokio::test]
+    async fn test_polling_keeps_the_selected_runner() {
+        let mut app = app(AppConfig::default());
+        run(&mut app, Command::Fetch).await;
+        let position = app.row_position(3).unwrap();
+        app.table_state.select(Some(position));
+
+        // Runner 1 goes away, so runner 3 moves up a row
+        app.conductor
+            .source_mut()
+            .runners_mut()
+            .retain(|r| r.id != 1);
+        app.execute_search().await;
+        assert_eq!(app.result_len(), 2);
+        assert_eq!(app.selected_row_id(), Some(3));
+
+        // A runner that disappears leaves the cursor on the first row
+        app.conductor
+            .source_mut()
+            .runners_mut()
+            .retain(|r| r.id != 3);
+        app.execute_search().await;
+        assert_eq!(app.table_state.selected(), Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_search_only_matches_shown_columns() {
+        let mut app = app(AppConfig::default());
+        run(&mut app, Command::Fetch).await;
+        app.search.query = "group_type".to_string();
+        app.refresh_search();
+        assert_eq!(app.search.matches.len(), 3);
+
+        // Too narrow for the type column
+        app.set_results_width(30);
+        let columns = app.columns();
+        assert!(app
+            .shown_columns()
+            .iter()
+            .all(|&i| columns[i].column != Column::Type));
+        assert!(app.search.matches.is_empty());
+
+        app.set_results_width(200);
+        assert_eq!(app.search.matches.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_first_sort_on_any_column_is_ascending() {
+        let mut app = app(AppConfig::default());
+        run(&mut app, Command::Fetch).await;
+        assert_eq!(app.sort.column, None);
+        assert_eq!(app.current_preset("p").sort, None);
+
+        app.runners.reverse();
+        app.sort_by_column(0);
+        let ids: Vec<u64> = app.runners.iter().map(|r| r.id).collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert!(!app.sort.descending);
+
+        app.sort_by_column(0);
+        assert!(app.sort.descending);
+    }
+
+    fn has_entry(app: &App<FakeSource>, action: &PaletteAction) -> bool {
+        app.palette_entries().iter().any(|e| e.action == *action)
+    }
+
+    #[tokio::test]
+    async fn test_palette_pauses_and_resumes_the_selected_runner() {
+        let mut app = app(AppConfig::default());
+        assert!(!has_entry(&app, &PaletteAction::PauseRunner));
+        run(&mut app, Command::Fetch).await;
+        assert!(has_entry(&app, &PaletteAction::PauseRunner));
+
+        app.run_palette_action(PaletteAction::PauseRunner).await;
+        assert!(app.runners[0].paused);
+        // The source has it too, so the next poll keeps it paused
+        run(&mut app, Command::Fetch).await;
+        assert!(app.runners[0].paused);
+        let entry = app
+            .palette_entries()
+            .into_iter()
+            .find(|e| e.action == PaletteAction::PauseRunner)
+            .unwrap();
+        assert_eq!(entry.title, "Resume runner");
+
+        app.run_palette_action(PaletteAction::PauseRunner).await;
+        assert!(!app.runners[0].paused);
+
+        // Manager rows have no paused state to toggle
+        run(&mut app, Command::Workers).await;
+        assert!(!has_entry(&app, &PaletteAction::PauseRunner));
+    }
+
+    #[tokio::test]
+    async fn test_palette_exports_every_column_of_the_results() {
+        let dir = std::env::temp_dir().join(format!("app-export-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut app = app(AppConfig::default());
+        app.export_dir = dir.clone();
+        run(&mut app, Command::Fetch).await;
+        // Hidden by the width, still exported
+        app.set_results_width(30);
+
+        app.run_palette_action(PaletteAction::Export(ExportFormat::Csv))
+            .await;
+        let files: Vec<PathBuf> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(files.len(), 1);
+        let name = files[0].file_name().unwrap().to_string_lossy().to_string();
+        assert!(
+            name.starts_with("fetch-") && name.ends_with(".csv"),
+            "{}",
+            name
+        );
+        let csv = std::fs::read_to_string(&files[0]).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].split(',').any(|name| name == "type"));
+        assert!(!app.toast.unwrap().is_error);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_palette_switches_profile() {
+        let mut config = AppConfig::default();
+        config.profiles.insert(
+            "other".to_string(),
+            Profile {
+                gitlab_host: "https://other.example.com".to_string(),
+                gitlab_token: None,
+            },
+        );
+        let mut app = app(config);
+        let switch = PaletteAction::Profile("other".to_string());
+        assert!(!has_entry(&app, &switch));
+
+        app = app.with_profile_switcher(Box::new(|profile| {
+            assert_eq!(profile.gitlab_host, "https://other.example.com");
+            Ok(FakeSource::new(vec![test_support::runner(9)]))
+        }));
+        assert!(has_entry(&app, &switch));
+        run(&mut app, Command::Fetch).await;
+        app.run_palette_action(switch).await;
+
+        assert_eq!(app.profile.as_deref(), Some("other"));
+        let ids: Vec<u64> = app.runners.iter().map(|r| r.id).collect();
+        assert_eq!(ids, vec![9]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_version_fetch_waits_before_retrying() {
+        let mut app = app(AppConfig::default());
+        run(&mut app, Command::Workers).await;
+        assert!(app.server_version.is_none());
+        let retry_at = app.server_version_retry_at.unwrap();
+
+        // The next search doesn't ask again
+        app.execute_search().await;
+        assert_eq!(app.server_version_retry_at, Some(retry_at));
+
+        app.server_version_retry_at = Instant::now().checked_sub(Duration::from_secs(1));
+        app.execute_search().await;
+        assert!(app.server_version_retry_at.unwrap() > retry_at);
+    }
+
+    #[test]
+    fn test_poll_timeout_clock_stops_while_unfocused() {
+        let config = AppConfig {
+            pause_when_unfocused: true,
+            ..AppConfig::default()
+        };
+        let mut app = App::new(Conductor::new(FakeSource::new(vec![])), config);
+        app.toggle_polling();
+        let ago = |secs| Instant::now().checked_sub(Duration::from_secs(secs));
+        app.poll_started_at = ago(100);
+
+        app.set_focus(false);
+        assert!(app.polling_paused());
+        app.poll_paused_at = ago(40);
+        assert_eq!(app.poll_elapsed_secs(), 60);
+
+        app.set_focus(true);
+        assert!(app.poll_paused_at.is_none());
+        assert_eq!(app.poll_elapsed_secs(), 60);
+    }
 }
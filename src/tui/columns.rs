@@ -0,0 +1,481 @@
+use crate::tui::app::ResultsViewType;
+use crate::tui::table::Column;
+use anyhow::{bail, Result};
+use crossterm::event::KeyCode;
+use ratatui::layout::Constraint;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Width of a table column, as written after the `:` in a column spec
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnWidth {
+    /// Exactly this many cells, e.g. `"version:12"`
+    Length(u16),
+    /// Share of the table width, e.g. `"tags:30%"`
+    Percentage(u16),
+    /// At least this many cells, growing into spare space, e.g. `"description:20+"`
+    Min(u16),
+}
+
+impl ColumnWidth {
+    pub fn constraint(&self) -> Constraint {
+        match *self {
+            ColumnWidth::Length(n) => Constraint::Length(n),
+            ColumnWidth::Percentage(p) => Constraint::Percentage(p),
+            ColumnWidth::Min(n) => Constraint::Min(n),
+        }
+    }
+
+    /// Cells the column needs before it is worth showing at all
+    fn min_width(&self, header: &str) -> u16 {
+        match *self {
+            ColumnWidth::Length(n) | ColumnWidth::Min(n) => n,
+            ColumnWidth::Percentage(_) => header.chars().count() as u16 + 2,
+        }
+    }
+
+    /// Grow or shrink by `delta` cells (or percentage points), keeping it above zero
+    fn resized(&self, delta: i16) -> ColumnWidth {
+        let apply = |n: u16| n.saturating_add_signed(delta).max(1);
+        match *self {
+            ColumnWidth::Length(n) => ColumnWidth::Length(apply(n)),
+            ColumnWidth::Percentage(p) => ColumnWidth::Percentage(apply(p).min(100)),
+            ColumnWidth::Min(n) => ColumnWidth::Min(apply(n)),
+        }
+    }
+}
+
+impl fmt::Display for ColumnWidth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColumnWidth::Length(n) => write!(f, "{}", n),
+            ColumnWidth::Percentage(p) => write!(f, "{}%", p),
+            ColumnWidth::Min(n) => write!(f, "{}+", n),
+        }
+    }
+}
+
+/// A column and its width, written as `"name"` or `"name:width"` in `config.toml`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ColumnSpec {
+    pub column: Column,
+    pub width: ColumnWidth,
+}
+
+impl ColumnSpec {
+    pub fn new(column: Column) -> Self {
+        Self {
+            column,
+            width: column.default_width(),
+        }
+    }
+
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (name, width) = match spec.split_once(':') {
+            Some((name, width)) => (name.trim(), Some(width.trim())),
+            None => (spec.trim(), None),
+        };
+        let Some(column) = Column::from_name(name) else {
+            let names: Vec<&str> = Column::ALL.iter().map(|c| c.name()).collect();
+            bail!(
+                "unknown column '{}' (expected one of: {})",
+                name,
+                names.join(", ")
+            );
+        };
+        let width = match width {
+            None => column.default_width(),
+            Some(w) => parse_width(w).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "invalid width '{}' for column '{}' (expected e.g. 12, 30% or 12+)",
+                    w,
+                    name
+                )
+            })?,
+        };
+        Ok(Self { column, width })
+    }
+}
+
+fn parse_width(raw: &str) -> Option<ColumnWidth> {
+    let width = if let Some(p) = raw.strip_suffix('%') {
+        ColumnWidth::Percentage(p.parse().ok().filter(|p| (1..=100).contains(p))?)
+    } else if let Some(n) = raw.strip_suffix('+') {
+        ColumnWidth::Min(n.parse().ok()?)
+    } else {
+        ColumnWidth::Length(raw.parse().ok()?)
+    };
+    Some(width)
+}
+
+impl TryFrom<String> for ColumnSpec {
+    type Error = anyhow::Error;
+
+    fn try_from(spec: String) -> Result<Self> {
+        ColumnSpec::parse(&spec)
+    }
+}
+
+impl From<ColumnSpec> for String {
+    fn from(spec: ColumnSpec) -> Self {
+        spec.to_string()
+    }
+}
+
+impl fmt::Display for ColumnSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.width == self.column.default_width() {
+            write!(f, "{}", self.column.name())
+        } else {
+            write!(f, "{}:{}", self.column.name(), self.width)
+        }
+    }
+}
+
+/// Per-view column layouts from the `[columns]` table of `config.toml`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColumnsConfig {
+    pub runners: Option<Vec<ColumnSpec>>,
+    pub workers: Option<Vec<ColumnSpec>>,
+    pub health_check: Option<Vec<ColumnSpec>>,
+    pub rotation: Option<Vec<ColumnSpec>>,
+    pub drift: Option<Vec<ColumnSpec>>,
+    pub coverage: Option<Vec<ColumnSpec>>,
+    pub pipeline: Option<Vec<ColumnSpec>>,
+}
+
+impl ColumnsConfig {
+    fn slot(&self, view: ResultsViewType) -> &Option<Vec<ColumnSpec>> {
+        match view {
+            ResultsViewType::Runners => &self.runners,
+            ResultsViewType::Workers => &self.workers,
+            ResultsViewType::HealthCheck => &self.health_check,
+            ResultsViewType::Rotation => &self.rotation,
+            ResultsViewType::Drift => &self.drift,
+            ResultsViewType::Coverage => &self.coverage,
+            ResultsViewType::Pipeline => &self.pipeline,
+        }
+    }
+
+    /// Configured layout for a view, or its defaults
+    pub fn for_view(&self, view: ResultsViewType) -> Vec<ColumnSpec> {
+        match self.slot(view) {
+            Some(specs) => specs.clone(),
+            None => view
+                .default_columns()
+                .iter()
+                .copied()
+                .map(ColumnSpec::new)
+                .collect(),
+        }
+    }
+
+    pub fn set(&mut self, view: ResultsViewType, specs: Vec<ColumnSpec>) {
+        let slot = match view {
+            ResultsViewType::Runners => &mut self.runners,
+            ResultsViewType::Workers => &mut self.workers,
+            ResultsViewType::HealthCheck => &mut self.health_check,
+            ResultsViewType::Rotation => &mut self.rotation,
+            ResultsViewType::Drift => &mut self.drift,
+            ResultsViewType::Coverage => &mut self.coverage,
+            ResultsViewType::Pipeline => &mut self.pipeline,
+        };
+        *slot = Some(specs);
+    }
+
+    /// Reject empty layouts and columns that have no data in their view
+    pub fn validate(&self) -> Result<()> {
+        for view in ResultsViewType::ALL {
+            let Some(specs) = self.slot(*view) else {
+                continue;
+            };
+            if specs.is_empty() {
+                bail!("columns.{}: at least one column is required", view.name());
+            }
+            let available = view.available_columns();
+            if let Some(spec) = specs.iter().find(|s| !available.contains(&s.column)) {
+                let names: Vec<&str> = available.iter().map(|c| c.name()).collect();
+                bail!(
+                    "columns.{}: column '{}' is not available in this view (expected one of: {})",
+                    view.name(),
+                    spec.column.name(),
+                    names.join(", ")
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Indices of the columns that fit in `width` cells, dropping the
+/// lowest-priority (rightmost on ties) columns first
+pub fn fit_columns(specs: &[ColumnSpec], width: u16) -> Vec<usize> {
+    // Summed wider than u16: configured widths can add up past it
+    let needed = |indices: &[usize]| -> usize {
+        let cells: usize = indices
+            .iter()
+            .map(|&i| specs[i].width.min_width(specs[i].column.header()) as usize)
+            .sum();
+        // One cell of spacing between adjacent columns
+        cells + indices.len().saturating_sub(1)
+    };
+
+    let mut kept: Vec<usize> = (0..specs.len()).collect();
+    while kept.len() > 1 && needed(&kept) > width as usize {
+        let drop = kept
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, &i)| (specs[i].column.priority(), i))
+            .map(|(pos, _)| pos)
+            .unwrap_or(kept.len() - 1);
+        kept.remove(drop);
+    }
+    kept
+}
+
+/// One row of the column picker
+#[derive(Debug, Clone, PartialEq)]
+pub struct PickerEntry {
+    pub spec: ColumnSpec,
+    pub enabled: bool,
+}
+
+/// In-TUI editor for the current view's columns: visibility, order and width
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnPicker {
+    pub view: ResultsViewType,
+    pub entries: Vec<PickerEntry>,
+    pub selected: usize,
+}
+
+impl ColumnPicker {
+    /// Enabled columns in their current order, followed by the rest of the
+    /// view's available columns
+    pub fn new(view: ResultsViewType, current: &[ColumnSpec]) -> Self {
+        let mut entries: Vec<PickerEntry> = current
+            .iter()
+            .map(|spec| PickerEntry {
+                spec: *spec,
+                enabled: true,
+            })
+            .collect();
+        for column in view.available_columns() {
+            if !current.iter().any(|s| s.column == *column) {
+                entries.push(PickerEntry {
+                    spec: ColumnSpec::new(*column),
+                    enabled: false,
+                });
+            }
+        }
+        Self {
+            view,
+            entries,
+            selected: 0,
+        }
+    }
+
+    /// Enabled columns, in order
+    pub fn specs(&self) -> Vec<ColumnSpec> {
+        self.entries
+            .iter()
+            .filter(|e| e.enabled)
+            .map(|e| e.spec)
+            .collect()
+    }
+
+    /// The `[columns]` line that reproduces this layout in `config.toml`
+    pub fn config_line(&self) -> String {
+        let specs: Vec<String> = self.specs().iter().map(|s| format!("\"{}\"", s)).collect();
+        format!("{} = [{}]", self.view.name(), specs.join(", "))
+    }
+
+    fn toggle(&mut self) {
+        let enabled_count = self.entries.iter().filter(|e| e.enabled).count();
+        let entry = &mut self.entries[self.selected];
+        // Keep at least one column visible
+        if !entry.enabled || enabled_count > 1 {
+            entry.enabled = !entry.enabled;
+        }
+    }
+
+    fn move_selected(&mut self, up: bool) {
+        let target = if up {
+            self.selected.checked_sub(1)
+        } else {
+            Some(self.selected + 1).filter(|&i| i < self.entries.len())
+        };
+        if let Some(target) = target {
+            self.entries.swap(self.selected, target);
+            self.selected = target;
+        }
+    }
+
+    fn resize(&mut self, delta: i16) {
+        let spec = &mut self.entries[self.selected].spec;
+        spec.width = spec.width.resized(delta);
+    }
+
+    /// Apply a key press; returns true if the layout changed
+    pub fn handle_key(&mut self, code: KeyCode) -> bool {
+        let len = self.entries.len();
+        match code {
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1) % len;
+                false
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = (self.selected + len - 1) % len;
+                false
+            }
+            KeyCode::Char(' ') => {
+                self.toggle();
+                true
+            }
+            KeyCode::Char('K') => {
+                self.move_selected(true);
+                true
+            }
+            KeyCode::Char('J') => {
+                self.move_selected(false);
+                true
+            }
+            KeyCode::Char('+') | KeyCode::Char('>') => {
+                self.resize(1);
+                true
+            }
+            KeyCode::Char('-') | KeyCode::Char('<') => {
+                self.resize(-1);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn specs(raw: &[&str]) -> Vec<ColumnSpec> {
+        raw.iter().map(|s| ColumnSpec::parse(s).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_parse_column_specs() {
+        assert_eq!(
+            ColumnSpec::parse("version").unwrap(),
+            ColumnSpec::new(Column::Version)
+        );
+        assert_eq!(
+            ColumnSpec::parse("tags:30%").unwrap().width,
+            ColumnWidth::Percentage(30)
+        );
+        assert_eq!(
+            ColumnSpec::parse("description:20+").unwrap().width,
+            ColumnWidth::Min(20)
+        );
+        assert_eq!(
+            ColumnSpec::parse(" ip : 16 ").unwrap().width,
+            ColumnWidth::Length(16)
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_specs() {
+        let err = ColumnSpec::parse("hostname").unwrap_err().to_string();
+        assert!(err.contains("unknown column 'hostname'"));
+        assert!(ColumnSpec::parse("tags:wide").is_err());
+        assert!(ColumnSpec::parse("tags:150%").is_err());
+    }
+
+    #[test]
+    fn test_spec_display_round_trips() {
+        for raw in ["id", "tags:30%", "version:12", "description:20+"] {
+            assert_eq!(ColumnSpec::parse(raw).unwrap().to_string(), raw);
+        }
+    }
+
+    #[test]
+    fn test_columns_config_from_toml() {
+        let config: ColumnsConfig =
+            toml::from_str(r#"workers = ["runner_id", "status:8", "platform"]"#).unwrap();
+        assert_eq!(
+            config.for_view(ResultsViewType::Workers),
+            specs(&["runner_id", "status:8", "platform"])
+        );
+        assert_eq!(
+            config.for_view(ResultsViewType::Runners).len(),
+            ResultsViewType::Runners.default_columns().len()
+        );
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn test_columns_config_rejects_unknown_column() {
+        let result: Result<ColumnsConfig, _> = toml::from_str(r#"runners = ["id", "nope"]"#);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_columns_from_other_views() {
+        let mut config = ColumnsConfig::default();
+        config.set(ResultsViewType::Runners, specs(&["id", "contacted"]));
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("columns.runners"));
+        assert!(err.contains("'contacted'"));
+
+        config.set(ResultsViewType::Runners, vec![]);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_fit_columns_drops_low_priority_first() {
+        let layout = specs(&["id", "type", "status", "version", "ip"]);
+        // id(10) type(15) status(10) version(10) ip(15) + 4 spaces = 64
+        assert_eq!(fit_columns(&layout, 80), vec![0, 1, 2, 3, 4]);
+        // Drops ip (priority 6), then type (5)
+        assert_eq!(fit_columns(&layout, 50), vec![0, 1, 2, 3]);
+        assert_eq!(fit_columns(&layout, 35), vec![0, 2, 3]);
+        // Always keeps at least one column
+        assert_eq!(fit_columns(&layout, 3), vec![0]);
+
+        // Widths adding up past u16::MAX still drop columns
+        let wide = specs(&["id:40000", "status:40000"]);
+        assert_eq!(fit_columns(&wide, 80), vec![0]);
+        assert_eq!(fit_columns(&wide, u16::MAX), vec![0]);
+    }
+
+    #[test]
+    fn test_picker_toggle_reorder_and_resize() {
+        let current = specs(&["id", "status"]);
+        let mut picker = ColumnPicker::new(ResultsViewType::Runners, &current);
+        assert_eq!(
+            picker.entries.len(),
+            ResultsViewType::Runners.available_columns().len()
+        );
+
+        // Move status above id and widen it
+        picker.handle_key(KeyCode::Down);
+        picker.handle_key(KeyCode::Char('K'));
+        picker.handle_key(KeyCode::Char('+'));
+        assert_eq!(picker.specs(), specs(&["status:11", "id"]));
+
+        // Enable the third entry (first disabled column)
+        picker.handle_key(KeyCode::Down);
+        picker.handle_key(KeyCode::Down);
+        picker.handle_key(KeyCode::Char(' '));
+        assert_eq!(picker.specs().len(), 3);
+        assert!(picker
+            .config_line()
+            .starts_with(r#"runners = ["status:11", "id", "#));
+    }
+
+    #[test]
+    fn test_picker_keeps_one_column() {
+        let mut picker = ColumnPicker::new(ResultsViewType::Workers, &specs(&["runner_id"]));
+        picker.handle_key(KeyCode::Char(' '));
+        assert_eq!(picker.specs(), specs(&["runner_id"]));
+    }
+}
@@ -1,4 +1,4 @@
-use crossterm::event::{Event as CrosstermEvent, KeyEvent};
+use crossterm::event::{Event as CrosstermEvent, KeyEvent, MouseEvent};
 use futures::StreamExt;
 use std::time::Duration;
 use tokio::sync::mpsc;
@@ -7,6 +7,13 @@ use tokio_util::sync::CancellationToken;
 #[derive(Clone, Debug)]
 pub enum Event {
     Key(KeyEvent),
+    Mouse(MouseEvent),
+    /// The terminal changed size; the next draw picks it up
+    Resize,
+    /// Bracketed paste, delivered whole rather than as key presses
+    Paste(String),
+    FocusGained,
+    FocusLost,
     Tick,
 }
 
@@ -30,10 +37,16 @@ impl EventHandler {
                         break;
                     }
                     Some(Ok(event)) = reader.next() => {
-                        if let CrosstermEvent::Key(key) = event {
-                            if sender.send(Event::Key(key)).is_err() {
-                                break;
-                            }
+                        let event = match event {
+                            CrosstermEvent::Key(key) => Event::Key(key),
+                            CrosstermEvent::Mouse(mouse) => Event::Mouse(mouse),
+                            CrosstermEvent::Resize(..) => Event::Resize,
+                            CrosstermEvent::Paste(text) => Event::Paste(text),
+                            CrosstermEvent::FocusGained => Event::FocusGained,
+                            CrosstermEvent::FocusLost => Event::FocusLost,
+                        };
+                        if sender.send(event).is_err() {
+                            break;
                         }
                     }
                     _ = tokio::time::sleep(tick_rate) => {
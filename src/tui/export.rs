@@ -0,0 +1,117 @@
+use crate::tui::table::Column;
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// File formats the palette exports results to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+
+    /// Rows under a header of column names; JSON is an array of objects
+    /// keyed by column name
+    pub fn render(self, columns: &[Column], rows: &[Vec<String>]) -> Result<String> {
+        match self {
+            ExportFormat::Csv => {
+                let mut out = String::new();
+                let header = columns.iter().map(|c| c.name().to_string());
+                for line in std::iter::once(header.collect::<Vec<_>>()).chain(rows.iter().cloned())
+                {
+                    let fields: Vec<String> = line.iter().map(|f| csv_field(f)).collect();
+                    out.push_str(&fields.join(","));
+                    out.push('\n');
+                }
+                Ok(out)
+            }
+            ExportFormat::Json => {
+                let objects: Vec<Value> = rows
+                    .iter()
+                    .map(|row| {
+                        let fields: Map<String, Value> = columns
+                            .iter()
+                            .zip(row)
+                            .map(|(c, cell)| (c.name().to_string(), Value::String(cell.clone())))
+                            .collect();
+                        Value::Object(fields)
+                    })
+                    .collect();
+                Ok(serde_json::to_string_pretty(&objects)? + "\n")
+            }
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportFormat::Csv => write!(f, "CSV"),
+            ExportFormat::Json => write!(f, "JSON"),
+        }
+    }
+}
+
+/// Quote a field holding a separator, quote or line break (RFC 4180)
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Write `rows` to `<dir>/<stem>.<extension>`, returning the path
+pub fn write(
+    dir: &Path,
+    stem: &str,
+    format: ExportFormat,
+    columns: &[Column],
+    rows: &[Vec<String>],
+) -> Result<PathBuf> {
+    let path = dir.join(format!("{}.{}", stem, format.extension()));
+    let contents = format.render(columns, rows)?;
+    std::fs::write(&path, contents)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows() -> Vec<Vec<String>> {
+        vec![
+            vec!["1".to_string(), "docker,linux".to_string()],
+            vec!["2".to_string(), "say \"hi\"".to_string()],
+        ]
+    }
+
+    #[test]
+    fn test_csv_quotes_fields_that_need_it() {
+        let csv = ExportFormat::Csv
+            .render(&[Column::Id, Column::Tags], &rows())
+            .unwrap();
+        assert_eq!(csv, "id,tags\n1,\"docker,linux\"\n2,\"say \"\"hi\"\"\"\n");
+    }
+
+    #[test]
+    fn test_json_keys_cells_by_column_name() {
+        let json = ExportFormat::Json
+            .render(&[Column::Id, Column::Tags], &rows())
+            .unwrap();
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value[0]["id"], "1");
+        assert_eq!(value[1]["tags"], "say \"hi\"");
+        assert_eq!(value.as_array().unwrap().len(), 2);
+    }
+}
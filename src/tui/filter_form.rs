@@ -0,0 +1,424 @@
+use crate::models::runner::{RunnerFilters, RunnerType};
+use crate::models::status::RunnerStatus;
+use crate::presets::Preset;
+use crate::query::{ParseError, Query};
+use crate::tui::app::Command;
+use crossterm::event::KeyCode;
+
+pub const STATUS_OPTIONS: &[&str] = &["online", "offline", "stale", "never_contacted"];
+pub const RUNNER_TYPE_OPTIONS: &[&str] = &["instance_type", "group_type", "project_type"];
+pub const PAUSED_OPTIONS: &[&str] = &["true", "false"];
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum FilterField {
+    #[default]
+    Tags,
+    Status,
+    VersionPrefix,
+    RunnerType,
+    Paused,
+    Where,
+}
+
+impl FilterField {
+    pub const ALL: &[FilterField] = &[
+        FilterField::Tags,
+        FilterField::Status,
+        FilterField::VersionPrefix,
+        FilterField::RunnerType,
+        FilterField::Paused,
+        FilterField::Where,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FilterField::Tags => "Tags",
+            FilterField::Status => "Status",
+            FilterField::VersionPrefix => "Version",
+            FilterField::RunnerType => "Type",
+            FilterField::Paused => "Paused",
+            FilterField::Where => "Where",
+        }
+    }
+
+    /// Options for picker fields, `None` for free-text fields
+    pub fn options(&self) -> Option<&'static [&'static str]> {
+        match self {
+            FilterField::Status => Some(STATUS_OPTIONS),
+            FilterField::RunnerType => Some(RUNNER_TYPE_OPTIONS),
+            FilterField::Paused => Some(PAUSED_OPTIONS),
+            FilterField::Tags | FilterField::VersionPrefix | FilterField::Where => None,
+        }
+    }
+}
+
+/// State of the multi-field filter form shown in `FilterInput` mode.
+///
+/// Picker fields store an index into their option list; `None` means "any".
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FilterForm {
+    pub focused: FilterField,
+    pub tags: String,
+    pub status: Option<usize>,
+    pub version_prefix: String,
+    pub runner_type: Option<usize>,
+    pub paused: Option<usize>,
+    /// Client-side filter expression, see [`crate::query`]
+    pub where_clause: String,
+    /// Parse error for `where_clause` from the last search attempt
+    pub error: Option<String>,
+}
+
+impl FilterForm {
+    pub fn clear(&mut self) {
+        *self = FilterForm::default();
+    }
+
+    pub fn next_field(&mut self) {
+        let i = self.focused_index();
+        self.focused = FilterField::ALL[(i + 1) % FilterField::ALL.len()];
+    }
+
+    pub fn previous_field(&mut self) {
+        let i = self.focused_index();
+        let len = FilterField::ALL.len();
+        self.focused = FilterField::ALL[(i + len - 1) % len];
+    }
+
+    fn focused_index(&self) -> usize {
+        FilterField::ALL
+            .iter()
+            .position(|f| *f == self.focused)
+            .unwrap_or(0)
+    }
+
+    /// Route a key to the focused field. Returns `true` if the key was consumed.
+    pub fn handle_key(&mut self, code: KeyCode) -> bool {
+        match code {
+            KeyCode::Tab | KeyCode::Down => self.next_field(),
+            KeyCode::BackTab | KeyCode::Up => self.previous_field(),
+            KeyCode::Right if self.focused.options().is_some() => self.cycle_option(true),
+            KeyCode::Left if self.focused.options().is_some() => self.cycle_option(false),
+            KeyCode::Char(' ') if self.focused.options().is_some() => self.cycle_option(true),
+            KeyCode::Backspace => match self.focused_text_mut() {
+                Some(text) => {
+                    text.pop();
+                }
+                None => *self.focused_picker_mut() = None,
+            },
+            KeyCode::Char(c) => match self.focused_text_mut() {
+                Some(text) => text.push(c),
+                None => return false,
+            },
+            _ => return false,
+        }
+        true
+    }
+
+    /// Insert pasted text into the focused text field in one go; line
+    /// breaks in a pasted tag list become commas
+    pub fn paste(&mut self, text: &str) -> bool {
+        let separator = if self.focused == FilterField::Tags {
+            ","
+        } else {
+            " "
+        };
+        let text = text
+            .split(['\r', '\n'])
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join(separator);
+        match self.focused_text_mut() {
+            Some(field) => {
+                field.push_str(&text);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn focused_text_mut(&mut self) -> Option<&mut String> {
+        match self.focused {
+            FilterField::Tags => Some(&mut self.tags),
+            FilterField::VersionPrefix => Some(&mut self.version_prefix),
+            FilterField::Where => Some(&mut self.where_clause),
+            _ => None,
+        }
+    }
+
+    fn focused_picker_mut(&mut self) -> &mut Option<usize> {
+        match self.focused {
+            FilterField::RunnerType => &mut self.runner_type,
+            FilterField::Paused => &mut self.paused,
+            _ => &mut self.status,
+        }
+    }
+
+    /// Cycle through "any" followed by each option of the focused picker
+    fn cycle_option(&mut self, forward: bool) {
+        let len = self.focused.options().map(|o| o.len()).unwrap_or(0);
+        if len == 0 {
+            return;
+        }
+        let picker = self.focused_picker_mut();
+        *picker = match (*picker, forward) {
+            (None, true) => Some(0),
+            (None, false) => Some(len - 1),
+            (Some(i), true) if i + 1 < len => Some(i + 1),
+            (Some(_), true) => None,
+            (Some(0), false) => None,
+            (Some(i), false) => Some(i - 1),
+        };
+    }
+
+    /// Display value for a field; pickers show "any" when unset
+    pub fn value(&self, field: FilterField) -> String {
+        let picked = |index: Option<usize>| {
+            index
+                .and_then(|i| field.options().and_then(|o| o.get(i)))
+                .unwrap_or(&"any")
+                .to_string()
+        };
+        match field {
+            FilterField::Tags => self.tags.clone(),
+            FilterField::VersionPrefix => self.version_prefix.clone(),
+            FilterField::Where => self.where_clause.clone(),
+            FilterField::Status => picked(self.status),
+            FilterField::RunnerType => picked(self.runner_type),
+            FilterField::Paused => picked(self.paused),
+        }
+    }
+
+    pub fn to_filters(&self) -> RunnerFilters {
+        let tags: Vec<String> = self
+            .tags
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+        let version_prefix = self.version_prefix.trim();
+
+        RunnerFilters {
+            tag_list: if tags.is_empty() { None } else { Some(tags) },
+            status: self.status.map(|i| RunnerStatus::from(STATUS_OPTIONS[i])),
+            version_prefix: if version_prefix.is_empty() {
+                None
+            } else {
+                Some(version_prefix.to_string())
+            },
+            runner_type: self
+                .runner_type
+                .map(|i| RunnerType::from(RUNNER_TYPE_OPTIONS[i])),
+            paused: self.paused.map(|i| PAUSED_OPTIONS[i] == "true"),
+        }
+    }
+
+    /// The form filled in from a preset's filters
+    pub fn from_preset(preset: &Preset) -> Self {
+        let index = |options: &[&str], value: Option<&str>| {
+            value.and_then(|v| options.iter().position(|o| *o == v))
+        };
+        Self {
+            tags: preset.tags.join(", "),
+            status: index(STATUS_OPTIONS, preset.status.as_deref()),
+            version_prefix: preset.version_prefix.clone().unwrap_or_default(),
+            runner_type: index(RUNNER_TYPE_OPTIONS, preset.runner_type.as_deref()),
+            paused: preset
+                .paused
+                .and_then(|p| index(PAUSED_OPTIONS, Some(&p.to_string()))),
+            where_clause: preset.where_clause.clone().unwrap_or_default(),
+            ..Self::default()
+        }
+    }
+
+    /// A preset running `command` with this form's filters; sort and
+    /// columns are left for the caller
+    pub fn to_preset(&self, name: &str, command: Command) -> Preset {
+        let text = |value: &str| {
+            let value = value.trim();
+            (!value.is_empty()).then(|| value.to_string())
+        };
+        let picked = |options: &[&str], index: Option<usize>| {
+            index.and_then(|i| options.get(i)).map(|o| o.to_string())
+        };
+        Preset {
+            name: name.to_string(),
+            command,
+            tags: self.to_filters().tag_list.unwrap_or_default(),
+            status: picked(STATUS_OPTIONS, self.status),
+            version_prefix: text(&self.version_prefix),
+            runner_type: picked(RUNNER_TYPE_OPTIONS, self.runner_type),
+            paused: self.paused.map(|i| PAUSED_OPTIONS[i] == "true"),
+            where_clause: text(&self.where_clause),
+            sort: None,
+            descending: false,
+            columns: None,
+        }
+    }
+
+    /// Parse the `Where` field; an empty field means no client-side filter
+    pub fn query(&self) -> Result<Option<Query>, ParseError> {
+        if self.where_clause.trim().is_empty() {
+            Ok(None)
+        } else {
+            Query::parse(&self.where_clause).map(Some)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_form_produces_default_filters() {
+        let form = FilterForm::default();
+        assert_eq!(form.to_filters(), RunnerFilters::default());
+    }
+
+    #[test]
+    fn test_tab_cycles_through_all_fields() {
+        let mut form = FilterForm::default();
+        for expected in FilterField::ALL.iter().skip(1) {
+            form.handle_key(KeyCode::Tab);
+            assert_eq!(form.focused, *expected);
+        }
+        form.handle_key(KeyCode::Tab);
+        assert_eq!(form.focused, FilterField::Tags);
+
+        form.handle_key(KeyCode::BackTab);
+        assert_eq!(form.focused, FilterField::Where);
+    }
+
+    #[test]
+    fn test_typing_goes_to_focused_text_field() {
+        let mut form = FilterForm::default();
+        for c in "alm, prod".chars() {
+            form.handle_key(KeyCode::Char(c));
+        }
+        form.focused = FilterField::VersionPrefix;
+        for c in "17.".chars() {
+            form.handle_key(KeyCode::Char(c));
+        }
+        form.handle_key(KeyCode::Backspace);
+
+        let filters = form.to_filters();
+        assert_eq!(
+            filters.tag_list,
+            Some(vec!["alm".to_string(), "prod".to_string()])
+        );
+        assert_eq!(filters.version_prefix, Some("17".to_string()));
+    }
+
+    #[test]
+    fn test_paste_goes_to_focused_text_field() {
+        let mut form = FilterForm::default();
+        assert!(form.paste("alm\nprod\r\n\rlinux\n"));
+        assert_eq!(form.value(FilterField::Tags), "alm,prod,linux");
+
+        form.focused = FilterField::Where;
+        form.paste("status = online\nand managers > 1");
+        assert_eq!(form.where_clause, "status = online and managers > 1");
+
+        form.focused = FilterField::Status;
+        assert!(!form.paste("online"));
+        assert_eq!(form.value(FilterField::Status), "any");
+    }
+
+    #[test]
+    fn test_picker_cycles_through_any_and_options() {
+        let mut form = FilterForm {
+            focused: FilterField::Status,
+            ..Default::default()
+        };
+
+        form.handle_key(KeyCode::Right);
+        assert_eq!(form.value(FilterField::Status), "online");
+        form.handle_key(KeyCode::Right);
+        assert_eq!(form.value(FilterField::Status), "offline");
+
+        form.handle_key(KeyCode::Left);
+        form.handle_key(KeyCode::Left);
+        assert_eq!(form.value(FilterField::Status), "any");
+
+        form.handle_key(KeyCode::Left);
+        assert_eq!(form.value(FilterField::Status), "never_contacted");
+    }
+
+    #[test]
+    fn test_where_field_parses_query() {
+        let mut form = FilterForm::default();
+        assert_eq!(form.query(), Ok(None));
+
+        form.where_clause = "managers > 1".to_string();
+        assert_eq!(form.query().unwrap().unwrap().source(), "managers > 1");
+
+        form.where_clause = "managers >".to_string();
+        assert!(form.query().is_err());
+    }
+
+    #[test]
+    fn test_picker_ignores_text_input() {
+        let mut form = FilterForm {
+            focused: FilterField::RunnerType,
+            ..Default::default()
+        };
+        assert!(!form.handle_key(KeyCode::Char('x')));
+        assert!(form.runner_type.is_none());
+    }
+
+    #[test]
+    fn test_backspace_resets_picker_to_any() {
+        let mut form = FilterForm {
+            focused: FilterField::Paused,
+            paused: Some(0),
+            ..Default::default()
+        };
+        form.handle_key(KeyCode::Backspace);
+        assert!(form.paused.is_none());
+    }
+
+    #[test]
+    fn test_all_fields_map_to_filters() {
+        let form = FilterForm {
+            focused: FilterField::Tags,
+            tags: "docker".to_string(),
+            status: Some(1),
+            version_prefix: " 16.11 ".to_string(),
+            runner_type: Some(0),
+            paused: Some(1),
+            ..Default::default()
+        };
+
+        let filters = form.to_filters();
+        assert_eq!(filters.tag_list, Some(vec!["docker".to_string()]));
+        assert_eq!(filters.status, Some(RunnerStatus::Offline));
+        assert_eq!(filters.version_prefix, Some("16.11".to_string()));
+        assert_eq!(filters.runner_type, Some(RunnerType::InstanceType));
+        assert_eq!(filters.paused, Some(false));
+    }
+
+    #[test]
+    fn test_preset_round_trip() {
+        let form = FilterForm {
+            tags: "docker, arm64".to_string(),
+            status: Some(0),
+            runner_type: Some(2),
+            paused: Some(0),
+            where_clause: "version < 17.0".to_string(),
+            ..Default::default()
+        };
+        let preset = form.to_preset("arm", Command::Lights);
+        assert_eq!(preset.tags, vec!["docker".to_string(), "arm64".to_string()]);
+        assert_eq!(preset.status.as_deref(), Some("online"));
+        assert_eq!(preset.runner_type.as_deref(), Some("project_type"));
+        assert_eq!(preset.paused, Some(true));
+        assert!(preset.version_prefix.is_none());
+
+        let restored = FilterForm::from_preset(&preset);
+        assert_eq!(restored.tags, "docker, arm64");
+        assert_eq!(restored.to_filters(), form.to_filters());
+        assert_eq!(restored.where_clause, form.where_clause);
+    }
+}
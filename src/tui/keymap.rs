@@ -0,0 +1,539 @@
+use crate::tui::app::AppMode;
+use anyhow::{anyhow, bail, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Something a key does on the command list or over results. Text input
+/// (filter form, search, palette, preset names) is not remappable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Up,
+    Down,
+    Select,
+    Back,
+    Help,
+    Quit,
+    Palette,
+    ToggleAlerts,
+    Presets,
+    TogglePolling,
+    Search,
+    NextMatch,
+    PreviousMatch,
+    ColumnLeft,
+    ColumnRight,
+    Sort,
+    Columns,
+    Timeline,
+    SavePreset,
+}
+
+/// Where an action's keys are live
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// Command list and results
+    Global,
+    Commands,
+    Results,
+}
+
+impl Scope {
+    fn applies(self, mode: AppMode) -> bool {
+        match self {
+            Scope::Global => matches!(mode, AppMode::CommandSelection | AppMode::ResultsView),
+            Scope::Commands => mode == AppMode::CommandSelection,
+            Scope::Results => mode == AppMode::ResultsView,
+        }
+    }
+
+    fn overlaps(self, other: Scope) -> bool {
+        self == other || self == Scope::Global || other == Scope::Global
+    }
+}
+
+impl Action {
+    pub const ALL: &[Action] = &[
+        Action::Up,
+        Action::Down,
+        Action::Select,
+        Action::Back,
+        Action::Help,
+        Action::Quit,
+        Action::Palette,
+        Action::ToggleAlerts,
+        Action::Presets,
+        Action::TogglePolling,
+        Action::Search,
+        Action::NextMatch,
+        Action::PreviousMatch,
+        Action::ColumnLeft,
+        Action::ColumnRight,
+        Action::Sort,
+        Action::Columns,
+        Action::Timeline,
+        Action::SavePreset,
+    ];
+
+    /// Name used in `[keys.bind]`
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Select => "select",
+            Action::Back => "back",
+            Action::Help => "help",
+            Action::Quit => "quit",
+            Action::Palette => "palette",
+            Action::ToggleAlerts => "toggle_alerts",
+            Action::Presets => "presets",
+            Action::TogglePolling => "toggle_polling",
+            Action::Search => "search",
+            Action::NextMatch => "next_match",
+            Action::PreviousMatch => "previous_match",
+            Action::ColumnLeft => "column_left",
+            Action::ColumnRight => "column_right",
+            Action::Sort => "sort",
+            Action::Columns => "columns",
+            Action::Timeline => "timeline",
+            Action::SavePreset => "save_preset",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Up => "Previous command / scroll results up",
+            Action::Down => "Next command / scroll results down",
+            Action::Select => "Select command",
+            Action::Back => "Clear the search, go back, or quit from the command list",
+            Action::Help => "Show this help",
+            Action::Quit => "Quit application",
+            Action::Palette => "Command palette: fuzzy search commands, presets and actions",
+            Action::ToggleAlerts => "Show / hide the alerts panel ([[alerts]] rules)",
+            Action::Presets => "Run a saved preset ([[presets]] in config.toml)",
+            Action::TogglePolling => "Toggle auto-refresh polling",
+            Action::Search => "Fuzzy search visible columns (Enter keeps, Esc clears)",
+            Action::NextMatch => "Jump to the next match",
+            Action::PreviousMatch => "Jump to the previous match",
+            Action::ColumnLeft => "Move the column cursor left",
+            Action::ColumnRight => "Move the column cursor right",
+            Action::Sort => "Sort by the column under the cursor (again to reverse)",
+            Action::Columns => "Choose, reorder and resize columns",
+            Action::Timeline => "Manager timeline of the selected runner (rotate)",
+            Action::SavePreset => "Save the command, filters, sort and columns as a preset",
+        }
+    }
+
+    pub fn scope(self) -> Scope {
+        match self {
+            Action::Up
+            | Action::Down
+            | Action::Back
+            | Action::Help
+            | Action::Quit
+            | Action::Palette
+            | Action::ToggleAlerts => Scope::Global,
+            Action::Select | Action::Presets => Scope::Commands,
+            _ => Scope::Results,
+        }
+    }
+}
+
+/// A key and its modifiers, written like `q`, `ctrl+p`, `enter` or `f5`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    pub fn parse(raw: &str) -> Result<Self> {
+        // "+" is a key on its own or after a modifier ("ctrl++")
+        let (prefix, key) = match raw.strip_suffix("++") {
+            Some(prefix) => (prefix, "+"),
+            None => match raw.rsplit_once('+') {
+                Some((prefix, key)) if !key.is_empty() => (prefix, key),
+                _ => ("", raw),
+            },
+        };
+        let mut modifiers = KeyModifiers::NONE;
+        for part in prefix.split('+').filter(|part| !part.is_empty()) {
+            modifiers |= match part.to_ascii_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => bail!("unknown modifier '{}' in '{}'", part, raw),
+            };
+        }
+
+        // Characters carry their own shift, so `shift+n` is stored as `N`
+        // and compares equal to it; terminals send Ctrl+letters lowercase
+        let mut chars = key.chars();
+        let shift = modifiers.contains(KeyModifiers::SHIFT);
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) if modifiers.contains(KeyModifiers::CONTROL) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::Char(c.to_ascii_lowercase())
+            }
+            (Some(c), None) if shift && c.is_ascii_alphabetic() => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::Char(c.to_ascii_uppercase())
+            }
+            (Some(_), None) if shift => {
+                bail!("write the shifted character instead of '{}'", raw)
+            }
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_ascii_lowercase().as_str() {
+                "enter" => KeyCode::Enter,
+                "esc" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backspace" => KeyCode::Backspace,
+                "space" => KeyCode::Char(' '),
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "delete" => KeyCode::Delete,
+                name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => bail!("unknown key '{}'", raw),
+                },
+            },
+        };
+        Ok(Self { code, modifiers })
+    }
+
+    /// Characters carry their own shift (`N`, `?`), so only Ctrl and Alt
+    /// are compared for them
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        let compared = match self.code {
+            KeyCode::Char(_) => KeyModifiers::CONTROL | KeyModifiers::ALT,
+            _ => KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT,
+        };
+        self.code == key.code && self.modifiers & compared == key.modifiers & compared
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "Ctrl+"),
+            (KeyModifiers::ALT, "Alt+"),
+            (KeyModifiers::SHIFT, "Shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}", name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) if self.modifiers.contains(KeyModifiers::CONTROL) => {
+                write!(f, "{}", c.to_ascii_uppercase())
+            }
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::PageUp => write!(f, "PgUp"),
+            KeyCode::PageDown => write!(f, "PgDn"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+/// Built-in layouts that `[keys.bind]` adjusts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyPreset {
+    /// `j`/`k`/`h`/`l` alongside the arrows, single letters for everything
+    #[default]
+    Vim,
+    /// Arrows, F-keys and Ctrl combos only, so stray letters (or a tmux
+    /// prefix typed twice) do nothing
+    Arrows,
+}
+
+impl KeyPreset {
+    fn keys(self, action: Action) -> &'static [&'static str] {
+        match (self, action) {
+            (KeyPreset::Vim, Action::Up) => &["up", "k"],
+            (KeyPreset::Vim, Action::Down) => &["down", "j"],
+            (KeyPreset::Vim, Action::ColumnLeft) => &["left", "h"],
+            (KeyPreset::Vim, Action::ColumnRight) => &["right", "l"],
+            (KeyPreset::Vim, Action::Help) => &["?"],
+            (KeyPreset::Vim, Action::Quit) => &["q"],
+            (KeyPreset::Vim, Action::Palette) => &[":", "ctrl+p"],
+            (KeyPreset::Vim, Action::ToggleAlerts) => &["a"],
+            (KeyPreset::Vim, Action::Presets) => &["P"],
+            (KeyPreset::Vim, Action::TogglePolling) => &["p"],
+            (KeyPreset::Vim, Action::Search) => &["/"],
+            (KeyPreset::Vim, Action::NextMatch) => &["n"],
+            (KeyPreset::Vim, Action::PreviousMatch) => &["N"],
+            (KeyPreset::Vim, Action::Sort) => &["s"],
+            (KeyPreset::Vim, Action::Columns) => &["c"],
+            (KeyPreset::Vim, Action::Timeline) => &["t"],
+            (KeyPreset::Vim, Action::SavePreset) => &["S"],
+            (KeyPreset::Arrows, Action::Up) => &["up"],
+            (KeyPreset::Arrows, Action::Down) => &["down"],
+            (KeyPreset::Arrows, Action::ColumnLeft) => &["left"],
+            (KeyPreset::Arrows, Action::ColumnRight) => &["right"],
+            (KeyPreset::Arrows, Action::Help) => &["f1"],
+            (KeyPreset::Arrows, Action::Quit) => &["ctrl+q"],
+            (KeyPreset::Arrows, Action::Palette) => &["ctrl+p"],
+            (KeyPreset::Arrows, Action::ToggleAlerts) => &["f2"],
+            (KeyPreset::Arrows, Action::Presets) => &["f4"],
+            (KeyPreset::Arrows, Action::TogglePolling) => &["f5"],
+            (KeyPreset::Arrows, Action::Search) => &["ctrl+f"],
+            (KeyPreset::Arrows, Action::NextMatch) => &["f3"],
+            (KeyPreset::Arrows, Action::PreviousMatch) => &["shift+f3"],
+            (KeyPreset::Arrows, Action::Sort) => &["f6"],
+            (KeyPreset::Arrows, Action::Columns) => &["f7"],
+            (KeyPreset::Arrows, Action::Timeline) => &["f8"],
+            (KeyPreset::Arrows, Action::SavePreset) => &["f9"],
+            (_, Action::Select) => &["enter"],
+            (_, Action::Back) => &["esc"],
+        }
+    }
+}
+
+/// One key or several for an action in `[keys.bind]`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum KeyList {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyList {
+    fn keys(&self) -> Vec<&str> {
+        match self {
+            KeyList::One(key) => vec![key.as_str()],
+            KeyList::Many(keys) => keys.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+/// The `[keys]` table of `config.toml`: a preset, and per-action
+/// replacements for its keys
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeysConfig {
+    pub preset: KeyPreset,
+    pub bind: BTreeMap<Action, KeyList>,
+}
+
+impl KeysConfig {
+    /// The active keymap; unknown keys, an unbound `quit` and keys bound
+    /// to two actions live at the same time are errors
+    pub fn keymap(&self) -> Result<Keymap> {
+        let mut bindings = Vec::new();
+        for &action in Action::ALL {
+            let keys = match self.bind.get(&action) {
+                Some(list) => list.keys(),
+                None => self.preset.keys(action).to_vec(),
+            };
+            let keys = keys
+                .into_iter()
+                .map(KeyBinding::parse)
+                .collect::<Result<Vec<_>>>()
+                .map_err(|e| anyhow!("keys.bind.{}: {}", action.name(), e))?;
+            if action == Action::Quit && keys.is_empty() {
+                bail!("keys.bind.quit: at least one key is required");
+            }
+            bindings.push((action, keys));
+        }
+
+        for (i, (action, keys)) in bindings.iter().enumerate() {
+            for (other, other_keys) in &bindings[i + 1..] {
+                if !action.scope().overlaps(other.scope()) {
+                    continue;
+                }
+                if let Some(key) = keys.iter().find(|key| other_keys.contains(key)) {
+                    bail!(
+                        "keys: {} is bound to both {} and {}",
+                        key,
+                        action.name(),
+                        other.name()
+                    );
+                }
+            }
+        }
+        Ok(Keymap { bindings })
+    }
+}
+
+/// Keys for every action, resolved from a preset and `[keys.bind]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keymap {
+    bindings: Vec<(Action, Vec<KeyBinding>)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        KeysConfig::default()
+            .keymap()
+            .expect("built-in keymap is valid")
+    }
+}
+
+impl Keymap {
+    /// The action `key` triggers in `mode`
+    pub fn action(&self, mode: AppMode, key: &KeyEvent) -> Option<Action> {
+        self.bindings
+            .iter()
+            .filter(|(action, _)| action.scope().applies(mode))
+            .find(|(_, keys)| keys.iter().any(|binding| binding.matches(key)))
+            .map(|(action, _)| *action)
+    }
+
+    /// Whether `key` is bound to `action`, wherever it is pressed
+    pub fn is(&self, action: Action, key: &KeyEvent) -> bool {
+        self.keys(action).iter().any(|binding| binding.matches(key))
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyBinding] {
+        self.bindings
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map(|(_, keys)| keys.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// The first key of `action`, for the status bar
+    pub fn hint(&self, action: Action) -> String {
+        self.keys(action)
+            .first()
+            .map(ToString::to_string)
+            .unwrap_or_else(|| "-".to_string())
+    }
+
+    /// Every key of `action`, for the help screen
+    pub fn describe(&self, action: Action) -> String {
+        let keys: Vec<String> = self.keys(action).iter().map(ToString::to_string).collect();
+        if keys.is_empty() {
+            "(unbound)".to_string()
+        } else {
+            keys.join(" / ")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keymap(toml_str: &str) -> Result<Keymap> {
+        let config: KeysConfig = toml::from_str(toml_str)?;
+        config.keymap()
+    }
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_parses_and_displays_keys() {
+        let ctrl_p = KeyBinding::parse("Ctrl+P").unwrap();
+        assert_eq!(ctrl_p.code, KeyCode::Char('p'));
+        assert_eq!(ctrl_p.to_string(), "Ctrl+P");
+        assert!(ctrl_p.matches(&key(KeyCode::Char('p'), KeyModifiers::CONTROL)));
+        assert!(!ctrl_p.matches(&key(KeyCode::Char('p'), KeyModifiers::NONE)));
+
+        let upper = KeyBinding::parse("N").unwrap();
+        assert!(upper.matches(&key(KeyCode::Char('N'), KeyModifiers::SHIFT)));
+        assert_eq!(KeyBinding::parse("f5").unwrap().code, KeyCode::F(5));
+        assert_eq!(KeyBinding::parse("+").unwrap().code, KeyCode::Char('+'));
+        assert_eq!(KeyBinding::parse("pageup").unwrap().to_string(), "PgUp");
+        assert!(KeyBinding::parse("hyper+x").is_err());
+        assert!(KeyBinding::parse("shift+1").is_err());
+        assert!(KeyBinding::parse("f13").is_err());
+    }
+
+    #[test]
+    fn test_presets_and_overrides() {
+        let vim = Keymap::default();
+        let j = key(KeyCode::Char('j'), KeyModifiers::NONE);
+        assert_eq!(vim.action(AppMode::ResultsView, &j), Some(Action::Down));
+
+        let arrows =
+            keymap("preset = \"arrows\"\n[bind]\ntoggle_polling = [\"r\", \"f5\"]").unwrap();
+        assert_eq!(arrows.action(AppMode::ResultsView, &j), None);
+        let q = key(KeyCode::Char('q'), KeyModifiers::CONTROL);
+        assert_eq!(
+            arrows.action(AppMode::CommandSelection, &q),
+            Some(Action::Quit)
+        );
+        let r = key(KeyCode::Char('r'), KeyModifiers::NONE);
+        assert_eq!(
+            arrows.action(AppMode::ResultsView, &r),
+            Some(Action::TogglePolling)
+        );
+        assert_eq!(arrows.action(AppMode::CommandSelection, &r), None);
+        assert_eq!(arrows.describe(Action::TogglePolling), "r / F5");
+    }
+
+    #[test]
+    fn test_arrows_binds_no_plain_characters() {
+        let arrows = keymap("preset = \"arrows\"").unwrap();
+        for &action in Action::ALL {
+            assert!(
+                arrows
+                    .keys(action)
+                    .iter()
+                    .all(|b| !matches!(b.code, KeyCode::Char(_)) || !b.modifiers.is_empty()),
+                "{} has a plain character key",
+                action.name()
+            );
+        }
+        let shift_f3 = key(KeyCode::F(3), KeyModifiers::SHIFT);
+        assert_eq!(
+            arrows.action(AppMode::ResultsView, &shift_f3),
+            Some(Action::PreviousMatch)
+        );
+        assert_eq!(arrows.describe(Action::PreviousMatch), "Shift+F3");
+    }
+
+    #[test]
+    fn test_shift_letters_are_the_uppercase_letter() {
+        let shift_n = KeyBinding::parse("shift+n").unwrap();
+        assert_eq!(shift_n, KeyBinding::parse("N").unwrap());
+        assert_eq!(shift_n.to_string(), "N");
+        assert!(shift_n.matches(&key(KeyCode::Char('N'), KeyModifiers::SHIFT)));
+        assert!(!shift_n.matches(&key(KeyCode::Char('n'), KeyModifiers::NONE)));
+        assert_eq!(
+            KeyBinding::parse("ctrl+shift+p").unwrap(),
+            KeyBinding::parse("ctrl+p").unwrap()
+        );
+
+        let err = keymap("[bind]\nsort = \"shift+n\"").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "keys: N is bound to both previous_match and sort"
+        );
+    }
+
+    #[test]
+    fn test_detects_conflicts_within_a_scope() {
+        let err = keymap("[bind]\nsort = \"q\"").unwrap_err();
+        assert_eq!(err.to_string(), "keys: q is bound to both quit and sort");
+
+        // select only applies to the command list, search only to results
+        assert!(keymap("[bind]\nselect = [\"enter\", \"/\"]").is_ok());
+
+        let err = keymap("[bind]\nquit = []").unwrap_err();
+        assert!(err.to_string().starts_with("keys.bind.quit: "));
+        let err = keymap("[bind]\nhelp = \"meta+h\"").unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("keys.bind.help: unknown modifier"));
+        assert!(keymap("[bind]\nteleport = \"x\"").is_err());
+    }
+}
@@ -1,3 +1,12 @@
 pub mod app;
+pub mod columns;
 pub mod event;
+pub mod export;
+pub mod filter_form;
+pub mod keymap;
+pub mod mouse;
+pub mod palette;
+pub mod search;
+pub mod sort;
+pub mod table;
 pub mod ui;
@@ -0,0 +1,107 @@
+use ratatui::layout::{Constraint, Flex, Layout, Position, Rect};
+
+/// Where the last frame drew the clickable parts of the screen, so mouse
+/// events can be mapped back to commands, rows and columns
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClickAreas {
+    pub commands: Option<ListArea>,
+    pub table: Option<TableArea>,
+}
+
+/// Rows of a list inside its borders, scrolled by `offset`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ListArea {
+    pub rows: Rect,
+    pub offset: usize,
+}
+
+impl ListArea {
+    /// Index of the item drawn at `(column, row)`
+    pub fn item_at(&self, column: u16, row: u16) -> Option<usize> {
+        self.rows
+            .contains(Position::new(column, row))
+            .then(|| self.offset + (row - self.rows.y) as usize)
+    }
+}
+
+/// A results table: its header row, body rows and the span of each shown
+/// column as `(index into the layout, x, width)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableArea {
+    pub header: Rect,
+    pub body: ListArea,
+    pub columns: Vec<(usize, u16, u16)>,
+}
+
+impl TableArea {
+    /// Lay out `kept` columns with `constraints` the way `Table` does:
+    /// from the left, one cell apart, inside the block's borders
+    pub fn new(area: Rect, kept: &[usize], constraints: Vec<Constraint>, offset: usize) -> Self {
+        let inner = Rect {
+            x: area.x + 1,
+            y: area.y + 1,
+            width: area.width.saturating_sub(2),
+            height: area.height.saturating_sub(2),
+        };
+        let rects = Layout::horizontal(constraints)
+            .flex(Flex::Start)
+            .spacing(1)
+            .split(inner);
+        let header = Rect {
+            height: inner.height.min(1),
+            ..inner
+        };
+        let rows = Rect {
+            y: inner.y + header.height,
+            height: inner.height - header.height,
+            ..inner
+        };
+        Self {
+            header,
+            body: ListArea { rows, offset },
+            columns: kept
+                .iter()
+                .zip(rects.iter())
+                .map(|(&index, rect)| (index, rect.x, rect.width))
+                .collect(),
+        }
+    }
+
+    /// Column whose header is at `(column, row)`
+    pub fn header_at(&self, column: u16, row: u16) -> Option<usize> {
+        if !self.header.contains(Position::new(column, row)) {
+            return None;
+        }
+        self.columns
+            .iter()
+            .find(|(_, x, width)| (*x..x + width).contains(&column))
+            .map(|(index, _, _)| *index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_maps_clicks_to_headers_and_rows() {
+        // Bordered table at (0, 5), two columns: 0..6 and 7..17 inside
+        let area = Rect::new(0, 5, 40, 10);
+        let table = TableArea::new(
+            area,
+            &[0, 2],
+            vec![Constraint::Length(6), Constraint::Length(10)],
+            3,
+        );
+        assert_eq!(table.header_at(1, 6), Some(0));
+        assert_eq!(table.header_at(7, 6), None);
+        assert_eq!(table.header_at(8, 6), Some(2));
+        assert_eq!(table.header_at(30, 6), None);
+        assert_eq!(table.header_at(1, 7), None);
+
+        assert_eq!(table.body.item_at(1, 7), Some(3));
+        assert_eq!(table.body.item_at(1, 13), Some(9));
+        assert_eq!(table.body.item_at(1, 14), None);
+        assert_eq!(table.body.item_at(0, 7), None);
+    }
+}
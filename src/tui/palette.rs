@@ -0,0 +1,284 @@
+use crate::tui::app::{AppMode, Command};
+use crate::tui::export::ExportFormat;
+use crate::tui::search::fuzzy_indices;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Recently used palette entries remembered for ordering
+const MAX_RECENT: usize = 20;
+
+/// Something the command palette can run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaletteAction {
+    /// Open the filter form for a command
+    Command(Command),
+    /// Run a saved preset by name
+    Preset(String),
+    /// Connect to a `[profiles]` entry by name
+    Profile(String),
+    /// Pause the selected runner, or resume it if paused
+    PauseRunner,
+    /// Write the current results to a file
+    Export(ExportFormat),
+    TogglePolling,
+    Search,
+    Columns,
+    Timeline,
+    SavePreset,
+    ToggleAlerts,
+    Presets,
+    Help,
+    Quit,
+}
+
+impl PaletteAction {
+    /// Stable identity, so recents survive the palette being rebuilt
+    fn id(&self) -> String {
+        match self {
+            PaletteAction::Command(command) => format!("command:{}", command),
+            PaletteAction::Preset(name) => format!("preset:{}", name),
+            PaletteAction::Profile(name) => format!("profile:{}", name),
+            action => format!("{:?}", action),
+        }
+    }
+}
+
+/// One row of the palette
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaletteEntry {
+    pub action: PaletteAction,
+    pub title: String,
+    pub description: String,
+}
+
+impl PaletteEntry {
+    pub fn new(
+        action: PaletteAction,
+        title: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Self {
+        Self {
+            action,
+            title: title.into(),
+            description: description.into(),
+        }
+    }
+}
+
+/// Most recently used first
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RecentActions(Vec<String>);
+
+impl RecentActions {
+    pub fn record(&mut self, action: &PaletteAction) {
+        let id = action.id();
+        self.0.retain(|recent| *recent != id);
+        self.0.insert(0, id);
+        self.0.truncate(MAX_RECENT);
+    }
+
+    fn rank(&self, action: &PaletteAction) -> usize {
+        let id = action.id();
+        self.0
+            .iter()
+            .position(|recent| *recent == id)
+            .unwrap_or(usize::MAX)
+    }
+}
+
+/// Fuzzy search over every command, preset and action available where the
+/// palette was opened
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    pub query: String,
+    /// Index into `matches`
+    pub selected: usize,
+    /// Entries matching the query, best first
+    pub matches: Vec<PaletteEntry>,
+    /// Mode to return to when the palette closes
+    pub previous: AppMode,
+    entries: Vec<PaletteEntry>,
+    recent: RecentActions,
+}
+
+/// What a key did to the palette
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaletteOutcome {
+    Open,
+    Cancel,
+    Run(PaletteAction),
+}
+
+impl Palette {
+    pub fn new(entries: Vec<PaletteEntry>, recent: RecentActions, previous: AppMode) -> Self {
+        let mut palette = Self {
+            query: String::new(),
+            selected: 0,
+            matches: Vec::new(),
+            previous,
+            entries,
+            recent,
+        };
+        palette.refresh();
+        palette
+    }
+
+    /// Re-rank the entries: a title starting with the query beats one
+    /// containing it, which beats a scattered or description-only match;
+    /// ties go to the most recently used, then to the original order
+    fn refresh(&mut self) {
+        let query = self.query.trim();
+        let mut ranked: Vec<(usize, usize, usize, &PaletteEntry)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, entry)| {
+                let quality = if query.is_empty() {
+                    0
+                } else {
+                    match fuzzy_indices(query, &entry.title) {
+                        Some(indices) if indices.first() == Some(&0) && is_run(&indices) => 0,
+                        Some(indices) if is_run(&indices) => 1,
+                        Some(_) => 2,
+                        None => {
+                            fuzzy_indices(query, &entry.description)?;
+                            3
+                        }
+                    }
+                };
+                Some((quality, self.recent.rank(&entry.action), i, entry))
+            })
+            .collect();
+        ranked.sort_by_key(|(quality, recent, i, _)| (*quality, *recent, *i));
+        self.matches = ranked
+            .into_iter()
+            .map(|(_, _, _, entry)| entry.clone())
+            .collect();
+        self.selected = 0;
+    }
+
+    pub fn paste(&mut self, text: &str) {
+        self.query.push_str(text);
+        self.refresh();
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> PaletteOutcome {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let count = self.matches.len().max(1);
+        match key.code {
+            KeyCode::Esc => return PaletteOutcome::Cancel,
+            KeyCode::Enter => {
+                return match self.matches.get(self.selected) {
+                    Some(entry) => PaletteOutcome::Run(entry.action.clone()),
+                    None => PaletteOutcome::Open,
+                }
+            }
+            KeyCode::Up => self.selected = (self.selected + count - 1) % count,
+            KeyCode::Down | KeyCode::Tab => self.selected = (self.selected + 1) % count,
+            KeyCode::Char('p') if ctrl => self.selected = (self.selected + count - 1) % count,
+            KeyCode::Char('n') if ctrl => self.selected = (self.selected + 1) % count,
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.refresh();
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.query.push(c);
+                self.refresh();
+            }
+            _ => {}
+        }
+        PaletteOutcome::Open
+    }
+}
+
+/// Whether matched indices are one contiguous run
+fn is_run(indices: &[usize]) -> bool {
+    indices.windows(2).all(|pair| pair[1] == pair[0] + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries() -> Vec<PaletteEntry> {
+        vec![
+            PaletteEntry::new(
+                PaletteAction::Command(Command::Fetch),
+                "fetch",
+                "All runners matching the filters",
+            ),
+            PaletteEntry::new(
+                PaletteAction::Command(Command::Flames),
+                "flames",
+                "Runners not contacted in the last hour",
+            ),
+            PaletteEntry::new(
+                PaletteAction::TogglePolling,
+                "Toggle polling",
+                "Refresh the results every poll interval",
+            ),
+            PaletteEntry::new(
+                PaletteAction::Preset("prod-linux".to_string()),
+                "Preset: prod-linux",
+                "lights tags=production,linux",
+            ),
+        ]
+    }
+
+    fn titles(palette: &Palette) -> Vec<&str> {
+        palette.matches.iter().map(|e| e.title.as_str()).collect()
+    }
+
+    fn type_query(palette: &mut Palette, query: &str) {
+        for c in query.chars() {
+            palette.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+    }
+
+    #[test]
+    fn test_ranks_prefix_then_substring_then_description() {
+        let mut palette = Palette::new(entries(), RecentActions::default(), AppMode::default());
+        type_query(&mut palette, "ol");
+        // In "polling", scattered over "prod-linux", only in flames' description
+        assert_eq!(
+            titles(&palette),
+            vec!["Toggle polling", "Preset: prod-linux", "flames"]
+        );
+
+        palette.query.clear();
+        type_query(&mut palette, "f");
+        assert_eq!(titles(&palette), vec!["fetch", "flames", "Toggle polling"]);
+    }
+
+    #[test]
+    fn test_recently_used_come_first() {
+        let mut recent = RecentActions::default();
+        recent.record(&PaletteAction::Command(Command::Flames));
+        recent.record(&PaletteAction::TogglePolling);
+        let mut palette = Palette::new(entries(), recent, AppMode::default());
+        assert_eq!(titles(&palette)[..3], ["Toggle polling", "flames", "fetch"]);
+
+        type_query(&mut palette, "f");
+        assert_eq!(titles(&palette)[..2], ["flames", "fetch"]);
+    }
+
+    #[test]
+    fn test_keys_select_and_run() {
+        let mut palette = Palette::new(entries(), RecentActions::default(), AppMode::default());
+        palette.handle_key(KeyEvent::from(KeyCode::Down));
+        palette.handle_key(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL));
+        assert_eq!(
+            palette.handle_key(KeyEvent::from(KeyCode::Enter)),
+            PaletteOutcome::Run(PaletteAction::TogglePolling)
+        );
+        type_query(&mut palette, "zzz");
+        assert!(palette.matches.is_empty());
+        assert_eq!(
+            palette.handle_key(KeyEvent::from(KeyCode::Enter)),
+            PaletteOutcome::Open
+        );
+        assert_eq!(
+            palette.handle_key(KeyEvent::from(KeyCode::Esc)),
+            PaletteOutcome::Cancel
+        );
+    }
+}
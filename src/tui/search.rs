@@ -0,0 +1,207 @@
+/// Incremental search over the rows of the current results table
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchState {
+    pub query: String,
+    /// True while the user is typing the query after pressing `/`
+    pub editing: bool,
+    /// Indices of rows with at least one matching cell, in table order
+    pub matches: Vec<usize>,
+}
+
+impl SearchState {
+    pub fn is_active(&self) -> bool {
+        self.editing || !self.query.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        *self = SearchState::default();
+    }
+
+    /// Recompute matching rows from the visible cell text of each row
+    pub fn update_matches(&mut self, rows: &[Vec<String>]) {
+        self.matches = if self.query.is_empty() {
+            Vec::new()
+        } else {
+            rows.iter()
+                .enumerate()
+                .filter(|(_, cells)| {
+                    cells
+                        .iter()
+                        .any(|cell| fuzzy_indices(&self.query, cell).is_some())
+                })
+                .map(|(i, _)| i)
+                .collect()
+        };
+    }
+
+    /// First match at or after `from`, wrapping around
+    pub fn match_from(&self, from: usize) -> Option<usize> {
+        self.matches
+            .iter()
+            .copied()
+            .find(|&i| i >= from)
+            .or_else(|| self.matches.first().copied())
+    }
+
+    /// Next match strictly after `current`, wrapping around
+    pub fn next_match(&self, current: Option<usize>) -> Option<usize> {
+        match current {
+            Some(i) => self.match_from(i + 1),
+            None => self.matches.first().copied(),
+        }
+    }
+
+    /// Previous match strictly before `current`, wrapping around
+    pub fn previous_match(&self, current: Option<usize>) -> Option<usize> {
+        match current {
+            Some(i) => self
+                .matches
+                .iter()
+                .rev()
+                .copied()
+                .find(|&m| m < i)
+                .or_else(|| self.matches.last().copied()),
+            None => self.matches.last().copied(),
+        }
+    }
+
+    /// 1-based position of `row` among the matches, for the search bar
+    pub fn position_of(&self, row: Option<usize>) -> Option<usize> {
+        row.and_then(|r| self.matches.iter().position(|&m| m == r))
+            .map(|p| p + 1)
+    }
+}
+
+/// Fuzzy-match `query` against `text`, returning the char indices to highlight.
+///
+/// A contiguous substring match is preferred; otherwise every query character
+/// must appear in order. Matching is case-insensitive unless the query
+/// contains an uppercase character (smart case).
+pub fn fuzzy_indices(query: &str, text: &str) -> Option<Vec<usize>> {
+    if query.is_empty() {
+        return None;
+    }
+
+    let case_sensitive = query.chars().any(char::is_uppercase);
+    let normalize = |c: char| {
+        if case_sensitive {
+            c
+        } else {
+            c.to_lowercase().next().unwrap_or(c)
+        }
+    };
+    let query: Vec<char> = query.chars().map(normalize).collect();
+    let text: Vec<char> = text.chars().map(normalize).collect();
+
+    if let Some(start) = text
+        .windows(query.len())
+        .position(|window| window == query.as_slice())
+    {
+        return Some((start..start + query.len()).collect());
+    }
+
+    let mut indices = Vec::with_capacity(query.len());
+    let mut next = query.iter().peekable();
+    for (i, c) in text.iter().enumerate() {
+        match next.peek() {
+            Some(q) if *q == c => {
+                indices.push(i);
+                next.next();
+            }
+            Some(_) => {}
+            None => break,
+        }
+    }
+
+    if next.peek().is_none() {
+        Some(indices)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(cells: &[&[&str]]) -> Vec<Vec<String>> {
+        cells
+            .iter()
+            .map(|row| row.iter().map(|c| c.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_fuzzy_prefers_contiguous_substring() {
+        assert_eq!(
+            fuzzy_indices("prod", "my-prod-runner"),
+            Some(vec![3, 4, 5, 6])
+        );
+    }
+
+    #[test]
+    fn test_fuzzy_subsequence() {
+        assert_eq!(fuzzy_indices("hst1", "host-01"), Some(vec![0, 2, 3, 6]));
+        assert_eq!(fuzzy_indices("10.1", "10.0.1.50"), Some(vec![0, 1, 2, 5]));
+    }
+
+    #[test]
+    fn test_fuzzy_no_match() {
+        assert_eq!(fuzzy_indices("xyz", "host-01"), None);
+        assert_eq!(fuzzy_indices("10", "1"), None);
+        assert_eq!(fuzzy_indices("", "anything"), None);
+    }
+
+    #[test]
+    fn test_fuzzy_smart_case() {
+        assert!(fuzzy_indices("prod", "PROD").is_some());
+        assert!(fuzzy_indices("Prod", "prod").is_none());
+        assert!(fuzzy_indices("Prod", "Production").is_some());
+    }
+
+    #[test]
+    fn test_fuzzy_indices_are_char_based() {
+        assert_eq!(fuzzy_indices("b", "✓ab"), Some(vec![2]));
+    }
+
+    #[test]
+    fn test_update_matches_checks_every_cell() {
+        let mut search = SearchState {
+            query: "win".to_string(),
+            ..Default::default()
+        };
+        search.update_matches(&rows(&[
+            &["1", "linux"],
+            &["2", "windows"],
+            &["3", "macos"],
+            &["4", "linux", "win-host"],
+        ]));
+        assert_eq!(search.matches, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_next_and_previous_wrap_around() {
+        let search = SearchState {
+            query: "x".to_string(),
+            editing: false,
+            matches: vec![1, 4, 7],
+        };
+        assert_eq!(search.next_match(Some(1)), Some(4));
+        assert_eq!(search.next_match(Some(7)), Some(1));
+        assert_eq!(search.next_match(None), Some(1));
+        assert_eq!(search.previous_match(Some(4)), Some(1));
+        assert_eq!(search.previous_match(Some(1)), Some(7));
+        assert_eq!(search.match_from(5), Some(7));
+        assert_eq!(search.match_from(8), Some(1));
+        assert_eq!(search.position_of(Some(7)), Some(3));
+        assert_eq!(search.position_of(Some(2)), None);
+    }
+
+    #[test]
+    fn test_no_matches_yields_none() {
+        let search = SearchState::default();
+        assert_eq!(search.next_match(Some(0)), None);
+        assert_eq!(search.previous_match(None), None);
+        assert!(!search.is_active());
+    }
+}
@@ -0,0 +1,194 @@
+use crate::models::version::parse_version;
+use crate::tui::table::{Column, TableRow};
+use chrono::{DateTime, Utc};
+use std::cmp::Ordering;
+use std::net::IpAddr;
+
+/// How the cells of a column compare when sorting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKind {
+    Numeric,
+    Version,
+    Timestamp,
+    Ip,
+    Text,
+}
+
+/// Parsed sort key for a single cell
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum SortKey {
+    Number(u64),
+    Version(semver::Version),
+    Time(DateTime<Utc>),
+    Ip(IpAddr),
+    Text(String),
+}
+
+impl SortKey {
+    /// Parse a cell; placeholders such as "-" or "Never" have no key
+    fn parse(kind: SortKind, text: &str) -> Option<SortKey> {
+        match kind {
+            SortKind::Numeric => text.parse().ok().map(SortKey::Number),
+            SortKind::Version => parse_version(text).map(SortKey::Version),
+            SortKind::Timestamp => DateTime::parse_from_rfc3339(text)
+                .ok()
+                .map(|t| SortKey::Time(t.with_timezone(&Utc))),
+            SortKind::Ip => text.parse().ok().map(SortKey::Ip),
+            SortKind::Text if text == "-" || text.is_empty() => None,
+            SortKind::Text => Some(SortKey::Text(text.to_lowercase())),
+        }
+    }
+}
+
+/// Compare two optional keys; missing values sort last in either direction
+fn compare_keys(a: &Option<SortKey>, b: &Option<SortKey>, descending: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if descending => b.cmp(a),
+        (Some(a), Some(b)) => a.cmp(b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Sort rows by a column, falling back to ascending row id for ties
+pub fn sort_rows<T: TableRow>(rows: &mut Vec<T>, column: Column, descending: bool) {
+    let kind = column.sort_kind();
+    let mut keyed: Vec<(Option<SortKey>, u64, T)> = rows
+        .drain(..)
+        .map(|row| (SortKey::parse(kind, &row.cell(column)), row.row_id(), row))
+        .collect();
+    keyed.sort_by(|a, b| compare_keys(&a.0, &b.0, descending).then(a.1.cmp(&b.1)));
+    rows.extend(keyed.into_iter().map(|(_, _, row)| row));
+}
+
+/// Sort column and header cursor for the current results table
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SortState {
+    /// Index into the view's columns that rows are sorted by, if any
+    pub column: Option<usize>,
+    pub descending: bool,
+    /// Index of the header highlighted for keyboard selection
+    pub cursor: usize,
+}
+
+impl SortState {
+    pub fn next_column(&mut self, count: usize) {
+        if count > 0 {
+            self.cursor = (self.cursor + 1) % count;
+        }
+    }
+
+    pub fn previous_column(&mut self, count: usize) {
+        if count > 0 {
+            self.cursor = (self.cursor + count - 1) % count;
+        }
+    }
+
+    /// Sort by `index`, toggling the direction if it is already the sort column
+    pub fn sort_by(&mut self, index: usize) {
+        if self.column == Some(index) {
+            self.descending = !self.descending;
+        } else {
+            self.column = Some(index);
+            self.descending = false;
+        }
+        self.cursor = index;
+    }
+
+    /// Arrow shown next to the sorted column's header
+    pub fn indicator(&self) -> &'static str {
+        if self.descending {
+            "▼"
+        } else {
+            "▲"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::manager::RunnerManager;
+    use crate::models::test_support;
+
+    fn manager(id: u64, version: Option<&str>, contacted_at: Option<&str>) -> RunnerManager {
+        RunnerManager {
+            contacted_at: contacted_at.map(|t| t.parse().unwrap()),
+            version: version.map(String::from),
+            ..test_support::manager(id, &format!("host-{}", id))
+        }
+    }
+
+    fn ids(rows: &[RunnerManager]) -> Vec<u64> {
+        rows.iter().map(|m| m.id).collect()
+    }
+
+    #[test]
+    fn test_version_sort_is_semver_aware() {
+        let mut rows = vec![
+            manager(1, Some("17.10.0"), None),
+            manager(2, Some("17.9.1"), None),
+            manager(3, Some("v16.11.2"), None),
+        ];
+        sort_rows(&mut rows, Column::Version, false);
+        assert_eq!(ids(&rows), vec![3, 2, 1]);
+        sort_rows(&mut rows, Column::Version, true);
+        assert_eq!(ids(&rows), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_timestamp_sort_and_missing_last() {
+        let mut rows = vec![
+            manager(1, None, None),
+            manager(2, None, Some("2024-01-20T14:22:00.000Z")),
+            manager(3, None, Some("2024-01-20T09:00:00+02:00")),
+        ];
+        sort_rows(&mut rows, Column::Contacted, false);
+        assert_eq!(ids(&rows), vec![3, 2, 1]);
+        sort_rows(&mut rows, Column::Contacted, true);
+        assert_eq!(ids(&rows), vec![2, 3, 1]);
+    }
+
+    #[test]
+    fn test_ties_break_by_ascending_id() {
+        let mut rows = vec![
+            manager(9, Some("17.0.0"), None),
+            manager(4, Some("17.0.0"), None),
+            manager(6, Some("16.0.0"), None),
+        ];
+        sort_rows(&mut rows, Column::Version, true);
+        assert_eq!(ids(&rows), vec![4, 9, 6]);
+    }
+
+    #[test]
+    fn test_numeric_ids_sort_numerically() {
+        let mut rows = vec![manager(10, None, None), manager(9, None, None)];
+        sort_rows(&mut rows, Column::ManagerId, false);
+        assert_eq!(ids(&rows), vec![9, 10]);
+    }
+
+    #[test]
+    fn test_sort_by_toggles_direction() {
+        let mut state = SortState::default();
+        assert_eq!(state.column, None);
+        state.sort_by(0);
+        assert_eq!(state.column, Some(0));
+        assert!(!state.descending);
+        state.sort_by(0);
+        assert!(state.descending);
+        state.sort_by(2);
+        assert_eq!(state.column, Some(2));
+        assert!(!state.descending);
+        assert_eq!(state.cursor, 2);
+    }
+
+    #[test]
+    fn test_cursor_wraps() {
+        let mut state = SortState::default();
+        state.previous_column(3);
+        assert_eq!(state.cursor, 2);
+        state.next_column(3);
+        assert_eq!(state.cursor, 0);
+    }
+}
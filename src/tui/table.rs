@@ -0,0 +1,613 @@
+use crate::ci::JobCoverage;
+use crate::models::coverage::TagSetCoverage;
+use crate::models::manager::RunnerManager;
+use crate::models::runner::Runner;
+use crate::tui::app::{ManagerRow, ResultsViewType, RotationRow};
+use crate::tui::columns::ColumnWidth;
+use crate::tui::sort::SortKind;
+use chrono::SecondsFormat;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// A column that can appear in one of the results tables
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Id,
+    RunnerId,
+    Type,
+    Status,
+    Version,
+    Tags,
+    Managers,
+    Ip,
+    Description,
+    Paused,
+    ManagerId,
+    SystemId,
+    Contacted,
+    Platform,
+    Architecture,
+    Compat,
+    OldSystem,
+    OldVersion,
+    OldStatus,
+    NewSystem,
+    NewVersion,
+    NewStatus,
+    TagSet,
+    Online,
+    Offline,
+    PausedCount,
+    Covered,
+    Job,
+    Flaps,
+}
+
+impl Column {
+    pub const ALL: &[Column] = &[
+        Column::Id,
+        Column::RunnerId,
+        Column::Type,
+        Column::Status,
+        Column::Version,
+        Column::Tags,
+        Column::Managers,
+        Column::Ip,
+        Column::Description,
+        Column::Paused,
+        Column::ManagerId,
+        Column::SystemId,
+        Column::Contacted,
+        Column::Platform,
+        Column::Architecture,
+        Column::Compat,
+        Column::OldSystem,
+        Column::OldVersion,
+        Column::OldStatus,
+        Column::NewSystem,
+        Column::NewVersion,
+        Column::NewStatus,
+        Column::TagSet,
+        Column::Online,
+        Column::Offline,
+        Column::PausedCount,
+        Column::Covered,
+        Column::Job,
+        Column::Flaps,
+    ];
+
+    /// Identifier used for this column in `config.toml`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Column::Id => "id",
+            Column::RunnerId => "runner_id",
+            Column::Type => "type",
+            Column::Status => "status",
+            Column::Version => "version",
+            Column::Tags => "tags",
+            Column::Managers => "managers",
+            Column::Ip => "ip",
+            Column::Description => "description",
+            Column::Paused => "paused",
+            Column::ManagerId => "manager_id",
+            Column::SystemId => "system_id",
+            Column::Contacted => "contacted",
+            Column::Platform => "platform",
+            Column::Architecture => "arch",
+            Column::Compat => "compat",
+            Column::OldSystem => "old_system",
+            Column::OldVersion => "old_version",
+            Column::OldStatus => "old_status",
+            Column::NewSystem => "new_system",
+            Column::NewVersion => "new_version",
+            Column::NewStatus => "new_status",
+            Column::TagSet => "tag_set",
+            Column::Online => "online",
+            Column::Offline => "offline",
+            Column::PausedCount => "paused_runners",
+            Column::Covered => "covered",
+            Column::Job => "job",
+            Column::Flaps => "flaps",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Column> {
+        Column::ALL.iter().copied().find(|c| c.name() == name)
+    }
+
+    pub fn header(&self) -> &'static str {
+        match self {
+            Column::Id => "ID",
+            Column::RunnerId => "Runner ID",
+            Column::Type => "Type",
+            Column::Status => "Status",
+            Column::Version => "Version",
+            Column::Tags => "Tags",
+            Column::Managers => "Mgrs",
+            Column::Ip => "IP",
+            Column::Description => "Description",
+            Column::Paused => "Paused",
+            Column::ManagerId => "Manager ID",
+            Column::SystemId => "System ID",
+            Column::Contacted => "Contacted",
+            Column::Platform => "Platform",
+            Column::Architecture => "Arch",
+            Column::Compat => "Compat",
+            Column::OldSystem => "Old System",
+            Column::OldVersion => "Old Ver",
+            Column::OldStatus => "Old Status",
+            Column::NewSystem => "New System",
+            Column::NewVersion => "New Ver",
+            Column::NewStatus => "New Status",
+            Column::TagSet => "Tag Set",
+            Column::Online => "Online",
+            Column::Offline => "Offline",
+            Column::PausedCount => "Paused",
+            Column::Covered => "Covered",
+            Column::Job => "Job",
+            Column::Flaps => "Flaps",
+        }
+    }
+
+    /// Default width, used unless `config.toml` or the column picker overrides it
+    pub fn default_width(&self) -> ColumnWidth {
+        match self {
+            Column::Id => ColumnWidth::Length(10),
+            Column::RunnerId => ColumnWidth::Length(12),
+            Column::Type => ColumnWidth::Length(15),
+            Column::Status | Column::OldStatus | Column::NewStatus => ColumnWidth::Length(10),
+            Column::Version | Column::OldVersion | Column::NewVersion => ColumnWidth::Length(10),
+            Column::Tags => ColumnWidth::Percentage(20),
+            Column::Managers => ColumnWidth::Length(5),
+            Column::Ip => ColumnWidth::Length(15),
+            Column::Description => ColumnWidth::Percentage(20),
+            Column::Paused => ColumnWidth::Length(7),
+            Column::ManagerId => ColumnWidth::Length(12),
+            Column::SystemId => ColumnWidth::Percentage(15),
+            Column::Contacted => ColumnWidth::Length(20),
+            Column::Platform => ColumnWidth::Length(10),
+            Column::Architecture => ColumnWidth::Length(8),
+            Column::Compat => ColumnWidth::Length(20),
+            Column::OldSystem | Column::NewSystem => ColumnWidth::Percentage(12),
+            Column::TagSet => ColumnWidth::Percentage(40),
+            Column::Online | Column::Offline | Column::PausedCount => ColumnWidth::Length(8),
+            Column::Covered => ColumnWidth::Length(8),
+            Column::Job => ColumnWidth::Percentage(25),
+            Column::Flaps => ColumnWidth::Length(6),
+        }
+    }
+
+    /// Lower values are kept longer when the terminal is too narrow
+    pub fn priority(&self) -> u8 {
+        match self {
+            Column::Id | Column::RunnerId | Column::TagSet | Column::Job => 0,
+            Column::Covered | Column::Online | Column::Flaps => 1,
+            Column::Offline | Column::PausedCount => 2,
+            Column::Status | Column::OldStatus | Column::NewStatus => 1,
+            Column::Version | Column::OldVersion | Column::NewVersion => 2,
+            Column::SystemId | Column::OldSystem | Column::NewSystem => 2,
+            Column::Tags | Column::Compat => 3,
+            Column::ManagerId | Column::Contacted => 4,
+            Column::Type | Column::Managers | Column::Description => 5,
+            Column::Ip | Column::Paused => 6,
+            Column::Platform | Column::Architecture => 7,
+        }
+    }
+
+    pub fn sort_kind(&self) -> SortKind {
+        match self {
+            Column::Id
+            | Column::RunnerId
+            | Column::ManagerId
+            | Column::Managers
+            | Column::Online
+            | Column::Offline
+            | Column::PausedCount
+            | Column::Flaps => SortKind::Numeric,
+            Column::Version | Column::OldVersion | Column::NewVersion => SortKind::Version,
+            Column::Contacted => SortKind::Timestamp,
+            Column::Ip => SortKind::Ip,
+            _ => SortKind::Text,
+        }
+    }
+
+    /// Whether cells in this column hold a runner/manager status
+    pub fn is_status(&self) -> bool {
+        matches!(self, Column::Status | Column::OldStatus | Column::NewStatus)
+    }
+}
+
+impl ResultsViewType {
+    pub const ALL: &[ResultsViewType] = &[
+        ResultsViewType::Runners,
+        ResultsViewType::Workers,
+        ResultsViewType::HealthCheck,
+        ResultsViewType::Rotation,
+        ResultsViewType::Drift,
+        ResultsViewType::Coverage,
+        ResultsViewType::Pipeline,
+    ];
+
+    /// Identifier used for this view in the `[columns]` table of `config.toml`
+    pub fn name(&self) -> &'static str {
+        match self {
+            ResultsViewType::Runners => "runners",
+            ResultsViewType::Workers => "workers",
+            ResultsViewType::HealthCheck => "health_check",
+            ResultsViewType::Rotation => "rotation",
+            ResultsViewType::Drift => "drift",
+            ResultsViewType::Coverage => "coverage",
+            ResultsViewType::Pipeline => "pipeline",
+        }
+    }
+
+    /// Columns shown when no layout is configured
+    pub fn default_columns(&self) -> &'static [Column] {
+        match self {
+            ResultsViewType::Runners | ResultsViewType::HealthCheck => &[
+                Column::Id,
+                Column::Type,
+                Column::Status,
+                Column::Version,
+                Column::Tags,
+                Column::Managers,
+                Column::Ip,
+            ],
+            ResultsViewType::Coverage => &[
+                Column::TagSet,
+                Column::Covered,
+                Column::Online,
+                Column::Offline,
+                Column::PausedCount,
+            ],
+            ResultsViewType::Pipeline => &[
+                Column::Job,
+                Column::TagSet,
+                Column::Covered,
+                Column::Online,
+                Column::Offline,
+                Column::PausedCount,
+            ],
+            ResultsViewType::Drift => &[
+                Column::Id,
+                Column::Version,
+                Column::Status,
+                Column::Managers,
+                Column::Tags,
+                Column::Description,
+            ],
+            ResultsViewType::Workers => &[
+                Column::RunnerId,
+                Column::Tags,
+                Column::ManagerId,
+                Column::SystemId,
+                Column::Status,
+                Column::Version,
+                Column::Compat,
+                Column::Contacted,
+                Column::Ip,
+            ],
+            ResultsViewType::Rotation => &[
+                Column::RunnerId,
+                Column::Tags,
+                Column::Managers,
+                Column::Flaps,
+                Column::OldSystem,
+                Column::OldVersion,
+                Column::OldStatus,
+                Column::NewSystem,
+                Column::NewVersion,
+                Column::NewStatus,
+            ],
+        }
+    }
+
+    /// Every column that has data in this view
+    pub fn available_columns(&self) -> &'static [Column] {
+        match self {
+            ResultsViewType::Coverage => &[
+                Column::TagSet,
+                Column::Covered,
+                Column::Online,
+                Column::Offline,
+                Column::PausedCount,
+            ],
+            ResultsViewType::Pipeline => &[
+                Column::Job,
+                Column::TagSet,
+                Column::Covered,
+                Column::Online,
+                Column::Offline,
+                Column::PausedCount,
+            ],
+            ResultsViewType::Runners | ResultsViewType::HealthCheck | ResultsViewType::Drift => &[
+                Column::Id,
+                Column::Type,
+                Column::Status,
+                Column::Version,
+                Column::Tags,
+                Column::Managers,
+                Column::Ip,
+                Column::Description,
+                Column::Paused,
+            ],
+            ResultsViewType::Workers => &[
+                Column::RunnerId,
+                Column::Tags,
+                Column::ManagerId,
+                Column::SystemId,
+                Column::Status,
+                Column::Version,
+                Column::Compat,
+                Column::Contacted,
+                Column::Ip,
+                Column::Platform,
+                Column::Architecture,
+            ],
+            ResultsViewType::Rotation => &[
+                Column::RunnerId,
+                Column::Tags,
+                Column::Managers,
+                Column::Flaps,
+                Column::Description,
+                Column::OldSystem,
+                Column::OldVersion,
+                Column::OldStatus,
+                Column::NewSystem,
+                Column::NewVersion,
+                Column::NewStatus,
+            ],
+        }
+    }
+}
+
+/// A row in one of the results tables
+pub trait TableRow {
+    fn cell(&self, column: Column) -> String;
+
+    /// Stable identity used as the secondary sort key and to keep the
+    /// selection on the same row across refreshes
+    fn row_id(&self) -> u64;
+}
+
+fn dash_or(value: &Option<String>) -> String {
+    value.as_deref().unwrap_or("-").to_string()
+}
+
+/// Oldest and (if different) newest manager by creation time
+fn rotation_pair(runner: &Runner) -> (Option<&RunnerManager>, Option<&RunnerManager>) {
+    let oldest = runner
+        .managers
+        .iter()
+        .min_by(|a, b| a.created_at.cmp(&b.created_at));
+    let newest = if runner.managers.len() > 1 {
+        runner
+            .managers
+            .iter()
+            .max_by(|a, b| a.created_at.cmp(&b.created_at))
+    } else {
+        None
+    };
+    (oldest, newest)
+}
+
+impl TableRow for Runner {
+    fn cell(&self, column: Column) -> String {
+        let manager_cell = |manager: Option<&RunnerManager>, column: Column| {
+            manager
+                .map(|m| m.cell(column))
+                .unwrap_or_else(|| "-".to_string())
+        };
+        match column {
+            Column::Id | Column::RunnerId => self.id.to_string(),
+            Column::Type => self.runner_type.to_string(),
+            Column::Status => self.status.to_string(),
+            Column::Version => dash_or(&self.version),
+            Column::Tags => self.tag_list.join(", "),
+            Column::Managers => self.managers.len().to_string(),
+            Column::Ip => dash_or(&self.ip_address),
+            Column::Description => dash_or(&self.description),
+            Column::Paused => if self.paused { "yes" } else { "no" }.to_string(),
+            Column::OldSystem => manager_cell(rotation_pair(self).0, Column::SystemId),
+            Column::OldVersion => manager_cell(rotation_pair(self).0, Column::Version),
+            Column::OldStatus => manager_cell(rotation_pair(self).0, Column::Status),
+            Column::NewSystem => manager_cell(rotation_pair(self).1, Column::SystemId),
+            Column::NewVersion => manager_cell(rotation_pair(self).1, Column::Version),
+            Column::NewStatus => manager_cell(rotation_pair(self).1, Column::Status),
+            Column::ManagerId
+            | Column::SystemId
+            | Column::Contacted
+            | Column::Platform
+            | Column::Architecture
+            | Column::Compat
+            | Column::TagSet
+            | Column::Online
+            | Column::Offline
+            | Column::PausedCount
+            | Column::Covered
+            | Column::Job
+            | Column::Flaps => "-".to_string(),
+        }
+    }
+
+    fn row_id(&self) -> u64 {
+        self.id
+    }
+}
+
+impl TableRow for RunnerManager {
+    fn cell(&self, column: Column) -> String {
+        match column {
+            Column::ManagerId => self.id.to_string(),
+            Column::SystemId => self.system_id.clone(),
+            Column::Status => self.status.to_string(),
+            Column::Version => dash_or(&self.version),
+            Column::Contacted => self
+                .contacted_at
+                .map(|t| t.to_rfc3339_opts(SecondsFormat::Secs, true))
+                .unwrap_or_else(|| "Never".to_string()),
+            Column::Ip => dash_or(&self.ip_address),
+            Column::Platform => dash_or(&self.platform),
+            Column::Architecture => dash_or(&self.architecture),
+            _ => "-".to_string(),
+        }
+    }
+
+    fn row_id(&self) -> u64 {
+        self.id
+    }
+}
+
+impl TableRow for ManagerRow {
+    fn cell(&self, column: Column) -> String {
+        match column {
+            Column::Id | Column::RunnerId => self.runner_id.to_string(),
+            Column::Tags => self.runner_tags.join(", "),
+            Column::Compat => self.compatibility.to_string(),
+            _ => self.manager.cell(column),
+        }
+    }
+
+    fn row_id(&self) -> u64 {
+        self.manager.id
+    }
+}
+
+impl TableRow for TagSetCoverage {
+    fn cell(&self, column: Column) -> String {
+        match column {
+            Column::TagSet | Column::Tags => self.tags.join(", "),
+            Column::Online => self.online.to_string(),
+            Column::Offline => self.offline.to_string(),
+            Column::PausedCount => self.paused.to_string(),
+            Column::Covered => if self.is_covered() { "yes" } else { "no" }.to_string(),
+            _ => "-".to_string(),
+        }
+    }
+
+    /// Tag sets have no id; hash the tags so the selection survives polls
+    fn row_id(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.tags.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl TableRow for RotationRow {
+    fn cell(&self, column: Column) -> String {
+        match column {
+            Column::Flaps => self.flaps.to_string(),
+            _ => self.runner.cell(column),
+        }
+    }
+
+    fn row_id(&self) -> u64 {
+        self.runner.id
+    }
+}
+
+impl TableRow for JobCoverage {
+    fn cell(&self, column: Column) -> String {
+        match column {
+            Column::Job => self.name.clone(),
+            Column::TagSet | Column::Tags if self.coverage.tags.is_empty() => {
+                "(untagged)".to_string()
+            }
+            _ => self.coverage.cell(column),
+        }
+    }
+
+    fn row_id(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.name.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::server::Compatibility;
+    use crate::models::status::RunnerStatus;
+    use crate::models::test_support;
+
+    fn manager(id: u64, created_at: &str, status: &str) -> RunnerManager {
+        RunnerManager {
+            created_at: created_at.parse().unwrap(),
+            status: RunnerStatus::from(status),
+            version: Some(format!("17.{}.0", id)),
+            ..test_support::manager(id, &format!("host-{}", id))
+        }
+    }
+
+    fn runner(managers: Vec<RunnerManager>) -> Runner {
+        Runner {
+            description: None,
+            tag_list: test_support::tags(&["alm", "prod"]),
+            managers,
+            ..test_support::runner(42)
+        }
+    }
+
+    #[test]
+    fn test_runner_cells() {
+        let r = runner(vec![]);
+        assert_eq!(r.cell(Column::Id), "42");
+        assert_eq!(r.cell(Column::Tags), "alm, prod");
+        assert_eq!(r.cell(Column::Version), "-");
+        assert_eq!(r.cell(Column::Managers), "0");
+    }
+
+    #[test]
+    fn test_rotation_cells_use_oldest_and_newest_manager() {
+        let r = runner(vec![
+            manager(2, "2024-02-01T00:00:00Z", "online"),
+            manager(1, "2024-01-01T00:00:00Z", "offline"),
+        ]);
+        assert_eq!(r.cell(Column::OldSystem), "host-1");
+        assert_eq!(r.cell(Column::OldStatus), "offline");
+        assert_eq!(r.cell(Column::NewSystem), "host-2");
+        assert_eq!(r.cell(Column::NewVersion), "17.2.0");
+    }
+
+    #[test]
+    fn test_rotation_cells_single_manager_has_no_new() {
+        let r = runner(vec![manager(1, "2024-01-01T00:00:00Z", "online")]);
+        assert_eq!(r.cell(Column::OldSystem), "host-1");
+        assert_eq!(r.cell(Column::NewSystem), "-");
+    }
+
+    #[test]
+    fn test_manager_row_cells() {
+        let row = ManagerRow {
+            runner_id: 7,
+            runner_tags: vec!["alm".to_string()],
+            manager: manager(3, "2024-01-01T00:00:00Z", "stale"),
+            compatibility: Compatibility::TooOld(3),
+        };
+        assert_eq!(row.cell(Column::RunnerId), "7");
+        assert_eq!(row.cell(Column::Tags), "alm");
+        assert_eq!(row.cell(Column::ManagerId), "3");
+        assert_eq!(row.cell(Column::Status), "stale");
+        assert_eq!(row.cell(Column::Contacted), "Never");
+        assert_eq!(row.cell(Column::Compat), "too old (3 behind)");
+    }
+
+    #[test]
+    fn test_default_columns_are_available() {
+        for view in ResultsViewType::ALL {
+            assert!(!view.default_columns().is_empty());
+            for column in view.default_columns() {
+                assert!(view.available_columns().contains(column));
+            }
+        }
+    }
+
+    #[test]
+    fn test_column_names_round_trip() {
+        for column in Column::ALL {
+            assert_eq!(Column::from_name(column.name()), Some(*column));
+        }
+        assert_eq!(Column::from_name("nope"), None);
+    }
+}
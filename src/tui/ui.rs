@@ -1,25 +1,85 @@
-use crate::tui::app::{App, AppMode, ResultsViewType};
+use crate::alerts::{AlertState, Severity};
+use crate::models::drift::Lag;
+use crate::models::status::RunnerStatus;
+use crate::models::timeline::ManagerEventKind;
+use crate::source::RunnerSource;
+use crate::tui::app::{App, AppMode, Command, ResultsViewType};
+use crate::tui::filter_form::FilterField;
+use crate::tui::keymap::{Action, Keymap, Scope};
+use crate::tui::mouse::{ClickAreas, ListArea, TableArea};
+use crate::tui::search::fuzzy_indices;
+use crate::tui::table::Column;
+use chrono::Utc;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    widgets::{Block, Borders, Cell, List, ListItem, Paragraph, Row, Table},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Clear, List, ListItem, Paragraph, Row, Table},
     Frame,
 };
 
-fn status_style(status: &str) -> Style {
+fn lag_style(lag: Lag) -> Style {
+    match lag {
+        Lag::Current | Lag::Ahead => Style::default().fg(Color::Green),
+        Lag::Patch(_) => Style::default().fg(Color::Yellow),
+        Lag::Minor(_) | Lag::Major(_) => Style::default().fg(Color::Red),
+    }
+}
+
+/// Colour a compatibility cell from its text: green when in step with the
+/// server, yellow when trailing within the allowed lag, red when flagged
+fn compat_style(text: &str) -> Style {
+    match text {
+        "ok" => Style::default().fg(Color::Green),
+        "-" => Style::default(),
+        t if t.ends_with(" behind") => Style::default().fg(Color::Yellow),
+        _ => Style::default().fg(Color::Red),
+    }
+}
+
+fn covered_style(text: &str) -> Style {
+    match text {
+        "yes" => Style::default().fg(Color::Green),
+        _ => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+    }
+}
+
+/// Red once a runner flaps more than the configured limit
+fn flaps_style(text: &str, max_flaps: usize) -> Style {
+    match text.parse::<usize>() {
+        Ok(flaps) if flaps > max_flaps => Style::default().fg(Color::Red),
+        Ok(flaps) if flaps > 0 => Style::default().fg(Color::Yellow),
+        _ => Style::default(),
+    }
+}
+
+fn severity_style(severity: Severity) -> Style {
+    match severity {
+        Severity::Info => Style::default().fg(Color::Cyan),
+        Severity::Warning => Style::default().fg(Color::Yellow),
+        Severity::Critical => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+    }
+}
+
+fn status_style(status: &RunnerStatus) -> Style {
     match status {
-        "online" => Style::default().fg(Color::Green),
-        "offline" => Style::default().fg(Color::Red),
-        "stale" => Style::default().fg(Color::Yellow),
-        _ => Style::default().fg(Color::Gray),
+        RunnerStatus::Online => Style::default().fg(Color::Green),
+        RunnerStatus::Offline => Style::default().fg(Color::Red),
+        RunnerStatus::Stale => Style::default().fg(Color::Yellow),
+        RunnerStatus::NeverContacted | RunnerStatus::Unknown(_) => Style::default().fg(Color::Gray),
     }
 }
 
-fn dash_or(value: &Option<String>) -> String {
-    value.as_deref().unwrap_or("-").to_string()
+/// Append the active client-side query to a results title
+fn with_query<S: RunnerSource>(app: &App<S>, title: String) -> String {
+    match &app.active_query {
+        Some(query) => format!("{} — where {}", title, query.source()),
+        None => title,
+    }
 }
 
-pub fn render(app: &mut App, frame: &mut Frame) {
+pub fn render<S: RunnerSource>(app: &mut App<S>, frame: &mut Frame) {
+    app.click_areas = ClickAreas::default();
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -30,54 +90,178 @@ pub fn render(app: &mut App, frame: &mut Frame) {
         .split(frame.size());
 
     // Header
+    let profile = app
+        .profile
+        .as_deref()
+        .map(|name| format!(" · {}", name))
+        .unwrap_or_default();
+    let server = app
+        .server_version
+        .as_ref()
+        .map(|v| format!("{} · GitLab {}", profile, v.version))
+        .unwrap_or(profile);
     let title = if app.is_loading {
-        format!("GitLab Runner TUI {} Loading...", app.spinner_char())
+        format!(
+            "GitLab Runner TUI{} {} Loading...",
+            server,
+            app.spinner_char()
+        )
     } else if app.polling_active {
         let elapsed = app.poll_elapsed_secs();
         let timeout = app.config.poll_timeout_secs;
         format!(
-            "GitLab Runner TUI  ⟳ Polling ({:02}:{:02} / {:02}:{:02})",
+            "GitLab Runner TUI{}  ⟳ Polling ({:02}:{:02} / {:02}:{:02}){}",
+            server,
             elapsed / 60,
             elapsed % 60,
             timeout / 60,
-            timeout % 60
+            timeout % 60,
+            if app.polling_paused() {
+                " paused while unfocused"
+            } else {
+                ""
+            }
         )
     } else {
-        "GitLab Runner TUI".to_string()
+        format!("GitLab Runner TUI{}", server)
+    };
+    let title = match app.alerts.firing() {
+        0 => title,
+        firing => format!("{}  ⚠ {} alerts firing", title, firing),
     };
     let title = Paragraph::new(title).block(Block::default().borders(Borders::ALL));
     frame.render_widget(title, chunks[0]);
 
+    let content = if app.show_alerts {
+        let height = (app.alerts.alerts().len().max(1) as u16 + 2).min(chunks[1].height / 2);
+        let split = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(height)])
+            .split(chunks[1]);
+        render_alerts(app, frame, split[1]);
+        split[0]
+    } else {
+        chunks[1]
+    };
+
     // Content based on mode
     match app.mode {
-        AppMode::CommandSelection => render_command_selection(app, frame, chunks[1]),
-        AppMode::FilterInput => render_filter_input(app, frame, chunks[1]),
-        AppMode::ResultsView => render_results(app, frame, chunks[1]),
-        AppMode::Help => render_help_view(app, frame, chunks[1]),
+        AppMode::CommandSelection => render_command_selection(app, frame, content),
+        AppMode::FilterInput => render_filter_input(app, frame, content),
+        AppMode::ResultsView => render_results(app, frame, content),
+        AppMode::ColumnPicker => {
+            render_results(app, frame, content);
+            render_column_picker(app, frame, content);
+        }
+        AppMode::Timeline => {
+            render_results(app, frame, content);
+            render_timeline(app, frame, content);
+        }
+        AppMode::PresetPicker => {
+            render_command_selection(app, frame, content);
+            render_preset_picker(app, frame, content);
+        }
+        AppMode::SavePreset => {
+            render_results(app, frame, content);
+            render_save_preset(app, frame, content);
+        }
+        AppMode::Palette => {
+            match app.palette.as_ref().map(|palette| palette.previous) {
+                Some(AppMode::ResultsView) => render_results(app, frame, content),
+                _ => render_command_selection(app, frame, content),
+            }
+            render_palette(app, frame, content);
+        }
+        AppMode::Help => render_help_view(app, frame, content),
     };
+    render_toast(app, frame, content);
 
     // Status bar with context-sensitive help
+    let keymap = &app.keymap;
     let status_text = if app.error_message.is_some() {
-        "Press Esc to dismiss error and go back"
+        "Press Esc to dismiss error and go back".to_string()
     } else {
         match app.mode {
-            AppMode::CommandSelection => "↑/↓: Navigate | Enter: Select | ?: Help | q: Quit",
-            AppMode::FilterInput => "Enter: Search | Esc: Back | Type to filter by tags",
-            AppMode::ResultsView => {
-                if app.polling_active {
-                    "↑/↓: Scroll | p: Stop polling | Esc: Back | q: Quit"
-                } else {
-                    "↑/↓: Scroll | p: Start polling | Esc: Back | q: Quit"
-                }
+            AppMode::CommandSelection => key_hints(
+                keymap,
+                &[
+                    (&[Action::Up, Action::Down], "Navigate"),
+                    (&[Action::Select], "Select"),
+                    (&[Action::Presets], "Presets"),
+                    (&[Action::Palette], "Palette"),
+                    (&[Action::ToggleAlerts], "Alerts"),
+                    (&[Action::Help], "Help"),
+                    (&[Action::Quit], "Quit"),
+                ],
+            ),
+            AppMode::FilterInput => {
+                "Tab/↑/↓: Next field | ←/→: Change option | Enter: Search | Esc: Back".to_string()
             }
-            AppMode::Help => "Press any key to close help",
+            AppMode::ResultsView if app.search.editing => {
+                "Type to search | Enter: Keep matches | Esc: Cancel search".to_string()
+            }
+            AppMode::ResultsView => key_hints(
+                keymap,
+                &[
+                    (&[Action::Up, Action::Down], "Scroll"),
+                    (
+                        &[Action::ColumnLeft, Action::ColumnRight, Action::Sort],
+                        "Sort",
+                    ),
+                    (&[Action::Search], "Search"),
+                    (&[Action::NextMatch, Action::PreviousMatch], "Next/prev"),
+                    (&[Action::Columns], "Columns"),
+                    (&[Action::SavePreset], "Save preset"),
+                    (
+                        &[Action::TogglePolling],
+                        if app.polling_active {
+                            "Stop polling"
+                        } else {
+                            "Start polling"
+                        },
+                    ),
+                    (&[Action::Back], "Back"),
+                    (&[Action::Quit], "Quit"),
+                ],
+            ),
+            AppMode::ColumnPicker => {
+                "↑/↓: Select | Space: Show/hide | J/K: Move | +/-: Width | Esc: Done".to_string()
+            }
+            AppMode::Timeline => format!("Esc/{}: Close timeline", keymap.hint(Action::Timeline)),
+            AppMode::PresetPicker => key_hints(
+                keymap,
+                &[
+                    (&[Action::Up, Action::Down], "Select"),
+                    (&[Action::Select], "Run preset"),
+                    (&[Action::Back], "Back"),
+                ],
+            ),
+            AppMode::SavePreset => {
+                "Type a name | Enter: Save to config.toml | Esc: Cancel".to_string()
+            }
+            AppMode::Palette => {
+                "Type to filter | ↑/↓ Ctrl-N/P: Select | Enter: Run | Esc: Close".to_string()
+            }
+            AppMode::Help => "Press any key to close help".to_string(),
         }
     };
     let status = Paragraph::new(status_text).block(Block::default().borders(Borders::ALL));
     frame.render_widget(status, chunks[2]);
 }
 
-fn render_command_selection(app: &mut App, frame: &mut Frame, area: Rect) {
+/// Status bar hints, each the first key of its actions and a label
+fn key_hints(keymap: &Keymap, hints: &[(&[Action], &str)]) -> String {
+    hints
+        .iter()
+        .map(|(actions, label)| {
+            let keys: Vec<String> = actions.iter().map(|a| keymap.hint(*a)).collect();
+            format!("{}: {}", keys.join("/"), label)
+        })
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+fn render_command_selection<S: RunnerSource>(app: &mut App<S>, frame: &mut Frame, area: Rect) {
     let items: Vec<ListItem> = app
         .commands
         .iter()
@@ -102,20 +286,61 @@ fn render_command_selection(app: &mut App, frame: &mut Frame, area: Rect) {
     state.select(Some(app.selected_command_index));
 
     frame.render_stateful_widget(list, area, &mut state);
+    app.click_areas.commands = Some(ListArea {
+        rows: Rect {
+            x: area.x + 1,
+            y: area.y + 1,
+            width: area.width.saturating_sub(2),
+            height: area.height.saturating_sub(2),
+        },
+        offset: state.offset(),
+    });
 }
 
-fn render_filter_input(app: &App, frame: &mut Frame, area: Rect) {
-    let input = Paragraph::new(app.input_buffer.as_str())
-        .style(Style::default().fg(Color::Yellow))
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("Filter Input (Press Enter to search)"),
-        );
+fn render_filter_input<S: RunnerSource>(app: &App<S>, frame: &mut Frame, area: Rect) {
+    let form = &app.filter_form;
+    let mut lines: Vec<Line> = FilterField::ALL
+        .iter()
+        .map(|field| {
+            let focused = form.focused == *field;
+            let value = form.value(*field);
+            let value = match (field.options().is_some(), focused) {
+                (true, true) => format!("◀ {} ▶", value),
+                (false, true) => format!("{}█", value),
+                _ => value,
+            };
+            let style = if focused {
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            Line::from(vec![
+                Span::styled(if focused { ">> " } else { "   " }, style),
+                Span::styled(format!("{:<10}", field.label()), style),
+                Span::styled(value, style),
+            ])
+        })
+        .collect();
+
+    if let Some(error) = &form.error {
+        lines.push(Line::from(""));
+        lines.push(Line::styled(
+            format!("   ✗ {}", error),
+            Style::default().fg(Color::Red),
+        ));
+    }
+
+    let input = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Filter Input (Press Enter to search)"),
+    );
     frame.render_widget(input, area);
 }
 
-fn render_results(app: &mut App, frame: &mut Frame, area: Rect) {
+fn render_results<S: RunnerSource>(app: &mut App<S>, frame: &mut Frame, area: Rect) {
     // Check for error message first
     if let Some(error) = &app.error_message {
         render_error(error, frame, area);
@@ -127,6 +352,9 @@ fn render_results(app: &mut App, frame: &mut Frame, area: Rect) {
         ResultsViewType::Workers => render_workers_table(app, frame, area),
         ResultsViewType::HealthCheck => render_health_check(app, frame, area),
         ResultsViewType::Rotation => render_rotation_table(app, frame, area),
+        ResultsViewType::Drift => render_drift(app, frame, area),
+        ResultsViewType::Coverage => render_coverage(app, frame, area),
+        ResultsViewType::Pipeline => render_pipeline(app, frame, area),
     }
 }
 
@@ -160,76 +388,20 @@ fn render_error(error: &str, frame: &mut Frame, area: Rect) {
     frame.render_widget(list, area);
 }
 
-fn render_runners_table(app: &mut App, frame: &mut Frame, area: Rect) {
-    render_runners_table_impl(
-        app,
-        frame,
-        area,
-        format!("Results ({} runners)", app.runners.len()),
-    );
+fn render_runners_table<S: RunnerSource>(app: &mut App<S>, frame: &mut Frame, area: Rect) {
+    let title = with_query(app, format!("Results ({} runners)", app.runners.len()));
+    render_table(app, frame, area, title);
 }
 
-fn render_workers_table(app: &mut App, frame: &mut Frame, area: Rect) {
-    let header = Row::new(vec![
-        Cell::from("Runner ID"),
-        Cell::from("Tags"),
-        Cell::from("Manager ID"),
-        Cell::from("System ID"),
-        Cell::from("Status"),
-        Cell::from("Version"),
-        Cell::from("Contacted"),
-        Cell::from("IP"),
-    ])
-    .style(
-        Style::default()
-            .fg(Color::Cyan)
-            .add_modifier(Modifier::BOLD),
-    );
-
-    let rows = app.manager_rows.iter().map(|row| {
-        Row::new(vec![
-            Cell::from(row.runner_id.to_string()),
-            Cell::from(row.runner_tags.join(", ")),
-            Cell::from(row.manager.id.to_string()),
-            Cell::from(row.manager.system_id.clone()),
-            Cell::from(row.manager.status.clone()).style(status_style(&row.manager.status)),
-            Cell::from(dash_or(&row.manager.version)),
-            Cell::from(
-                row.manager
-                    .contacted_at
-                    .as_deref()
-                    .unwrap_or("Never")
-                    .to_string(),
-            ),
-            Cell::from(dash_or(&row.manager.ip_address)),
-        ])
-    });
-
-    let table = Table::new(
-        rows,
-        [
-            Constraint::Length(10),     // Runner ID
-            Constraint::Percentage(20), // Tags
-            Constraint::Length(12),     // Manager ID
-            Constraint::Percentage(15), // System ID
-            Constraint::Length(10),     // Status
-            Constraint::Length(10),     // Version
-            Constraint::Length(20),     // Contacted
-            Constraint::Length(15),     // IP
-        ],
-    )
-    .header(header)
-    .highlight_style(Style::default().bg(Color::DarkGray))
-    .block(
-        Block::default()
-            .borders(Borders::ALL)
-            .title(format!("Workers ({} managers)", app.manager_rows.len())),
+fn render_workers_table<S: RunnerSource>(app: &mut App<S>, frame: &mut Frame, area: Rect) {
+    let title = with_query(
+        app,
+        format!("Workers ({} managers)", app.manager_rows.len()),
     );
-
-    frame.render_stateful_widget(table, area, &mut app.table_state);
+    render_table(app, frame, area, title);
 }
 
-fn render_health_check(app: &mut App, frame: &mut Frame, area: Rect) {
+fn render_health_check<S: RunnerSource>(app: &mut App<S>, frame: &mut Frame, area: Rect) {
     // Split area: summary at top, table below
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -266,191 +438,647 @@ fn render_health_check(app: &mut App, frame: &mut Frame, area: Rect) {
     }
 
     // Render runners table in remaining space
-    render_runners_table_impl(
+    let title = with_query(app, format!("Runners ({})", app.runners.len()));
+    render_table(app, frame, chunks[1], title);
+}
+
+fn render_rotation_table<S: RunnerSource>(app: &mut App<S>, frame: &mut Frame, area: Rect) {
+    if app.rotation_rows.is_empty() {
+        let msg = Paragraph::new("  No rotation detected - all runners have a single manager")
+            .style(Style::default().fg(Color::Green))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Rotation Status"),
+            );
+        frame.render_widget(msg, area);
+        return;
+    }
+
+    let flapping = app.rotation_rows.iter().filter(|r| r.flapping).count();
+    let title = with_query(
         app,
-        frame,
-        chunks[1],
-        format!("Runners ({})", app.runners.len()),
+        format!(
+            "Rotating Runners ({} detected, {} flapping) — t: timeline",
+            app.rotation_rows.len(),
+            flapping
+        ),
     );
+    render_table(app, frame, area, title);
 }
 
-fn render_runners_table_impl(app: &mut App, frame: &mut Frame, area: Rect, title: String) {
-    let header = Row::new(vec![
-        Cell::from("ID"),
-        Cell::from("Type"),
-        Cell::from("Status"),
-        Cell::from("Version"),
-        Cell::from("Tags"),
-        Cell::from("Managers"),
-        Cell::from("IP"),
-    ])
-    .style(
-        Style::default()
-            .fg(Color::Cyan)
-            .add_modifier(Modifier::BOLD),
-    );
+fn render_drift<S: RunnerSource>(app: &mut App<S>, frame: &mut Frame, area: Rect) {
+    let Some(report) = app.drift_report.as_ref() else {
+        return;
+    };
 
-    let rows = app.runners.iter().map(|runner| {
-        Row::new(vec![
-            Cell::from(runner.id.to_string()),
-            Cell::from(runner.runner_type.clone()),
-            Cell::from(runner.status.clone()).style(status_style(&runner.status)),
-            Cell::from(dash_or(&runner.version)),
-            Cell::from(runner.tag_list.join(", ")),
-            Cell::from(runner.managers.len().to_string()),
-            Cell::from(dash_or(&runner.ip_address)),
-        ])
-    });
+    let mut lines: Vec<Line> = report
+        .versions
+        .iter()
+        .map(|v| {
+            Line::from(vec![
+                Span::raw(format!("{:<10}", v.version.to_string())),
+                Span::styled(format!("{:<18}", v.lag.to_string()), lag_style(v.lag)),
+                Span::raw(format!(
+                    "{:>5} runners {:>5} managers",
+                    v.runners, v.managers
+                )),
+            ])
+        })
+        .collect();
+    if report.unknown > 0 {
+        lines.push(Line::styled(
+            format!(
+                "{:<28}{:>5} missing or unparseable",
+                "unknown", report.unknown
+            ),
+            Style::default().fg(Color::Gray),
+        ));
+    }
 
-    let table = Table::new(
-        rows,
-        [
-            Constraint::Length(10),     // ID
-            Constraint::Length(15),     // Type
-            Constraint::Length(10),     // Status
-            Constraint::Length(10),     // Version
-            Constraint::Percentage(25), // Tags
-            Constraint::Length(10),     // Managers
-            Constraint::Length(15),     // IP
-        ],
-    )
-    .header(header)
-    .highlight_style(Style::default().bg(Color::DarkGray))
-    .block(Block::default().borders(Borders::ALL).title(title));
+    // Borders plus one line per version, leaving room for the table
+    let height = (lines.len() as u16 + 2).min(area.height / 2).max(3);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(height), Constraint::Min(3)])
+        .split(area);
 
-    frame.render_stateful_widget(table, area, &mut app.table_state);
-}
+    let summary = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Version Drift — target {}", report.target_label())),
+    );
+    frame.render_widget(summary, chunks[0]);
 
-fn render_rotation_table(app: &mut App, frame: &mut Frame, area: Rect) {
     if app.runners.is_empty() {
-        let msg = Paragraph::new("  No rotation detected - all runners have a single manager")
+        let msg = Paragraph::new("  ✓ Every runner is on the target version or newer")
             .style(Style::default().fg(Color::Green))
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .title("Rotation Status"),
+                    .title("Runners to Upgrade"),
             );
-        frame.render_widget(msg, area);
+        frame.render_widget(msg, chunks[1]);
         return;
     }
 
-    let header = Row::new(vec![
-        Cell::from("Runner ID"),
-        Cell::from("Tags"),
-        Cell::from("Mgrs"),
-        Cell::from("Old System"),
-        Cell::from("Old Ver"),
-        Cell::from("Old Status"),
-        Cell::from("New System"),
-        Cell::from("New Ver"),
-        Cell::from("New Status"),
-    ])
+    let title = with_query(app, format!("Runners to Upgrade ({})", app.runners.len()));
+    render_table(app, frame, chunks[1], title);
+}
+
+/// Render the current view's rows, highlighting search matches in each cell
+fn render_coverage<S: RunnerSource>(app: &mut App<S>, frame: &mut Frame, area: Rect) {
+    let uncovered = app.coverage_rows.iter().filter(|c| !c.is_covered()).count();
+    let title = with_query(
+        app,
+        format!(
+            "Tag Coverage ({} sets, {} uncovered)",
+            app.coverage_rows.len(),
+            uncovered
+        ),
+    );
+    render_table(app, frame, area, title);
+}
+
+fn render_pipeline<S: RunnerSource>(app: &mut App<S>, frame: &mut Frame, area: Rect) {
+    let stuck = app.job_rows.iter().filter(|j| j.is_stuck()).count();
+    let title = with_query(
+        app,
+        format!(
+            "Pipeline Jobs ({} jobs, {} would be stuck)",
+            app.job_rows.len(),
+            stuck
+        ),
+    );
+    render_table(app, frame, area, title);
+}
+
+fn render_table<S: RunnerSource>(app: &mut App<S>, frame: &mut Frame, area: Rect, title: String) {
+    let area = if app.search.is_active() {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .split(area);
+        render_search_bar(app, frame, chunks[1]);
+        chunks[0]
+    } else {
+        area
+    };
+
+    let columns = app.columns();
+    // Inner width: the block's left and right borders take one cell each
+    app.set_results_width(area.width.saturating_sub(2));
+    let kept = app.shown_columns();
+    let hidden = columns.len() - kept.len();
+    let title = if hidden > 0 {
+        format!("{} (+{} hidden)", title, hidden)
+    } else {
+        title
+    };
+
+    let sort = app.sort;
+    let header = Row::new(kept.iter().map(|&i| {
+        let header = columns[i].column.header();
+        let text = if sort.column == Some(i) {
+            format!("{} {}", header, sort.indicator())
+        } else {
+            header.to_string()
+        };
+        let style = if i == sort.cursor {
+            Style::default().add_modifier(Modifier::UNDERLINED)
+        } else {
+            Style::default()
+        };
+        Cell::from(text).style(style)
+    }))
     .style(
         Style::default()
             .fg(Color::Cyan)
             .add_modifier(Modifier::BOLD),
     );
 
-    let rows = app.runners.iter().map(|runner| {
-        let mgr_count = runner.managers.len();
+    let max_flaps = app.config.max_manager_flaps;
+    let query = app.search.query.as_str();
+    let rows: Vec<Row> = app
+        .visible_rows()
+        .into_iter()
+        .map(|cells| {
+            Row::new(kept.iter().zip(cells).map(|(&i, text)| {
+                let style = if columns[i].column.is_status() {
+                    status_style(&RunnerStatus::from(text.as_str()))
+                } else if columns[i].column == Column::Compat {
+                    compat_style(&text)
+                } else if columns[i].column == Column::Covered {
+                    covered_style(&text)
+                } else if columns[i].column == Column::Flaps {
+                    flaps_style(&text, max_flaps)
+                } else {
+                    Style::default()
+                };
+                Cell::from(highlight_matches(query, text)).style(style)
+            }))
+        })
+        .collect();
 
-        let mut sorted_mgrs = runner.managers.clone();
-        sorted_mgrs.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    let constraints: Vec<Constraint> = kept
+        .iter()
+        .map(|&i| columns[i].width.constraint())
+        .collect();
+    let table = Table::new(rows, constraints.clone())
+        .header(header)
+        .highlight_style(Style::default().bg(Color::DarkGray))
+        .block(Block::default().borders(Borders::ALL).title(title));
 
-        let oldest = sorted_mgrs.first();
-        let newest = if sorted_mgrs.len() > 1 {
-            sorted_mgrs.last()
-        } else {
-            None
-        };
+    frame.render_stateful_widget(table, area, &mut app.table_state);
+    app.click_areas.table = Some(TableArea::new(
+        area,
+        &kept,
+        constraints,
+        app.table_state.offset(),
+    ));
+}
 
-        let old_system = oldest
-            .map(|m| m.system_id.clone())
-            .unwrap_or_else(|| "-".to_string());
-        let old_ver = oldest
-            .and_then(|m| m.version.clone())
-            .unwrap_or_else(|| "-".to_string());
-        let old_status = oldest
-            .map(|m| m.status.clone())
-            .unwrap_or_else(|| "-".to_string());
-
-        let new_system = newest
-            .map(|m| m.system_id.clone())
-            .unwrap_or_else(|| "-".to_string());
-        let new_ver = newest
-            .and_then(|m| m.version.clone())
-            .unwrap_or_else(|| "-".to_string());
-        let new_status = newest
-            .map(|m| m.status.clone())
-            .unwrap_or_else(|| "-".to_string());
-
-        Row::new(vec![
-            Cell::from(runner.id.to_string()),
-            Cell::from(runner.tag_list.join(", ")),
-            Cell::from(mgr_count.to_string()),
-            Cell::from(old_system),
-            Cell::from(old_ver),
-            Cell::from(old_status.clone()).style(status_style(&old_status)),
-            Cell::from(new_system),
-            Cell::from(new_ver),
-            Cell::from(new_status.clone()).style(status_style(&new_status)),
-        ])
-    });
+/// Column picker panel, drawn over the right-hand side of the results
+fn render_column_picker<S: RunnerSource>(app: &App<S>, frame: &mut Frame, area: Rect) {
+    let Some(picker) = &app.column_picker else {
+        return;
+    };
 
-    let table = Table::new(
-        rows,
-        [
-            Constraint::Length(10),     // Runner ID
-            Constraint::Percentage(15), // Tags
-            Constraint::Length(5),      // Mgrs
-            Constraint::Percentage(12), // Old System
-            Constraint::Length(10),     // Old Ver
-            Constraint::Length(10),     // Old Status
-            Constraint::Percentage(12), // New System
-            Constraint::Length(10),     // New Ver
-            Constraint::Length(10),     // New Status
-        ],
-    )
-    .header(header)
-    .highlight_style(Style::default().bg(Color::DarkGray))
-    .block(
+    let width = area.width.clamp(20, 44);
+    let height = (picker.entries.len() as u16 + 5).min(area.height);
+    let panel = Rect {
+        x: area.x + area.width.saturating_sub(width),
+        y: area.y,
+        width,
+        height,
+    };
+
+    let mut lines: Vec<Line> = picker
+        .entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let marker = if i == picker.selected { ">> " } else { "   " };
+            let check = if entry.enabled { "[x]" } else { "[ ]" };
+            let text = format!(
+                "{}{} {:<12} {}",
+                marker,
+                check,
+                entry.spec.column.header(),
+                entry.spec.width
+            );
+            let style = if i == picker.selected {
+                Style::default().fg(Color::Yellow)
+            } else if entry.enabled {
+                Style::default()
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            Line::from(Span::styled(text, style))
+        })
+        .collect();
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        picker.config_line(),
+        Style::default().fg(Color::Gray),
+    )));
+
+    let paragraph = Paragraph::new(lines)
+        .wrap(ratatui::widgets::Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title(format!(
+            "Columns ({}) — [columns] in config.toml",
+            picker.view.name()
+        )));
+    frame.render_widget(Clear, panel);
+    frame.render_widget(paragraph, panel);
+}
+
+/// Saved presets over the command list, with the selected one's query
+fn render_preset_picker<S: RunnerSource>(app: &App<S>, frame: &mut Frame, area: Rect) {
+    let presets = &app.config.presets;
+    let width = area.width.min(64);
+    let height = (presets.len() as u16 + 5).min(area.height);
+    let panel = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y,
+        width,
+        height,
+    };
+
+    let mut lines: Vec<Line> = presets
+        .iter()
+        .enumerate()
+        .map(|(i, preset)| {
+            let marker = if i == app.selected_preset {
+                ">> "
+            } else {
+                "   "
+            };
+            let text = format!("{}{:<24} {}", marker, preset.name, preset.command);
+            let style = if i == app.selected_preset {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            };
+            Line::from(Span::styled(text, style))
+        })
+        .collect();
+    if let Some(preset) = presets.get(app.selected_preset) {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            preset.summary(),
+            Style::default().fg(Color::Gray),
+        )));
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .wrap(ratatui::widgets::Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title("Presets"));
+    frame.render_widget(Clear, panel);
+    frame.render_widget(paragraph, panel);
+}
+
+/// Command palette at the top of the content area: the query, then the
+/// matching entries with matched characters emphasised
+fn render_palette<S: RunnerSource>(app: &App<S>, frame: &mut Frame, area: Rect) {
+    let Some(palette) = &app.palette else {
+        return;
+    };
+    let width = area.width.min(80);
+    let visible = palette.matches.len().clamp(1, 12);
+    let height = (visible as u16 + 4).min(area.height);
+    let panel = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y,
+        width,
+        height,
+    };
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!("> {}█", palette.query),
+            Style::default().fg(Color::Yellow),
+        )),
+        Line::from(""),
+    ];
+    if palette.matches.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "   no matches",
+            Style::default().fg(Color::Gray),
+        )));
+    }
+    let first = palette.selected.saturating_sub(visible - 1);
+    for (i, entry) in palette.matches.iter().enumerate().skip(first).take(visible) {
+        let selected = i == palette.selected;
+        let mut spans = vec![Span::raw(if selected { ">> " } else { "   " })];
+        let padding = 24usize.saturating_sub(entry.title.chars().count());
+        spans.extend(highlight_matches(&palette.query, entry.title.clone()).spans);
+        spans.push(Span::raw(format!("{} ", " ".repeat(padding))));
+        spans.push(Span::styled(
+            entry.description.clone(),
+            Style::default().fg(Color::Gray),
+        ));
+        let mut line = Line::from(spans);
+        if selected {
+            line = line.style(Style::default().add_modifier(Modifier::REVERSED));
+        }
+        lines.push(line);
+    }
+
+    let paragraph = Paragraph::new(lines).block(
         Block::default()
             .borders(Borders::ALL)
-            .title(format!("Rotating Runners ({} detected)", app.runners.len())),
+            .title("Command palette"),
     );
+    frame.render_widget(Clear, panel);
+    frame.render_widget(paragraph, panel);
+}
 
-    frame.render_stateful_widget(table, area, &mut app.table_state);
+/// Name prompt for saving the current query as a preset
+fn render_save_preset<S: RunnerSource>(app: &App<S>, frame: &mut Frame, area: Rect) {
+    let width = area.width.min(50);
+    let panel = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + area.height.saturating_sub(3) / 2,
+        width,
+        height: 3.min(area.height),
+    };
+    let prompt = Paragraph::new(format!("{}_", app.preset_name)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Save preset as"),
+    );
+    frame.render_widget(Clear, panel);
+    frame.render_widget(prompt, panel);
 }
 
-fn render_help_view(_app: &mut App, frame: &mut Frame, area: Rect) {
-    let help_text = vec![
-        "GitLab Runner TUI - Help",
-        "---------",
-        "",
-        "Navigation:",
-        "  ↑/↓ or k/j    Navigate commands / Scroll results",
-        "  Enter         Select command / Execute search",
-        "  Esc           Back / Cancel",
-        "  ?             Toggle this help",
-        "  q             Quit application",
-        "",
-        "Commands:",
-        "  fetch         Fetch GitLab Runner details",
-        "  lights        Check if runners are online (health check)",
-        "  switch        List runners with offline managers",
-        "  workers       Show runner managers (flattened view)",
-        "  flames        List runners not contacted recently",
-        "  empty         List runners with no managers",
-        "  rotate        Detect runners with multiple managers (rotation)",
-        "",
-        "Polling (in results view):",
-        "  p             Toggle auto-refresh polling",
-        "",
-        "Filter (in filter mode):",
-        "  Tags          Comma-separated tags (e.g., alm,prod)",
-        "",
-        "Press any key to close help",
+/// Latest toast in the bottom-right corner of the content area
+fn render_toast<S: RunnerSource>(app: &App<S>, frame: &mut Frame, area: Rect) {
+    let Some(toast) = &app.toast else {
+        return;
+    };
+    let len = toast.message.chars().count();
+    let width = (len.clamp(16, 76) as u16 + 4).min(area.width);
+    let inner = width.saturating_sub(2).max(1) as usize;
+    let height = (len.div_ceil(inner) as u16 + 2).min(area.height);
+    let panel = Rect {
+        x: area.right().saturating_sub(width + 1).max(area.x),
+        y: area.bottom().saturating_sub(height + 1).max(area.y),
+        width,
+        height,
+    };
+    let (title, color) = if toast.is_error {
+        ("Error", Color::Red)
+    } else {
+        ("Notice", Color::Green)
+    };
+    let paragraph = Paragraph::new(toast.message.as_str())
+        .wrap(ratatui::widgets::Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(color))
+                .title(title),
+        );
+    frame.render_widget(Clear, panel);
+    frame.render_widget(paragraph, panel);
+}
+
+/// Manager timeline of one runner, newest event first, drawn over the results
+fn render_timeline<S: RunnerSource>(app: &App<S>, frame: &mut Frame, area: Rect) {
+    let Some(runner_id) = app.timeline_runner else {
+        return;
+    };
+
+    let width = area.width.clamp(20, 72);
+    let panel = Rect {
+        x: area.x + area.width.saturating_sub(width),
+        y: area.y,
+        width,
+        height: area.height,
+    };
+
+    let events = app.manager_tracker.timeline(runner_id);
+    let lines: Vec<Line> = if events.is_empty() {
+        vec![Line::from("  No manager changes recorded yet")]
+    } else {
+        events
+            .iter()
+            .rev()
+            .map(|event| {
+                let style = match event.kind {
+                    ManagerEventKind::WentOffline | ManagerEventKind::Disappeared => {
+                        Style::default().fg(Color::Red)
+                    }
+                    ManagerEventKind::Returned => Style::default().fg(Color::Yellow),
+                    _ => Style::default().fg(Color::Green),
+                };
+                Line::from(vec![
+                    Span::styled(
+                        event.at.format("%H:%M:%S ").to_string(),
+                        Style::default().fg(Color::Gray),
+                    ),
+                    Span::raw(format!("{:<28} ", event.system_id)),
+                    Span::styled(event.kind.to_string(), style),
+                ])
+            })
+            .collect()
+    };
+
+    let policy = app.manager_tracker.policy();
+    let flaps = app.manager_tracker.flaps(runner_id, Utc::now());
+    let title = format!(
+        "Runner {} — {} flaps in {}m (limit {})",
+        runner_id,
+        flaps,
+        policy.window.num_minutes(),
+        policy.max_flaps
+    );
+    let border = if flaps > policy.max_flaps {
+        Style::default().fg(Color::Red)
+    } else {
+        Style::default()
+    };
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_style(border),
+    );
+    frame.render_widget(Clear, panel);
+    frame.render_widget(paragraph, panel);
+}
+
+/// Alerts from the configured rules: firing, then pending, then recently
+/// resolved
+fn render_alerts<S: RunnerSource>(app: &App<S>, frame: &mut Frame, area: Rect) {
+    let alerts = app.alerts.alerts();
+    let lines: Vec<Line> = if !app.alerts.has_rules() {
+        vec![Line::from(
+            "  No alert rules configured, see [[alerts]] in config.toml",
+        )]
+    } else if alerts.is_empty() {
+        vec![Line::from(Span::styled(
+            "  ✓ No alerts",
+            Style::default().fg(Color::Green),
+        ))]
+    } else {
+        alerts
+            .iter()
+            .map(|alert| {
+                let state_style = match alert.state {
+                    AlertState::Firing => Style::default().fg(Color::Red),
+                    AlertState::Pending => Style::default().fg(Color::Yellow),
+                    AlertState::Resolved => Style::default().fg(Color::Green),
+                };
+                Line::from(vec![
+                    Span::styled(format!("{:<9}", alert.state), state_style),
+                    Span::styled(
+                        format!("{:<9}", alert.severity),
+                        severity_style(alert.severity),
+                    ),
+                    Span::raw(format!("{:<24} ", alert.rule)),
+                    Span::raw(format!(
+                        "Runner {} {}",
+                        alert.runner_id,
+                        alert.description.as_deref().unwrap_or("")
+                    )),
+                    Span::styled(
+                        format!("  since {}", alert.since.format("%H:%M:%S")),
+                        Style::default().fg(Color::Gray),
+                    ),
+                ])
+            })
+            .collect()
+    };
+
+    let firing = app.alerts.firing();
+    let border = if firing > 0 {
+        Style::default().fg(Color::Red)
+    } else {
+        Style::default()
+    };
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Alerts ({} firing) — a: hide", firing))
+            .border_style(border),
+    );
+    frame.render_widget(paragraph, area);
+}
+
+/// Split a cell into spans, emphasising characters matched by the search query
+fn highlight_matches(query: &str, text: String) -> Line<'static> {
+    let Some(indices) = fuzzy_indices(query, &text) else {
+        return Line::from(text);
+    };
+
+    let matched = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+    let mut spans = Vec::new();
+    let mut current = String::new();
+    let mut current_matched = false;
+
+    for (i, c) in text.chars().enumerate() {
+        let is_match = indices.contains(&i);
+        if is_match != current_matched && !current.is_empty() {
+            let style = if current_matched {
+                matched
+            } else {
+                Style::default()
+            };
+            spans.push(Span::styled(std::mem::take(&mut current), style));
+        }
+        current_matched = is_match;
+        current.push(c);
+    }
+    if !current.is_empty() {
+        let style = if current_matched {
+            matched
+        } else {
+            Style::default()
+        };
+        spans.push(Span::styled(current, style));
+    }
+
+    Line::from(spans)
+}
+
+fn render_search_bar<S: RunnerSource>(app: &App<S>, frame: &mut Frame, area: Rect) {
+    let search = &app.search;
+    let cursor = if search.editing { "█" } else { "" };
+    let summary = match (
+        search.matches.len(),
+        search.position_of(app.table_state.selected()),
+    ) {
+        (0, _) if !search.query.is_empty() => "  (no matches)".to_string(),
+        (0, _) => String::new(),
+        (total, Some(position)) => format!("  ({}/{} matches)", position, total),
+        (total, None) => format!("  ({} matches)", total),
+    };
+
+    let bar = Paragraph::new(Line::from(vec![
+        Span::styled(
+            format!("/{}{}", search.query, cursor),
+            Style::default().fg(Color::Yellow),
+        ),
+        Span::styled(summary, Style::default().fg(Color::Gray)),
+    ]));
+    frame.render_widget(bar, area);
+}
+
+/// Help generated from the active keymap, then the commands and the
+/// (fixed) filter form keys
+fn render_help_view<S: RunnerSource>(app: &mut App<S>, frame: &mut Frame, area: Rect) {
+    let mut help_text = vec![
+        "GitLab Runner TUI - Help".to_string(),
+        "---------".to_string(),
     ];
+    for (scope, title) in [
+        (Scope::Global, "Navigation:"),
+        (Scope::Commands, "Command list:"),
+        (Scope::Results, "Results view:"),
+    ] {
+        help_text.push(String::new());
+        help_text.push(title.to_string());
+        for action in Action::ALL.iter().filter(|a| a.scope() == scope) {
+            help_text.push(format!(
+                "  {:<14}{}",
+                app.keymap.describe(*action),
+                action.description()
+            ));
+        }
+    }
+
+    help_text.push(String::new());
+    help_text.push("Commands:".to_string());
+    for command in Command::ALL {
+        help_text.push(format!(
+            "  {:<14}{}",
+            command.to_string(),
+            command.description()
+        ));
+    }
+
+    help_text.extend(
+        [
+            "",
+            "Filter (in filter mode):",
+            "  Tab / ↑↓      Move between fields",
+            "  ←/→ / Space   Cycle status, type and paused options",
+            "  Tags          Comma-separated tags (e.g., alm,prod)",
+            "  Version       Version prefix (e.g., 17.5)",
+            "  Where         Client-side expression, e.g.",
+            "                  status = online and version < 17.0",
+            "                  managers > 1 and platform = windows",
+            "                  description matches ^prod-",
+            "",
+            "Keys are set in [keys] of config.toml",
+            "Press any key to close help",
+        ]
+        .map(String::from),
+    );
 
     let items: Vec<ListItem> = help_text.into_iter().map(ListItem::new).collect();
     let list = List::new(items).block(Block::default().borders(Borders::ALL).title("Help"));